@@ -0,0 +1,178 @@
+//! Conformance test runner for other Aegis implementations
+
+use crate::error::Result;
+use crate::utils::conformance::{
+    load_cases, reference_response, run_cases, RequirementReport, BUILTIN_VECTORS,
+};
+use anyhow::{anyhow, bail, Context};
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+#[derive(Debug, Serialize)]
+pub struct ConformanceOutput {
+    implementation: String,
+    compliant: bool,
+    requirements: Vec<RequirementReport>,
+}
+
+/// Implementation under test, speaking one JSON line per request
+struct Subprocess {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    lines: Receiver<String>,
+    timeout: Duration,
+    failed: Option<String>,
+}
+
+impl Subprocess {
+    fn spawn(command: &[String], timeout: Duration) -> Result<Self> {
+        let (program, args) = command
+            .split_first()
+            .ok_or_else(|| anyhow!("No implementation command given"))?;
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to start implementation: {}", program))?;
+        let stdout = child
+            .stdout
+            .take()
+            .context("Implementation has no stdout")?;
+        let stdin = child.stdin.take();
+
+        // Read on a separate thread so a hung implementation times out
+        let (sender, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(|line| line.ok()) {
+                if !line.trim().is_empty() && sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            child,
+            stdin,
+            lines,
+            timeout,
+            failed: None,
+        })
+    }
+
+    fn call(&mut self, request: &Value) -> Result<Value> {
+        if let Some(reason) = &self.failed {
+            bail!("{}", reason);
+        }
+        let stdin = self.stdin.as_mut().context("Implementation stdin closed")?;
+        let sent = writeln!(stdin, "{}", request).and_then(|_| stdin.flush());
+        let line = match (sent, self.lines.recv_timeout(self.timeout)) {
+            (Ok(()), Ok(line)) => line,
+            (_, Err(RecvTimeoutError::Timeout)) => {
+                return Err(self.fail(format!("no response within {:?}", self.timeout)))
+            }
+            _ => return Err(self.fail("implementation exited".to_string())),
+        };
+        serde_json::from_str(&line).with_context(|| format!("response is not JSON: {}", line))
+    }
+
+    /// Stop sending requests after a fatal error
+    fn fail(&mut self, reason: String) -> anyhow::Error {
+        self.failed = Some(reason.clone());
+        anyhow!(reason)
+    }
+}
+
+impl Drop for Subprocess {
+    fn drop(&mut self) {
+        // Closing stdin asks the implementation to exit
+        self.stdin.take();
+        if self.failed.is_some() {
+            let _ = self.child.kill();
+        }
+        let _ = self.child.wait();
+    }
+}
+
+pub fn run(
+    command: Vec<String>,
+    vectors_path: Option<PathBuf>,
+    timeout: u64,
+    json: bool,
+) -> Result<()> {
+    let vectors = match &vectors_path {
+        Some(path) => fs::read_to_string(path)
+            .with_context(|| format!("Failed to read test vectors: {}", path.display()))?,
+        None => BUILTIN_VECTORS.to_string(),
+    };
+    let cases = load_cases(&vectors)?;
+
+    let mut implementation = Subprocess::spawn(&command, Duration::from_secs(timeout))?;
+    let requirements = run_cases(&cases, |request| implementation.call(request));
+    drop(implementation);
+
+    let compliant = requirements.iter().all(RequirementReport::is_met);
+    let implementation = command.join(" ");
+
+    if json {
+        let output = ConformanceOutput {
+            implementation,
+            compliant,
+            requirements,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        println!("Aegis CRM Standard v1.0 conformance: {}\n", implementation);
+        for report in &requirements {
+            let icon = if report.is_met() { "✅" } else { "❌" };
+            println!(
+                "{} {:<32} {}/{}",
+                icon, report.title, report.passed, report.total
+            );
+            for failure in &report.failures {
+                println!("     - {}: {}", failure.case, failure.detail);
+            }
+        }
+        let met = requirements.iter().filter(|r| r.is_met()).count();
+        println!();
+        if compliant {
+            println!("✅ COMPLIANT ({}/{} requirements)", met, requirements.len());
+        } else {
+            println!(
+                "❌ NOT COMPLIANT ({}/{} requirements)",
+                met,
+                requirements.len()
+            );
+        }
+    }
+
+    if !compliant {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Answer protocol requests on stdin with `aegis-crm-core`
+pub fn serve() -> Result<()> {
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    for line in std::io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str(&line) {
+            Ok(request) => reference_response(&request),
+            Err(err) => serde_json::json!({ "error": format!("invalid JSON: {}", err) }),
+        };
+        writeln!(out, "{}", response)?;
+        out.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,55 @@
+//! Certificate format conversion command
+
+use crate::error::Result;
+use crate::utils::cert::{read_cert, CertFormat};
+use crate::utils::io::{check_overwrite, read_private_key};
+use anyhow::Context;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Serialize)]
+pub struct ConvertOutput {
+    from: String,
+    to: String,
+    cert_path: String,
+    out_path: String,
+}
+
+pub fn run(
+    cert_path: PathBuf,
+    from: Option<CertFormat>,
+    to: CertFormat,
+    vendor_priv: String,
+    out_path: PathBuf,
+    force: bool,
+    json: bool,
+) -> Result<()> {
+    check_overwrite(&out_path, force)?;
+
+    let cert = read_cert(&cert_path, from, &[])?;
+    let from = cert.format();
+
+    // Each format signs different bytes, so conversion re-signs with the vendor key
+    let vendor_key = read_private_key(&vendor_priv).context("Failed to read vendor private key")?;
+    let encoded = cert.encode(to, &vendor_key)?;
+
+    fs::write(&out_path, encoded)
+        .with_context(|| format!("Failed to write certificate: {}", out_path.display()))?;
+
+    if json {
+        let output = ConvertOutput {
+            from: from.to_string(),
+            to: to.to_string(),
+            cert_path: cert_path.display().to_string(),
+            out_path: out_path.display().to_string(),
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        println!("✅ Certificate converted: {} → {}", from, to);
+        println!("   Input:  {}", cert_path.display());
+        println!("   Output: {}", out_path.display());
+    }
+
+    Ok(())
+}
@@ -1,17 +1,40 @@
 //! Certificate inspection command
 
 use crate::error::Result;
-use crate::utils::io::read_hex_or_file;
-use aegis_crm_core::cert::{decode_cert, verify_cert};
-use anyhow::Context;
+use crate::utils::cert::{native_cert_bytes, read_cert, read_cert_file, AnyCert, CertFormat};
+use crate::utils::io::{read_pq_public_key, read_public_key};
+use crate::utils::payload::{load_catalog, MetadataJson, MetadataValueJson};
+use crate::utils::qr::{check_qr_target, write_qr};
+use aegis_crm_core::cert::{encode_cert, verify_cert_hybrid, Entitlement, FeatureGrant};
+use aegis_crm_core::clock::{Clock, FixedClock};
+use aegis_crm_core::keys::PublicKey;
+use aegis_crm_core::pq::PqPublicKey;
+use aegis_crm_core::uri::license_uri;
+use aegis_crm_core::verify::{CheckResult, CheckStatus, RevocationList, Verifier};
+use aegis_crm_core::AegisError;
+use anyhow::{bail, Context};
 use chrono::{DateTime, Utc};
 use serde::Serialize;
-use std::fs;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
+#[derive(Debug, Serialize)]
+pub struct FeatureOutput {
+    name: String,
+    not_before: Option<u64>,
+    expiry: Option<u64>,
+    active: bool,
+}
+
 #[derive(Debug, Serialize)]
 pub struct InspectOutput {
     status: String,
+    format: String,
+    alg: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pq_alg: Option<String>,
+    /// Whether the PQ signature was checked (`--vendor-pq-pub`)
+    pq_verified: bool,
     tier: String,
     product_id: String,
     license_id: String,
@@ -19,72 +42,208 @@ pub struct InspectOutput {
     issued_at_formatted: String,
     expiry: Option<u64>,
     expiry_formatted: Option<String>,
-    features: Vec<String>,
+    features: Vec<FeatureOutput>,
     features_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    resolved_features: Option<Vec<String>>,
+    entitlements: BTreeMap<String, Entitlement>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<MetadataJson>,
     user_pubkey: String,
+    user_alg: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExplainOutput {
+    cert_path: String,
+    valid: bool,
+    error_code: Option<&'static str>,
+    checks: Vec<CheckResult>,
+}
+
+/// Verifier configuration of `aegis inspect --explain`
+pub struct ExplainArgs {
+    pub product: Option<String>,
+    /// Challenge nonce and PoP signature (hex)
+    pub pop: Option<(String, String)>,
+    /// Revoked license IDs (hex)
+    pub revoked: Vec<String>,
+    pub last_online: Option<u64>,
 }
 
-pub fn run(cert_path: PathBuf, vendor_pub: String, json: bool) -> Result<()> {
+/// Arguments of `aegis inspect`
+pub struct InspectArgs {
+    pub cert: PathBuf,
+    pub vendor_pub: String,
+    pub vendor_pq_pub: Option<String>,
+    pub catalog: Option<PathBuf>,
+    /// QR code PNG path, or `-` for the terminal
+    pub qr: Option<PathBuf>,
+    /// Put an `aegis:license` URI in the QR code instead of the raw certificate
+    pub qr_uri: bool,
+    /// Report every verification check instead of the certificate contents
+    pub explain: Option<ExplainArgs>,
+    pub json: bool,
+    /// Verification time source (`--now` overrides the system clock)
+    pub clock: Box<dyn Clock>,
+}
+
+pub fn run(args: InspectArgs) -> Result<()> {
+    let InspectArgs {
+        cert: cert_path,
+        vendor_pub,
+        vendor_pq_pub,
+        catalog: catalog_path,
+        qr,
+        qr_uri,
+        explain,
+        json,
+        clock,
+    } = args;
+    check_qr_target(qr.as_deref(), json)?;
+
+    // Load the catalog first; its bit table is needed to decode license keys
+    let catalog = load_catalog(catalog_path.as_deref())?;
+
+    if let Some(explain) = explain {
+        let bytes = native_cert_bytes(&read_cert_file(&cert_path)?, &catalog.bits)?;
+        let vendor_key =
+            read_public_key(&vendor_pub).context("Failed to read vendor public key")?;
+        let vendor_pq_key = vendor_pq_pub
+            .as_deref()
+            .map(read_pq_public_key)
+            .transpose()
+            .context("Failed to read vendor PQ public key")?;
+        let verifier = explain_verifier(vendor_key, vendor_pq_key, clock.now_unix()?, &explain)?;
+        let pop = explain
+            .pop
+            .as_ref()
+            .map(|(nonce, sig)| -> Result<_> {
+                Ok((
+                    parse_hex::<32>(nonce, "nonce")?,
+                    parse_hex::<64>(sig, "PoP signature")?,
+                ))
+            })
+            .transpose()?;
+        let report = verifier.verify_report(&bytes, pop.as_ref().map(|(n, s)| (n, s)));
+        let error_code = report.first_failure().and_then(|c| c.code);
+
+        if json {
+            let output = ExplainOutput {
+                cert_path: cert_path.display().to_string(),
+                valid: report.is_valid(),
+                error_code,
+                checks: report.checks,
+            };
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        } else {
+            println!("\nCertificate: {}", cert_path.display());
+            for check in &report.checks {
+                println!("{}", format_check(check));
+            }
+            match error_code {
+                None => println!("Result: ✅ VALID\n"),
+                Some(code) => println!("Result: ❌ INVALID ({})\n", code),
+            }
+        }
+        return Ok(());
+    }
+
     // Load certificate
-    let cert_bytes = fs::read(&cert_path)
-        .with_context(|| format!("Failed to read certificate: {}", cert_path.display()))?;
+    let cert = read_cert(&cert_path, None, &catalog.bits)?;
+    let payload = cert.payload();
 
-    let cert = decode_cert(&cert_bytes).context("Failed to decode certificate")?;
+    // Re-export the certificate as a QR code
+    if let Some(target) = &qr {
+        let AnyCert::Native(native) = &cert else {
+            bail!(
+                "QR export needs a native certificate; convert it with `aegis convert --to native`"
+            );
+        };
+        let content = if qr_uri {
+            license_uri(native)?.into_bytes()
+        } else {
+            encode_cert(native)?
+        };
+        write_qr(&content, target)?;
+    }
 
     // Load vendor public key
-    let vendor_pub_bytes =
-        read_hex_or_file(&vendor_pub).context("Failed to read vendor public key")?;
-    if vendor_pub_bytes.len() != 33 {
-        anyhow::bail!(
-            "Vendor public key must be 33 bytes, got {}",
-            vendor_pub_bytes.len()
-        );
-    }
-    let mut vendor_pubkey = [0u8; 33];
-    vendor_pubkey.copy_from_slice(&vendor_pub_bytes);
+    let vendor_key = read_public_key(&vendor_pub).context("Failed to read vendor public key")?;
+
+    // Load vendor PQ public key (hybrid mode)
+    let vendor_pq_key = vendor_pq_pub
+        .as_deref()
+        .map(read_pq_public_key)
+        .transpose()
+        .context("Failed to read vendor PQ public key")?;
 
     // Get current timestamp
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)?
-        .as_secs();
+    let now = clock.now_unix()?;
 
     // Verify certificate
-    let verification_result = verify_cert(&vendor_pubkey, &cert, now);
+    let verification_result = match (&vendor_pq_key, &cert) {
+        (Some(pq_key), AnyCert::Native(native)) => {
+            verify_cert_hybrid(&vendor_key, pq_key, native, now)
+        }
+        // COSE_Sign1 and JWS carry a single signature, so they can never be hybrid
+        (Some(_), AnyCert::Cose(_) | AnyCert::Jwt(_)) => Err(AegisError::CertSignature),
+        (None, _) => cert.verify(&vendor_key, now),
+    };
     let status = if verification_result.is_ok() {
         "VALID"
-    } else if matches!(
-        verification_result,
-        Err(aegis_crm_core::AegisError::CertExpired)
-    ) {
+    } else if matches!(verification_result, Err(AegisError::CertExpired)) {
         "EXPIRED"
     } else {
         "INVALID"
     };
 
+    // Resolve implied features against the catalog, if given
+    let resolved_features = catalog_path.as_ref().map(|_| {
+        let resolved = payload.resolve_features(&catalog, now);
+        resolved.iter().map(String::from).collect::<Vec<_>>()
+    });
+
     // Format timestamps
-    let issued_at_dt = DateTime::from_timestamp(cert.payload.issued_at as i64, 0)
-        .unwrap_or(DateTime::<Utc>::MIN_UTC);
-    let issued_at_formatted = issued_at_dt.format("%Y-%m-%d %H:%M:%S UTC").to_string();
+    let issued_at_formatted = format_timestamp(payload.issued_at);
 
-    let expiry_formatted = cert.payload.expiry.map(|exp| {
-        let exp_dt = DateTime::from_timestamp(exp as i64, 0).unwrap_or(DateTime::<Utc>::MIN_UTC);
-        exp_dt.format("%Y-%m-%d %H:%M:%S UTC").to_string()
-    });
+    let expiry_formatted = payload.expiry.map(format_timestamp);
 
     // Output results
     if json {
         let output = InspectOutput {
             status: status.to_string(),
-            tier: cert.payload.tier.clone(),
-            product_id: cert.payload.product_id.clone(),
-            license_id: hex::encode(cert.payload.license_id),
-            issued_at: cert.payload.issued_at,
+            format: cert.format().to_string(),
+            alg: payload.alg.to_string(),
+            pq_alg: payload.pq_alg.map(|alg| alg.to_string()),
+            pq_verified: vendor_pq_key.is_some(),
+            tier: payload.tier.clone(),
+            product_id: payload.product_id.clone(),
+            license_id: hex::encode(payload.license_id),
+            issued_at: payload.issued_at,
             issued_at_formatted,
-            expiry: cert.payload.expiry,
+            expiry: payload.expiry,
             expiry_formatted,
-            features: cert.payload.features.clone(),
-            features_count: cert.payload.features.len(),
-            user_pubkey: hex::encode(cert.payload.user_pubkey),
+            features: payload
+                .features
+                .iter()
+                .map(|f| FeatureOutput {
+                    name: f.name.clone(),
+                    not_before: f.not_before,
+                    expiry: f.expiry,
+                    active: f.is_active_at(now),
+                })
+                .collect(),
+            features_count: payload.features.len(),
+            resolved_features,
+            entitlements: payload.entitlements.clone(),
+            metadata: payload.metadata.as_ref().map(|m| {
+                m.iter()
+                    .map(|(k, v)| (k.to_string(), MetadataValueJson::from(v)))
+                    .collect()
+            }),
+            user_pubkey: hex::encode(&payload.user_pubkey),
+            user_alg: payload.user_alg.to_string(),
         };
         println!("{}", serde_json::to_string_pretty(&output)?);
     } else {
@@ -97,13 +256,32 @@ pub fn run(cert_path: PathBuf, vendor_pub: String, json: bool) -> Result<()> {
             _ => "❌",
         };
         println!("Status: {} {}", status_icon, status);
+        match cert.format() {
+            CertFormat::Native => {}
+            CertFormat::Cose => println!("Format: COSE_Sign1"),
+            CertFormat::Jwt => println!("Format: JWS (JWT)"),
+        }
 
-        println!("Tier: {}", cert.payload.tier);
-        println!("Product: {}", cert.payload.product_id);
-        println!(
-            "License ID: {}...",
-            hex::encode(&cert.payload.license_id[..8])
-        );
+        println!("Tier: {}", payload.tier);
+        match payload.pq_alg {
+            Some(pq_alg) => {
+                let checked = if vendor_pq_key.is_some() {
+                    "verified"
+                } else {
+                    "not checked, pass --vendor-pq-pub"
+                };
+                println!(
+                    "Algorithm: {} + {} (hybrid, PQ signature {})",
+                    payload.alg, pq_alg, checked
+                );
+            }
+            None if !payload.alg.is_default() => {
+                println!("Algorithm: {}", payload.alg);
+            }
+            None => {}
+        }
+        println!("Product: {}", payload.product_id);
+        println!("License ID: {}...", hex::encode(&payload.license_id[..8]));
         println!("Issued: {}", issued_at_formatted);
 
         if let Some(exp_str) = expiry_formatted {
@@ -112,20 +290,23 @@ pub fn run(cert_path: PathBuf, vendor_pub: String, json: bool) -> Result<()> {
             println!("Expires: Never (perpetual)");
         }
 
-        let feature_count = cert.payload.features.len();
-        if cert.payload.features.contains(&"ALL".to_string()) {
+        let feature_count = payload.features.len();
+        let windowed = payload.features.iter().any(FeatureGrant::is_windowed);
+        if payload.features.iter().any(|f| f.name == "ALL") && !windowed {
             println!("Features: ALL (wildcard - unlimited access)");
+        } else if windowed {
+            println!("Features: {}", feature_count);
+            for feature in &payload.features {
+                println!("  - {}", format_feature(feature, now));
+            }
         } else if feature_count > 0 {
-            println!(
-                "Features: {} ({})",
-                feature_count,
-                cert.payload.features.join(", ")
-            );
+            let names: Vec<&str> = payload.features.iter().map(|f| f.name.as_str()).collect();
+            println!("Features: {} ({})", feature_count, names.join(", "));
         } else {
             println!("Features: None");
         }
 
-        if let Some(limits) = &cert.payload.limits {
+        if let Some(limits) = &payload.limits {
             if let Some(seats) = limits.seat_max {
                 println!("Seat Limit: {}", seats);
             }
@@ -134,25 +315,115 @@ pub fn run(cert_path: PathBuf, vendor_pub: String, json: bool) -> Result<()> {
             }
         }
 
-        if let Some(meta) = &cert.payload.metadata {
-            println!("Metadata:");
-            if let Some(product) = &meta.product {
-                println!("  - Product: {}", product);
-            }
-            if let Some(version) = &meta.version {
-                println!("  - Version: {}", version);
+        if let Some(resolved) = &resolved_features {
+            println!("Effective Features: {}", resolved.join(", "));
+        }
+
+        if !payload.entitlements.is_empty() {
+            println!("Entitlements:");
+            for (name, value) in &payload.entitlements {
+                println!("  - {}: {}", name, value);
             }
-            if let Some(university) = &meta.university {
-                println!("  - University: {}", university);
+        }
+
+        if let Some(meta) = &payload.metadata {
+            println!("Metadata:");
+            for (key, value) in meta.iter() {
+                println!("  - {}: {}", key, value);
             }
         }
 
-        println!(
-            "User Public Key: {}...",
-            hex::encode(&cert.payload.user_pubkey[..8])
-        );
+        let user_key_prefix = &payload.user_pubkey[..payload.user_pubkey.len().min(8)];
+        if payload.user_alg.is_default() {
+            println!("User Public Key: {}...", hex::encode(user_key_prefix));
+        } else {
+            println!(
+                "User Public Key: {}:{}...",
+                payload.user_alg,
+                hex::encode(user_key_prefix)
+            );
+        }
         println!();
     }
 
     Ok(())
 }
+
+/// Verifier at a fixed time with the `--explain` options
+fn explain_verifier(
+    vendor_key: PublicKey,
+    vendor_pq_key: Option<PqPublicKey>,
+    now: u64,
+    explain: &ExplainArgs,
+) -> Result<Verifier> {
+    let mut builder = Verifier::builder()
+        .trust(vendor_key)
+        .clock(FixedClock::new(now));
+    if let Some(pq_key) = vendor_pq_key {
+        builder = builder.trust_pq(pq_key);
+    }
+    if let Some(product) = &explain.product {
+        builder = builder.product(product);
+    }
+    if !explain.revoked.is_empty() {
+        let mut revocations = RevocationList::new();
+        for lid in &explain.revoked {
+            revocations.revoke(parse_hex::<32>(lid, "revoked license ID")?);
+        }
+        builder = builder.revocations(revocations);
+    }
+    if let Some(last_online) = explain.last_online {
+        builder = builder.last_online(last_online);
+    }
+    Ok(builder.build()?)
+}
+
+/// Parse a fixed-length hex argument
+fn parse_hex<const N: usize>(input: &str, what: &str) -> Result<[u8; N]> {
+    let bytes = hex::decode(input.trim()).with_context(|| format!("Invalid {} hex", what))?;
+    <[u8; N]>::try_from(bytes.as_slice())
+        .map_err(|_| anyhow::anyhow!("Expected {} bytes for {}, got {}", N, what, bytes.len()))
+}
+
+/// Format one verification check as a line of `--explain` output
+fn format_check(check: &CheckResult) -> String {
+    let icon = match check.status {
+        CheckStatus::Pass => "✅",
+        CheckStatus::Fail if check.code.is_none() => "⚠️",
+        CheckStatus::Fail => "❌",
+        CheckStatus::Skip => "⏭️",
+    };
+    match check.code {
+        Some(code) => format!("{} {:<11} {} [{}]", icon, check.check, check.detail, code),
+        None => format!("{} {:<11} {}", icon, check.check, check.detail),
+    }
+}
+
+/// Format a Unix timestamp for display
+fn format_timestamp(ts: u64) -> String {
+    DateTime::from_timestamp(ts as i64, 0)
+        .unwrap_or(DateTime::<Utc>::MIN_UTC)
+        .format("%Y-%m-%d %H:%M:%S UTC")
+        .to_string()
+}
+
+/// Format a feature with its validity window and current state
+fn format_feature(feature: &FeatureGrant, now: u64) -> String {
+    if !feature.is_windowed() {
+        return feature.name.clone();
+    }
+    let from = feature
+        .not_before
+        .map(format_timestamp)
+        .unwrap_or_else(|| "issuance".to_string());
+    let until = feature
+        .expiry
+        .map(format_timestamp)
+        .unwrap_or_else(|| "certificate expiry".to_string());
+    let state = if feature.is_active_at(now) {
+        "active"
+    } else {
+        "inactive"
+    };
+    format!("{} ({} → {}, {})", feature.name, from, until, state)
+}
@@ -1,13 +1,18 @@
 //! Certificate issuance command
 
 use crate::error::Result;
-use crate::utils::io::{check_overwrite, read_hex_or_file};
-use crate::utils::payload::{LicensePayloadJson, LimitsJson, MetadataJson};
+use crate::utils::io::{check_overwrite, read_pq_private_key, read_private_key, read_public_key};
+use crate::utils::payload::{load_catalog, load_policy, FeatureJson, LicensePayloadJson};
+use crate::utils::qr::{check_qr_target, write_qr};
+use aegis_crm_core::armor::encode_cert_armored;
 use aegis_crm_core::cert::{
-    encode_cert, issue_cert, LicensePayload, Limits, Metadata, PROTOCOL_VERSION,
+    encode_cert, encode_cert_key, issue_cert_hybrid, issue_cert_with_policy,
 };
+use aegis_crm_core::clock::Clock;
+use aegis_crm_core::uri::license_uri;
 use anyhow::Context;
 use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::ValueEnum;
 use getrandom::getrandom;
 use serde::Serialize;
 use std::fs;
@@ -16,48 +21,99 @@ use std::path::PathBuf;
 #[derive(Debug, Serialize)]
 pub struct IssueOutput {
     license_id: String,
+    alg: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pq_alg: Option<String>,
     tier: String,
     expires_at: Option<u64>,
     features_count: usize,
     cert_path: String,
-    cert_base64_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cert_base64_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cert_armor_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    license_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    qr_path: Option<String>,
 }
 
-pub fn run(
-    vendor_priv_path: String,
-    user_pub: String,
-    payload_path: PathBuf,
-    out_path: PathBuf,
-    force: bool,
-    json: bool,
-) -> Result<()> {
+/// Output of `aegis issue`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum IssueFormat {
+    /// CBOR certificate plus base64 and ASCII-armored copies
+    Cert,
+    /// Typable license key (grouped Crockford base32) in a text file
+    Key,
+}
+
+/// Arguments of `aegis issue`
+pub struct IssueArgs {
+    pub vendor_priv: String,
+    pub vendor_pq_priv: Option<String>,
+    pub user_pub: String,
+    pub payload: PathBuf,
+    pub policy: Option<PathBuf>,
+    pub format: IssueFormat,
+    pub catalog: Option<PathBuf>,
+    pub out: PathBuf,
+    /// QR code PNG path, or `-` for the terminal
+    pub qr: Option<PathBuf>,
+    /// Put an `aegis:license` URI in the QR code instead of the raw output
+    pub qr_uri: bool,
+    pub force: bool,
+    pub json: bool,
+    /// Issuance time source (`--now` overrides the system clock)
+    pub clock: Box<dyn Clock>,
+}
+
+pub fn run(args: IssueArgs) -> Result<()> {
+    let IssueArgs {
+        vendor_priv: vendor_priv_path,
+        vendor_pq_priv: vendor_pq_priv_path,
+        user_pub,
+        payload: payload_path,
+        policy: policy_path,
+        format,
+        catalog: catalog_path,
+        out: out_path,
+        qr: qr_path,
+        qr_uri,
+        force,
+        json,
+        clock,
+    } = args;
+
     // Check output paths
     check_overwrite(&out_path, force)?;
-    let base64_path = out_path.with_extension("cert.base64");
-    check_overwrite(&base64_path, force)?;
+    check_qr_target(qr_path.as_deref(), json)?;
+    // Terminal QR codes are not files
+    let qr_file = qr_path.clone().filter(|path| path.as_os_str() != "-");
+    let (base64_path, armor_path) = match format {
+        IssueFormat::Cert => (
+            Some(out_path.with_extension("cert.base64")),
+            Some(out_path.with_extension("cert.asc")),
+        ),
+        IssueFormat::Key => (None, None),
+    };
+    for path in base64_path.iter().chain(&armor_path).chain(&qr_file) {
+        check_overwrite(path, force)?;
+    }
+    let catalog = load_catalog(catalog_path.as_deref())?;
 
     // Load vendor private key
-    let vendor_priv_bytes =
-        read_hex_or_file(&vendor_priv_path).context("Failed to read vendor private key")?;
-    if vendor_priv_bytes.len() != 32 {
-        anyhow::bail!(
-            "Vendor private key must be 32 bytes, got {}",
-            vendor_priv_bytes.len()
-        );
-    }
-    let mut vendor_priv = [0u8; 32];
-    vendor_priv.copy_from_slice(&vendor_priv_bytes);
+    let vendor_key =
+        read_private_key(&vendor_priv_path).context("Failed to read vendor private key")?;
+
+    // Load vendor PQ private key (hybrid mode)
+    let vendor_pq_key = vendor_pq_priv_path
+        .as_deref()
+        .map(read_pq_private_key)
+        .transpose()
+        .context("Failed to read vendor PQ private key")?;
 
     // Load user public key
-    let user_pub_bytes = read_hex_or_file(&user_pub).context("Failed to read user public key")?;
-    if user_pub_bytes.len() != 33 {
-        anyhow::bail!(
-            "User public key must be 33 bytes, got {}",
-            user_pub_bytes.len()
-        );
-    }
-    let mut user_pubkey = [0u8; 33];
-    user_pubkey.copy_from_slice(&user_pub_bytes);
+    let user_key = read_public_key(&user_pub).context("Failed to read user public key")?;
 
     // Load and parse payload
     let payload_json_str = fs::read_to_string(&payload_path)
@@ -66,98 +122,160 @@ pub fn run(
         serde_json::from_str(&payload_json_str).context("Failed to parse payload JSON")?;
 
     // Validate payload
-    payload_json.validate()?;
+    payload_json.validate(&clock)?;
+    let policy = load_policy(policy_path.as_deref())?;
 
     // Generate random license ID
     let mut license_id = [0u8; 32];
     getrandom(&mut license_id).context("Failed to generate random license ID")?;
 
     // Get current timestamp
-    let issued_at = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)?
-        .as_secs();
-
-    // Convert limits to core format
-    let limits = payload_json.limits.as_ref().map(|l: &LimitsJson| Limits {
-        seat_max: l.seat_max,
-        offline_grace_days: l.offline_grace_days,
-    });
-
-    // Convert metadata to core format
-    let metadata = payload_json
-        .metadata
-        .as_ref()
-        .map(|m: &MetadataJson| Metadata {
-            product: m.product.clone(),
-            version: m.version.clone(),
-            university: m.university.clone(),
-        });
+    let issued_at = clock.now_unix()?;
 
     // Create core library payload
-    let payload = LicensePayload {
-        version: PROTOCOL_VERSION,
-        product_id: payload_json.tier_string(),
-        license_id,
-        issued_at,
-        expiry: payload_json.expires_at,
-        user_pubkey,
-        tier: payload_json.tier_string(),
-        features: payload_json.features.clone(),
-        limits,
-        metadata,
-    };
+    let payload = payload_json.to_core_payload(&user_key, license_id, issued_at);
 
     // Issue certificate
-    let cert = issue_cert(&vendor_priv, payload).context("Failed to issue certificate")?;
+    let cert = match &vendor_pq_key {
+        Some(pq_key) => issue_cert_hybrid(&vendor_key, pq_key, payload, &policy),
+        None => issue_cert_with_policy(&vendor_key, payload, &policy),
+    }
+    .context("Failed to issue certificate")?;
+
+    let license_key = match format {
+        IssueFormat::Cert => {
+            // Encode to CBOR
+            let cert_cbor = encode_cert(&cert).context("Failed to encode certificate")?;
 
-    // Encode to CBOR
-    let cert_cbor = encode_cert(&cert).context("Failed to encode certificate")?;
+            // Write binary CBOR
+            fs::write(&out_path, &cert_cbor)
+                .with_context(|| format!("Failed to write certificate: {}", out_path.display()))?;
 
-    // Write binary CBOR
-    fs::write(&out_path, &cert_cbor)
-        .with_context(|| format!("Failed to write certificate: {}", out_path.display()))?;
+            // Write base64 variant
+            if let Some(base64_path) = &base64_path {
+                let cert_base64 = STANDARD.encode(&cert_cbor);
+                fs::write(base64_path, cert_base64).with_context(|| {
+                    format!(
+                        "Failed to write base64 certificate: {}",
+                        base64_path.display()
+                    )
+                })?;
+            }
+
+            // Write ASCII-armored variant for copy/paste delivery
+            if let Some(armor_path) = &armor_path {
+                let cert_armored =
+                    encode_cert_armored(&cert).context("Failed to armor certificate")?;
+                fs::write(armor_path, cert_armored).with_context(|| {
+                    format!(
+                        "Failed to write armored certificate: {}",
+                        armor_path.display()
+                    )
+                })?;
+            }
+            None
+        }
+        IssueFormat::Key => {
+            let key =
+                encode_cert_key(&cert, &catalog.bits).context("Failed to encode license key")?;
+            fs::write(&out_path, format!("{}\n", key))
+                .with_context(|| format!("Failed to write license key: {}", out_path.display()))?;
+            Some(key)
+        }
+    };
 
-    // Write base64 variant
-    let cert_base64 = STANDARD.encode(&cert_cbor);
-    fs::write(&base64_path, cert_base64).with_context(|| {
-        format!(
-            "Failed to write base64 certificate: {}",
-            base64_path.display()
-        )
-    })?;
+    // Render QR code: the URI, or exactly what was written to the output file
+    if let Some(target) = &qr_path {
+        let content = match (&license_key, qr_uri) {
+            (_, true) => license_uri(&cert)
+                .context("Failed to encode license URI")?
+                .into_bytes(),
+            (Some(key), false) => key.clone().into_bytes(),
+            (None, false) => encode_cert(&cert).context("Failed to encode certificate")?,
+        };
+        write_qr(&content, target)?;
+    }
 
     // Output results
     if json {
         let output = IssueOutput {
             license_id: hex::encode(license_id),
+            alg: vendor_key.alg.to_string(),
+            pq_alg: cert.payload.pq_alg.map(|alg| alg.to_string()),
             tier: payload_json.tier_string(),
             expires_at: payload_json.expires_at,
             features_count: payload_json.features.len(),
             cert_path: out_path.display().to_string(),
-            cert_base64_path: base64_path.display().to_string(),
+            cert_base64_path: base64_path.map(|p| p.display().to_string()),
+            cert_armor_path: armor_path.map(|p| p.display().to_string()),
+            license_key,
+            qr_path: qr_file.map(|p| p.display().to_string()),
         };
         println!("{}", serde_json::to_string_pretty(&output)?);
     } else {
         println!("✅ License certificate issued successfully!");
         println!("   License ID: {}", hex::encode(&license_id[..8]));
         println!("   Tier: {}", payload_json.tier_string());
+        match cert.payload.pq_alg {
+            Some(pq_alg) => println!("   Algorithm: {} + {} (hybrid)", vendor_key.alg, pq_alg),
+            None => println!("   Algorithm: {}", vendor_key.alg),
+        }
         if let Some(exp) = payload_json.expires_at {
             println!("   Expires: {}", exp);
         } else {
             println!("   Expires: Never (perpetual)");
         }
+        let feature_list: Vec<String> = payload_json
+            .features
+            .iter()
+            .map(|f| match f {
+                FeatureJson::Windowed {
+                    name,
+                    not_before: Some(nbf),
+                    expires_at: Some(exp),
+                } => format!("{} [from {} until {}]", name, nbf, exp),
+                FeatureJson::Windowed {
+                    name,
+                    expires_at: Some(exp),
+                    ..
+                } => format!("{} [until {}]", name, exp),
+                FeatureJson::Windowed {
+                    name,
+                    not_before: Some(nbf),
+                    ..
+                } => format!("{} [from {}]", name, nbf),
+                _ => f.name().to_string(),
+            })
+            .collect();
         println!(
             "   Features: {} ({})",
-            payload_json.features.len(),
-            payload_json.features.join(", ")
+            feature_list.len(),
+            feature_list.join(", ")
         );
-        if let Some(lim) = &payload_json.limits {
+        if let Some(lim) = &cert.payload.limits {
             if let Some(seats) = lim.seat_max {
                 println!("   Seat Limit: {}", seats);
             }
         }
-        println!("   Certificate: {}", out_path.display());
-        println!("   Base64: {}", base64_path.display());
+        for (name, value) in &payload_json.entitlements {
+            println!("   Entitlement: {} = {}", name, value);
+        }
+        match &license_key {
+            Some(key) => {
+                println!("   License Key: {}", key);
+                println!("   Key File: {}", out_path.display());
+            }
+            None => println!("   Certificate: {}", out_path.display()),
+        }
+        if let Some(base64_path) = &base64_path {
+            println!("   Base64: {}", base64_path.display());
+        }
+        if let Some(armor_path) = &armor_path {
+            println!("   Armored: {}", armor_path.display());
+        }
+        if let Some(qr_file) = &qr_file {
+            println!("   QR Code: {}", qr_file.display());
+        }
     }
 
     Ok(())
@@ -0,0 +1,92 @@
+//! Certificate lint command
+
+use crate::error::Result;
+use crate::utils::io::check_overwrite;
+use aegis_crm_core::cert::{decode_cert, encode_cert, normalize_cert};
+use anyhow::Context;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Serialize)]
+pub struct LintIssue {
+    code: &'static str,
+    message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LintOutput {
+    cert_path: String,
+    normalized: bool,
+    issues: Vec<LintIssue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fixed_path: Option<String>,
+}
+
+pub fn run(cert_path: PathBuf, fix: Option<PathBuf>, force: bool, json: bool) -> Result<()> {
+    if let Some(fix_path) = &fix {
+        check_overwrite(fix_path, force)?;
+    }
+
+    let cert_bytes = fs::read(&cert_path)
+        .with_context(|| format!("Failed to read certificate: {}", cert_path.display()))?;
+    let original = decode_cert(&cert_bytes).context("Failed to decode certificate")?;
+    let mut cert = original.clone();
+
+    let mut issues = Vec::new();
+    if normalize_cert(&mut cert).context("Failed to normalize signature")? {
+        issues.push(LintIssue {
+            code: "high_s_signature",
+            message: format!(
+                "{} signature is not in low-S form; verifiers reject it",
+                cert.payload.alg
+            ),
+        });
+    }
+
+    if encode_cert(&original)? != cert_bytes {
+        issues.push(LintIssue {
+            code: "non_canonical_encoding",
+            message: "certificate bytes are not canonical CBOR".to_string(),
+        });
+    }
+
+    if let Some(fix_path) = &fix {
+        let canonical = encode_cert(&cert).context("Failed to encode certificate")?;
+        fs::write(fix_path, canonical)
+            .with_context(|| format!("Failed to write certificate: {}", fix_path.display()))?;
+    }
+
+    if json {
+        let output = LintOutput {
+            cert_path: cert_path.display().to_string(),
+            normalized: issues.is_empty(),
+            issues,
+            fixed_path: fix.as_ref().map(|p| p.display().to_string()),
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        if !output.normalized && fix.is_none() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if issues.is_empty() {
+        println!("✅ {}: normalized", cert_path.display());
+        return Ok(());
+    }
+
+    println!("❌ {}: {} issue(s)", cert_path.display(), issues.len());
+    for issue in &issues {
+        println!("   - [{}] {}", issue.code, issue.message);
+    }
+    match &fix {
+        Some(fix_path) => println!("   Normalized certificate: {}", fix_path.display()),
+        None => {
+            println!("   Use --fix <path> to write a normalized copy");
+            std::process::exit(1);
+        }
+    }
+
+    Ok(())
+}
@@ -1,7 +1,10 @@
 //! Command handlers
 
+pub mod conformance;
+pub mod convert;
 pub mod inspect;
 pub mod issue;
+pub mod lint;
 pub mod pop;
 pub mod user;
 pub mod vendor;
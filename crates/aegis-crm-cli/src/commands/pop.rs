@@ -1,13 +1,15 @@
 //! Proof-of-Possession commands
 
 use crate::error::Result;
-use crate::utils::io::read_hex_or_file;
-use aegis_crm_core::pop::{challenge as pop_challenge, prove as pop_prove, verify as pop_verify};
+use crate::utils::io::{read_private_key, read_public_key};
+use aegis_crm_core::pop::{
+    prove_with_key as pop_prove, try_challenge, verify_with_key as pop_verify,
+};
 use anyhow::Context;
 
 /// Generate PoP challenge nonce
 pub fn challenge() -> Result<()> {
-    let nonce = pop_challenge();
+    let nonce = try_challenge()?;
     println!("{}", hex::encode(nonce));
     Ok(())
 }
@@ -15,16 +17,7 @@ pub fn challenge() -> Result<()> {
 /// Prove ownership of user private key
 pub fn prove(user_priv: String, nonce_hex: String) -> Result<()> {
     // Load user private key
-    let user_priv_bytes =
-        read_hex_or_file(&user_priv).context("Failed to read user private key")?;
-    if user_priv_bytes.len() != 32 {
-        anyhow::bail!(
-            "User private key must be 32 bytes, got {}",
-            user_priv_bytes.len()
-        );
-    }
-    let mut user_privkey = [0u8; 32];
-    user_privkey.copy_from_slice(&user_priv_bytes);
+    let user_key = read_private_key(&user_priv).context("Failed to read user private key")?;
 
     // Parse nonce
     let nonce_bytes = hex::decode(&nonce_hex).context("Invalid nonce hex")?;
@@ -35,7 +28,7 @@ pub fn prove(user_priv: String, nonce_hex: String) -> Result<()> {
     nonce.copy_from_slice(&nonce_bytes);
 
     // Prove
-    let signature = pop_prove(&user_privkey, &nonce).context("Failed to generate PoP signature")?;
+    let signature = pop_prove(&user_key, &nonce).context("Failed to generate PoP signature")?;
 
     println!("{}", hex::encode(signature));
     Ok(())
@@ -44,15 +37,7 @@ pub fn prove(user_priv: String, nonce_hex: String) -> Result<()> {
 /// Verify PoP signature
 pub fn verify(user_pub: String, nonce_hex: String, sig_hex: String) -> Result<()> {
     // Load user public key
-    let user_pub_bytes = read_hex_or_file(&user_pub).context("Failed to read user public key")?;
-    if user_pub_bytes.len() != 33 {
-        anyhow::bail!(
-            "User public key must be 33 bytes, got {}",
-            user_pub_bytes.len()
-        );
-    }
-    let mut user_pubkey = [0u8; 33];
-    user_pubkey.copy_from_slice(&user_pub_bytes);
+    let user_key = read_public_key(&user_pub).context("Failed to read user public key")?;
 
     // Parse nonce
     let nonce_bytes = hex::decode(&nonce_hex).context("Invalid nonce hex")?;
@@ -71,7 +56,7 @@ pub fn verify(user_pub: String, nonce_hex: String, sig_hex: String) -> Result<()
     signature.copy_from_slice(&sig_bytes);
 
     // Verify
-    match pop_verify(&user_pubkey, &nonce, &signature) {
+    match pop_verify(&user_key, &nonce, &signature) {
         Ok(()) => {
             println!("✅ Proof-of-Possession VALID");
             std::process::exit(0);
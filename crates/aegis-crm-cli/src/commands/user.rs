@@ -1,18 +1,41 @@
-//! User key generation command
+//! User key generation and activation request commands
 
 use crate::error::Result;
-use crate::utils::io::{check_overwrite, ensure_directory, validate_output_path, write_hex_file};
-use aegis_crm_core::keys::user_keygen;
+use crate::utils::io::{
+    check_overwrite, ensure_directory, read_public_key, validate_output_path, write_key_file,
+};
+use crate::utils::qr::{check_qr_target, write_qr};
+use aegis_crm_core::crypto::SignatureAlgorithm;
+use aegis_crm_core::keys::try_keygen;
+use aegis_crm_core::uri::ActivationRequest;
+use anyhow::Context;
 use serde::Serialize;
 use std::path::PathBuf;
 
 #[derive(Debug, Serialize)]
 pub struct UserKeygenOutput {
+    algorithm: String,
     private_key_path: String,
     public_key_path: String,
 }
 
-pub fn run(out_dir: Option<PathBuf>, force: bool, json: bool) -> Result<()> {
+#[derive(Debug, Serialize)]
+pub struct UserRequestOutput {
+    algorithm: String,
+    user_pub: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    product_id: Option<String>,
+    uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    qr_path: Option<String>,
+}
+
+pub fn run(
+    out_dir: Option<PathBuf>,
+    alg: SignatureAlgorithm,
+    force: bool,
+    json: bool,
+) -> Result<()> {
     let out_dir = out_dir.unwrap_or(PathBuf::from("./user_keys"));
 
     // Validate output path
@@ -26,27 +49,64 @@ pub fn run(out_dir: Option<PathBuf>, force: bool, json: bool) -> Result<()> {
     check_overwrite(&pub_path, force)?;
 
     // Generate keypair
-    let keypair = user_keygen();
+    let keypair = try_keygen(alg)?;
 
     // Create output directory
     ensure_directory(&out_dir)?;
 
     // Write keys
-    write_hex_file(&priv_path, &keypair.privkey)?;
-    write_hex_file(&pub_path, &keypair.pubkey)?;
+    write_key_file(&priv_path, alg, &keypair.privkey.bytes)?;
+    write_key_file(&pub_path, alg, &keypair.pubkey.bytes)?;
 
     // Output results
     if json {
         let output = UserKeygenOutput {
+            algorithm: alg.to_string(),
             private_key_path: priv_path.display().to_string(),
             public_key_path: pub_path.display().to_string(),
         };
         println!("{}", serde_json::to_string_pretty(&output)?);
     } else {
         println!("✅ User keypair generated successfully!");
+        println!("   Algorithm:   {}", alg);
         println!("   Private key: {}", priv_path.display());
         println!("   Public key:  {}", pub_path.display());
     }
 
     Ok(())
 }
+
+pub fn request(
+    user_pub: String,
+    product_id: Option<String>,
+    qr: Option<PathBuf>,
+    json: bool,
+) -> Result<()> {
+    check_qr_target(qr.as_deref(), json)?;
+    let user_key = read_public_key(&user_pub).context("Failed to read user public key")?;
+    let request = ActivationRequest::new(user_key, product_id);
+    let uri = request.to_uri();
+
+    if let Some(target) = &qr {
+        write_qr(uri.as_bytes(), target)?;
+    }
+
+    if json {
+        let output = UserRequestOutput {
+            algorithm: request.user_key.alg.to_string(),
+            user_pub: hex::encode(&request.user_key.bytes),
+            product_id: request.product_id,
+            uri,
+            qr_path: qr.map(|p| p.display().to_string()),
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        println!("✅ Activation request");
+        println!("   URI: {}", uri);
+        if let Some(path) = qr.filter(|p| p.as_os_str() != "-") {
+            println!("   QR code: {}", path.display());
+        }
+    }
+
+    Ok(())
+}
@@ -1,18 +1,33 @@
 //! Vendor key generation command
 
 use crate::error::Result;
-use crate::utils::io::{check_overwrite, ensure_directory, validate_output_path, write_hex_file};
-use aegis_crm_core::keys::vendor_keygen;
+use crate::utils::io::{
+    check_overwrite, ensure_directory, validate_output_path, write_key_file, write_pq_key_file,
+};
+use aegis_crm_core::crypto::SignatureAlgorithm;
+use aegis_crm_core::keys::try_keygen;
+use aegis_crm_core::pq::{pq_keygen, PqAlgorithm};
 use serde::Serialize;
 use std::path::PathBuf;
 
 #[derive(Debug, Serialize)]
 pub struct VendorKeygenOutput {
+    algorithm: String,
     private_key_path: String,
     public_key_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pq_private_key_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pq_public_key_path: Option<String>,
 }
 
-pub fn run(out_dir: Option<PathBuf>, force: bool, json: bool) -> Result<()> {
+pub fn run(
+    out_dir: Option<PathBuf>,
+    alg: SignatureAlgorithm,
+    pq: bool,
+    force: bool,
+    json: bool,
+) -> Result<()> {
     let out_dir = out_dir.unwrap_or(PathBuf::from("./vendor_keys"));
 
     // Validate output path
@@ -25,6 +40,17 @@ pub fn run(out_dir: Option<PathBuf>, force: bool, json: bool) -> Result<()> {
     check_overwrite(&priv_path, force)?;
     check_overwrite(&pub_path, force)?;
 
+    let pq_paths = pq.then(|| {
+        (
+            out_dir.join("vendor_pq_priv.hex"),
+            out_dir.join("vendor_pq_pub.hex"),
+        )
+    });
+    if let Some((pq_priv_path, pq_pub_path)) = &pq_paths {
+        check_overwrite(pq_priv_path, force)?;
+        check_overwrite(pq_pub_path, force)?;
+    }
+
     // Display security warning (unless JSON mode)
     if !json {
         eprintln!("\n⚠️  WARNING: Vendor private key is CRITICAL!");
@@ -35,26 +61,48 @@ pub fn run(out_dir: Option<PathBuf>, force: bool, json: bool) -> Result<()> {
     }
 
     // Generate keypair
-    let keypair = vendor_keygen();
+    let keypair = try_keygen(alg)?;
 
     // Create output directory
     ensure_directory(&out_dir)?;
 
     // Write keys
-    write_hex_file(&priv_path, &keypair.privkey)?;
-    write_hex_file(&pub_path, &keypair.pubkey)?;
+    write_key_file(&priv_path, alg, &keypair.privkey.bytes)?;
+    write_key_file(&pub_path, alg, &keypair.pubkey.bytes)?;
+
+    if let Some((pq_priv_path, pq_pub_path)) = &pq_paths {
+        let pq_keypair = pq_keygen(PqAlgorithm::MlDsa65)?;
+        write_pq_key_file(
+            pq_priv_path,
+            pq_keypair.privkey.alg,
+            &pq_keypair.privkey.seed,
+        )?;
+        write_pq_key_file(pq_pub_path, pq_keypair.pubkey.alg, &pq_keypair.pubkey.bytes)?;
+    }
 
     // Output results
     if json {
         let output = VendorKeygenOutput {
+            algorithm: alg.to_string(),
             private_key_path: priv_path.display().to_string(),
             public_key_path: pub_path.display().to_string(),
+            pq_private_key_path: pq_paths.as_ref().map(|(p, _)| p.display().to_string()),
+            pq_public_key_path: pq_paths.as_ref().map(|(_, p)| p.display().to_string()),
         };
         println!("{}", serde_json::to_string_pretty(&output)?);
     } else {
         println!("✅ Vendor keypair generated successfully!");
+        println!("   Algorithm:   {}", alg);
         println!("   Private key: {}", priv_path.display());
         println!("   Public key:  {}", pub_path.display());
+        if let Some((pq_priv_path, pq_pub_path)) = &pq_paths {
+            println!(
+                "   PQ private:  {} ({})",
+                pq_priv_path.display(),
+                PqAlgorithm::MlDsa65
+            );
+            println!("   PQ public:   {}", pq_pub_path.display());
+        }
     }
 
     Ok(())
@@ -4,15 +4,22 @@ mod commands;
 mod error;
 mod utils;
 
+use aegis_crm_core::crypto::SignatureAlgorithm;
 use clap::{Parser, Subcommand};
+use commands::issue::IssueFormat;
 use error::Result;
 use std::path::PathBuf;
+use utils::cert::CertFormat;
 
 #[derive(Parser)]
 #[command(name = "aegis")]
 #[command(about = "Aegis CRM - Cryptographic license management", long_about = None)]
 #[command(version)]
 struct Cli {
+    /// Evaluate time-dependent checks at this time (Unix seconds or RFC 3339)
+    #[arg(long, global = true, value_name = "TIME", value_parser = utils::clock::parse_now)]
+    now: Option<u64>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -45,6 +52,30 @@ enum Commands {
         #[arg(long)]
         payload: PathBuf,
 
+        /// Path to vendor ML-DSA private key; issues a hybrid (ECDSA + ML-DSA) certificate
+        #[arg(long)]
+        vendor_pq_priv: Option<String>,
+
+        /// Path to tier policy file (TOML); defaults to the built-in v1.0 rules
+        #[arg(long)]
+        policy: Option<PathBuf>,
+
+        /// Output format: certificate files or a typable license key
+        #[arg(long, value_enum, default_value_t = IssueFormat::Cert)]
+        format: IssueFormat,
+
+        /// Path to JSON feature catalog whose `bits` table shortens license keys
+        #[arg(long)]
+        catalog: Option<PathBuf>,
+
+        /// Render a QR code to a PNG file, or to the terminal with `-`
+        #[arg(long, value_name = "PATH")]
+        qr: Option<PathBuf>,
+
+        /// Put an `aegis:license` URI in the QR code instead of raw bytes
+        #[arg(long, requires = "qr")]
+        qr_uri: bool,
+
         /// Output certificate path
         #[arg(long)]
         out: PathBuf,
@@ -68,6 +99,124 @@ enum Commands {
         #[arg(long)]
         vendor_pub: String,
 
+        /// Path to vendor ML-DSA public key; requires a valid hybrid signature
+        #[arg(long)]
+        vendor_pq_pub: Option<String>,
+
+        /// Path to JSON feature catalog used to resolve implied features
+        #[arg(long)]
+        catalog: Option<PathBuf>,
+
+        /// Render a QR code to a PNG file, or to the terminal with `-`
+        #[arg(long, value_name = "PATH")]
+        qr: Option<PathBuf>,
+
+        /// Put an `aegis:license` URI in the QR code instead of raw bytes
+        #[arg(long, requires = "qr")]
+        qr_uri: bool,
+
+        /// Run every verification check and explain each outcome
+        #[arg(long, conflicts_with = "qr")]
+        explain: bool,
+
+        /// Expected product ID (with --explain)
+        #[arg(long, requires = "explain")]
+        product: Option<String>,
+
+        /// Challenge nonce (hex) for the proof-of-possession check (with --explain)
+        #[arg(long, requires_all = ["explain", "pop_sig"])]
+        nonce: Option<String>,
+
+        /// Proof-of-possession signature over --nonce (hex)
+        #[arg(long, requires = "nonce")]
+        pop_sig: Option<String>,
+
+        /// Revoked license ID (hex); repeat for several (with --explain)
+        #[arg(long, value_name = "LID", requires = "explain")]
+        revoked: Vec<String>,
+
+        /// Time of the last online check for the offline grace period (with --explain)
+        #[arg(long, value_name = "TIME", requires = "explain", value_parser = utils::clock::parse_now)]
+        last_online: Option<u64>,
+
+        /// Output machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Convert a license certificate between native CBOR, COSE_Sign1 and JWS/JWT
+    Convert {
+        /// Path to certificate file
+        #[arg(long)]
+        cert: PathBuf,
+
+        /// Source format (detected when omitted)
+        #[arg(long, value_enum)]
+        from: Option<CertFormat>,
+
+        /// Target format
+        #[arg(long, value_enum)]
+        to: CertFormat,
+
+        /// Path to vendor private key or hex string (formats sign different bytes)
+        #[arg(long)]
+        vendor_priv: String,
+
+        /// Output certificate path
+        #[arg(long)]
+        out: PathBuf,
+
+        /// Force overwrite existing files
+        #[arg(long)]
+        force: bool,
+
+        /// Output machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Check a license certificate for non-canonical signatures and encoding
+    Lint {
+        /// Path to certificate file
+        #[arg(long)]
+        cert: PathBuf,
+
+        /// Write a normalized copy of the certificate to this path
+        #[arg(long)]
+        fix: Option<PathBuf>,
+
+        /// Force overwrite existing files
+        #[arg(long)]
+        force: bool,
+
+        /// Output machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Check another Aegis implementation against the test vectors (SPEC §11)
+    Conformance {
+        /// Implementation command and arguments, after `--`
+        #[arg(
+            trailing_var_arg = true,
+            allow_hyphen_values = true,
+            required_unless_present = "serve",
+            value_name = "COMMAND"
+        )]
+        command: Vec<String>,
+
+        /// Test vectors JSON (defaults to the built-in fixtures/vectors.json)
+        #[arg(long)]
+        vectors: Option<PathBuf>,
+
+        /// Seconds to wait for each response
+        #[arg(long, default_value_t = 10)]
+        timeout: u64,
+
+        /// Act as the reference implementation, answering requests on stdin
+        #[arg(long, conflicts_with_all = ["command", "vectors", "json"])]
+        serve: bool,
+
         /// Output machine-readable JSON
         #[arg(long)]
         json: bool,
@@ -88,6 +237,14 @@ enum VendorCommands {
         #[arg(long)]
         out: Option<PathBuf>,
 
+        /// Signature algorithm: secp256k1, p256, ed25519 or schnorr
+        #[arg(long, default_value = "secp256k1")]
+        alg: SignatureAlgorithm,
+
+        /// Also generate an ML-DSA-65 keypair for hybrid certificates
+        #[arg(long)]
+        pq: bool,
+
         /// Force overwrite existing files
         #[arg(long)]
         force: bool,
@@ -106,6 +263,10 @@ enum UserCommands {
         #[arg(long)]
         out: Option<PathBuf>,
 
+        /// Signature algorithm: secp256k1, p256, ed25519 or schnorr
+        #[arg(long, default_value = "secp256k1")]
+        alg: SignatureAlgorithm,
+
         /// Force overwrite existing files
         #[arg(long)]
         force: bool,
@@ -114,6 +275,25 @@ enum UserCommands {
         #[arg(long)]
         json: bool,
     },
+
+    /// Print an activation request (`aegis:request` URI) for the vendor
+    Request {
+        /// Path to user public key or hex string
+        #[arg(long)]
+        user_pub: String,
+
+        /// Product the license is requested for
+        #[arg(long)]
+        product: Option<String>,
+
+        /// Render the request as a QR code PNG, or to the terminal with `-`
+        #[arg(long, value_name = "PATH")]
+        qr: Option<PathBuf>,
+
+        /// Output machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -150,37 +330,138 @@ enum PopCommands {
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    let clock = utils::clock::clock(cli.now);
 
     match cli.command {
         Commands::Vendor { command } => match command {
-            VendorCommands::Keygen { out, force, json } => {
-                commands::vendor::run(out, force, json)?;
+            VendorCommands::Keygen {
+                out,
+                alg,
+                pq,
+                force,
+                json,
+            } => {
+                commands::vendor::run(out, alg, pq, force, json)?;
             }
         },
 
         Commands::User { command } => match command {
-            UserCommands::Keygen { out, force, json } => {
-                commands::user::run(out, force, json)?;
+            UserCommands::Keygen {
+                out,
+                alg,
+                force,
+                json,
+            } => {
+                commands::user::run(out, alg, force, json)?;
+            }
+            UserCommands::Request {
+                user_pub,
+                product,
+                qr,
+                json,
+            } => {
+                commands::user::request(user_pub, product, qr, json)?;
             }
         },
 
         Commands::Issue {
             vendor_priv,
+            vendor_pq_priv,
             user_pub,
             payload,
+            policy,
+            format,
+            catalog,
+            qr,
+            qr_uri,
             out,
             force,
             json,
         } => {
-            commands::issue::run(vendor_priv, user_pub, payload, out, force, json)?;
+            commands::issue::run(commands::issue::IssueArgs {
+                vendor_priv,
+                vendor_pq_priv,
+                user_pub,
+                payload,
+                policy,
+                format,
+                catalog,
+                qr,
+                qr_uri,
+                out,
+                force,
+                json,
+                clock,
+            })?;
         }
 
         Commands::Inspect {
             cert,
             vendor_pub,
+            vendor_pq_pub,
+            catalog,
+            qr,
+            qr_uri,
+            explain,
+            product,
+            nonce,
+            pop_sig,
+            revoked,
+            last_online,
             json,
         } => {
-            commands::inspect::run(cert, vendor_pub, json)?;
+            let explain = explain.then(|| commands::inspect::ExplainArgs {
+                product,
+                pop: nonce.zip(pop_sig),
+                revoked,
+                last_online,
+            });
+            commands::inspect::run(commands::inspect::InspectArgs {
+                cert,
+                vendor_pub,
+                vendor_pq_pub,
+                catalog,
+                qr,
+                qr_uri,
+                explain,
+                json,
+                clock,
+            })?;
+        }
+
+        Commands::Convert {
+            cert,
+            from,
+            to,
+            vendor_priv,
+            out,
+            force,
+            json,
+        } => {
+            commands::convert::run(cert, from, to, vendor_priv, out, force, json)?;
+        }
+
+        Commands::Lint {
+            cert,
+            fix,
+            force,
+            json,
+        } => {
+            commands::lint::run(cert, fix, force, json)?;
+        }
+
+        Commands::Conformance {
+            command,
+            vectors,
+            timeout,
+            serve,
+            json,
+        } => {
+            if serve {
+                commands::conformance::serve()?;
+            } else {
+                commands::conformance::run(command, vectors, timeout, json)?;
+            }
         }
 
         Commands::Pop { command } => match command {
@@ -0,0 +1,324 @@
+//! Certificate loading in any supported encoding
+
+use crate::error::Result;
+use crate::utils::qr::reject_png;
+use aegis_crm_core::armor::{check_headers, dearmor, is_armored, Armored};
+use aegis_crm_core::cert::{
+    cose_to_cert, decode_cert, decode_cert_cose, decode_cert_jwt, decode_cert_key, encode_cert,
+    encode_cert_cose, encode_cert_jwt, jwt_to_cert, verify_cert_cose, verify_cert_jwt,
+    verify_cert_with_key, CoseCert, JwtCert, LicenseCert, LicensePayload,
+};
+use aegis_crm_core::keys::{PrivateKey, PublicKey};
+use aegis_crm_core::uri::{is_aegis_uri, parse_uri, AegisUri};
+use aegis_crm_core::AegisError;
+use anyhow::Context;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::ValueEnum;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Certificate encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CertFormat {
+    /// Aegis CBOR map with inline signature
+    Native,
+    /// COSE_Sign1 (RFC 9052)
+    Cose,
+    /// Compact JWS/JWT (RFC 7515)
+    Jwt,
+}
+
+impl fmt::Display for CertFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CertFormat::Native => "native",
+            CertFormat::Cose => "cose",
+            CertFormat::Jwt => "jwt",
+        })
+    }
+}
+
+/// A decoded certificate in its original encoding
+pub enum AnyCert {
+    Native(LicenseCert),
+    Cose(CoseCert),
+    Jwt(JwtCert),
+}
+
+impl AnyCert {
+    /// Decode certificate bytes, detecting the encoding and any base64 or
+    /// ASCII armor wrapping; license keys decode with the `feature_bits` table
+    pub fn decode(bytes: &[u8], feature_bits: &[String]) -> std::result::Result<Self, AegisError> {
+        if let Some(body) = Self::unwrap_text(bytes)? {
+            if let Ok(cert) = Self::decode_binary(&body.data) {
+                check_headers(&body, cert.payload())?;
+                return Ok(cert);
+            }
+        }
+        let err = match Self::decode_binary(bytes) {
+            Ok(cert) => return Ok(cert),
+            Err(err) => err,
+        };
+        if let Some(text) = std::str::from_utf8(bytes).ok().filter(|t| is_aegis_uri(t)) {
+            return match parse_uri(text)? {
+                AegisUri::License(cert) => Ok(AnyCert::Native(*cert)),
+                AegisUri::Request(_) => Err(AegisError::Payload(
+                    "activation request is not a certificate".to_string(),
+                )),
+            };
+        }
+        if let Ok(cert) = Self::decode_as(bytes, CertFormat::Jwt) {
+            return Ok(cert);
+        }
+        match std::str::from_utf8(bytes) {
+            Ok(text) if looks_like_key(text) => {
+                decode_cert_key(text, feature_bits).map(AnyCert::Native)
+            }
+            _ => Err(err),
+        }
+    }
+
+    /// Decode certificate bytes in a known encoding, unwrapping base64 or
+    /// ASCII armor around binary formats
+    pub fn decode_as(bytes: &[u8], format: CertFormat) -> std::result::Result<Self, AegisError> {
+        let decode = |bytes: &[u8]| match format {
+            CertFormat::Native => decode_cert(bytes).map(AnyCert::Native),
+            CertFormat::Cose => decode_cert_cose(bytes).map(AnyCert::Cose),
+            CertFormat::Jwt => {
+                let token = std::str::from_utf8(bytes)
+                    .map_err(|_| AegisError::parse("JWS is not UTF-8 text"))?;
+                decode_cert_jwt(token).map(AnyCert::Jwt)
+            }
+        };
+        if format == CertFormat::Jwt {
+            return decode(bytes);
+        }
+        match Self::unwrap_text(bytes)? {
+            Some(body) => {
+                let cert = decode(&body.data)?;
+                check_headers(&body, cert.payload())?;
+                Ok(cert)
+            }
+            None => decode(bytes),
+        }
+    }
+
+    fn decode_binary(bytes: &[u8]) -> std::result::Result<Self, AegisError> {
+        match decode_cert(bytes) {
+            Ok(cert) => Ok(AnyCert::Native(cert)),
+            Err(native_err) => decode_cert_cose(bytes)
+                .map(AnyCert::Cose)
+                .map_err(|_| native_err),
+        }
+    }
+
+    /// Binary body of armored or base64 text, `None` for anything else
+    fn unwrap_text(bytes: &[u8]) -> std::result::Result<Option<Armored>, AegisError> {
+        let Ok(text) = std::str::from_utf8(bytes) else {
+            return Ok(None);
+        };
+        if is_armored(text) {
+            return dearmor(text).map(Some);
+        }
+        let compact: String = text.split_whitespace().collect();
+        Ok(STANDARD.decode(compact).ok().map(|data| Armored {
+            headers: Vec::new(),
+            data,
+        }))
+    }
+
+    pub fn format(&self) -> CertFormat {
+        match self {
+            AnyCert::Native(_) => CertFormat::Native,
+            AnyCert::Cose(_) => CertFormat::Cose,
+            AnyCert::Jwt(_) => CertFormat::Jwt,
+        }
+    }
+
+    pub fn payload(&self) -> &LicensePayload {
+        match self {
+            AnyCert::Native(cert) => &cert.payload,
+            AnyCert::Cose(cert) => &cert.payload,
+            AnyCert::Jwt(cert) => &cert.payload,
+        }
+    }
+
+    /// Verify against the vendor key (classical signature only)
+    pub fn verify(&self, vendor_key: &PublicKey, now: u64) -> std::result::Result<(), AegisError> {
+        match self {
+            AnyCert::Native(cert) => verify_cert_with_key(vendor_key, cert, now),
+            AnyCert::Cose(cert) => verify_cert_cose(vendor_key, cert, now),
+            AnyCert::Jwt(cert) => verify_cert_jwt(vendor_key, cert, now),
+        }
+    }
+
+    /// Native certificate, re-signing COSE or JWS input with `vendor_key`
+    pub fn into_native(self, vendor_key: &PrivateKey) -> Result<LicenseCert> {
+        match self {
+            AnyCert::Native(cert) => Ok(cert),
+            AnyCert::Cose(cert) => {
+                cose_to_cert(vendor_key, &cert).context("Failed to convert COSE certificate")
+            }
+            AnyCert::Jwt(cert) => {
+                jwt_to_cert(vendor_key, &cert).context("Failed to convert JWS certificate")
+            }
+        }
+    }
+
+    /// Encode in `format`, re-signing with `vendor_key` when the encoding changes
+    pub fn encode(self, format: CertFormat, vendor_key: &PrivateKey) -> Result<Vec<u8>> {
+        let native = self.into_native(vendor_key)?;
+        match format {
+            CertFormat::Native => Ok(encode_cert(&native)?),
+            CertFormat::Cose => {
+                encode_cert_cose(vendor_key, &native).context("Failed to encode COSE certificate")
+            }
+            CertFormat::Jwt => encode_cert_jwt(vendor_key, &native)
+                .map(|token| token.into_bytes())
+                .context("Failed to encode JWS certificate"),
+        }
+    }
+}
+
+/// Read a certificate file, decoding as `format` or detecting it when `None`
+/// (license keys are detected with the `feature_bits` table)
+pub fn read_cert(
+    path: &Path,
+    format: Option<CertFormat>,
+    feature_bits: &[String],
+) -> Result<AnyCert> {
+    let bytes = read_cert_file(path)?;
+    match format {
+        Some(format) => AnyCert::decode_as(&bytes, format)
+            .with_context(|| format!("Failed to decode {} certificate", format)),
+        None => AnyCert::decode(&bytes, feature_bits).context("Failed to decode certificate"),
+    }
+}
+
+/// Certificate file contents; QR code images are refused
+pub fn read_cert_file(path: &Path) -> Result<Vec<u8>> {
+    let bytes = fs::read(path)
+        .with_context(|| format!("Failed to read certificate: {}", path.display()))?;
+    reject_png(&bytes)?;
+    Ok(bytes)
+}
+
+/// Native CBOR certificate bytes for step-by-step verification
+///
+/// Armor and base64 are unwrapped and URIs and license keys re-encoded, but
+/// undecodable CBOR is returned as is so the decode check can report why.
+/// COSE and JWS have their own signature layout and are rejected.
+pub fn native_cert_bytes(bytes: &[u8], feature_bits: &[String]) -> Result<Vec<u8>> {
+    let body = match AnyCert::unwrap_text(bytes) {
+        Ok(Some(body)) => body.data,
+        _ => bytes.to_vec(),
+    };
+    if decode_cert(&body).is_ok() {
+        return Ok(body);
+    }
+    match AnyCert::decode(bytes, feature_bits) {
+        Ok(AnyCert::Native(cert)) => Ok(encode_cert(&cert)?),
+        Ok(cert) => anyhow::bail!(
+            "Step-by-step verification needs a native certificate, got {}; convert it with `aegis convert --to native`",
+            cert.format()
+        ),
+        Err(_) => Ok(body),
+    }
+}
+
+/// Whether `text` has the shape of a license key (base32 groups and dashes)
+fn looks_like_key(text: &str) -> bool {
+    let text = text.trim();
+    text.contains('-')
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c.is_ascii_whitespace())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aegis_crm_core::armor::encode_cert_armored;
+    use aegis_crm_core::cert::encode_cert_key;
+    use aegis_crm_core::cert::{issue_cert_with_policy, PROTOCOL_VERSION};
+    use aegis_crm_core::crypto::SignatureAlgorithm;
+    use aegis_crm_core::keys::try_keygen;
+    use aegis_crm_core::policy::PolicySet;
+    use std::collections::BTreeMap;
+
+    fn sample() -> (PrivateKey, LicenseCert) {
+        let vendor = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let user = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let payload = LicensePayload {
+            version: PROTOCOL_VERSION,
+            alg: SignatureAlgorithm::Secp256k1,
+            pq_alg: None,
+            product_id: "cli_test".to_string(),
+            license_id: [1u8; 32],
+            issued_at: 1700000000,
+            expiry: None,
+            user_pubkey: user.pubkey.bytes,
+            user_alg: SignatureAlgorithm::Secp256k1,
+            tier: "lifetime_pro".to_string(),
+            features: vec!["base".into()],
+            limits: None,
+            entitlements: BTreeMap::new(),
+            metadata: None,
+        };
+        let cert = issue_cert_with_policy(&vendor.privkey, payload, &PolicySet::builtin()).unwrap();
+        (vendor.privkey, cert)
+    }
+
+    #[test]
+    fn test_decode_detects_format_and_wrapping() {
+        let (vendor_key, cert) = sample();
+        let raw = encode_cert(&cert).unwrap();
+        let cose = encode_cert_cose(&vendor_key, &cert).unwrap();
+        let jwt = encode_cert_jwt(&vendor_key, &cert).unwrap();
+        let cases = [
+            (raw.clone(), CertFormat::Native),
+            (STANDARD.encode(&raw).into_bytes(), CertFormat::Native),
+            (
+                encode_cert_armored(&cert).unwrap().into_bytes(),
+                CertFormat::Native,
+            ),
+            (cose.clone(), CertFormat::Cose),
+            (STANDARD.encode(&cose).into_bytes(), CertFormat::Cose),
+            (jwt.into_bytes(), CertFormat::Jwt),
+        ];
+        for (bytes, format) in cases {
+            let decoded = AnyCert::decode(&bytes, &[]).unwrap();
+            assert_eq!(decoded.format(), format);
+            assert_eq!(decoded.payload(), &cert.payload);
+            assert!(AnyCert::decode_as(&bytes, format).is_ok());
+        }
+        assert!(AnyCert::decode(b"garbage", &[]).is_err());
+
+        let bits = vec!["base".to_string()];
+        let key = encode_cert_key(&cert, &bits).unwrap();
+        let decoded = AnyCert::decode(key.as_bytes(), &bits).unwrap();
+        assert_eq!(decoded.format(), CertFormat::Native);
+        assert_eq!(decoded.payload(), &cert.payload);
+    }
+
+    #[test]
+    fn test_native_cert_bytes() {
+        let (vendor_key, cert) = sample();
+        let raw = encode_cert(&cert).unwrap();
+        let armored = encode_cert_armored(&cert).unwrap();
+        assert_eq!(native_cert_bytes(&raw, &[]).unwrap(), raw);
+        assert_eq!(native_cert_bytes(armored.as_bytes(), &[]).unwrap(), raw);
+        let bits = vec!["base".to_string()];
+        let key = encode_cert_key(&cert, &bits).unwrap();
+        assert_eq!(native_cert_bytes(key.as_bytes(), &bits).unwrap(), raw);
+
+        // Undecodable input is kept for the decode check to explain
+        let mut truncated = raw.clone();
+        truncated.truncate(raw.len() / 2);
+        assert_eq!(native_cert_bytes(&truncated, &[]).unwrap(), truncated);
+
+        let cose = encode_cert_cose(&vendor_key, &cert).unwrap();
+        assert!(native_cert_bytes(&cose, &[]).is_err());
+    }
+}
@@ -0,0 +1,56 @@
+//! Global `--now` override for issuance and verification time
+
+use aegis_crm_core::clock::{Clock, FixedClock, SystemClock};
+use anyhow::{anyhow, bail};
+use chrono::DateTime;
+
+/// Parse `--now` as Unix seconds or an RFC 3339 timestamp
+pub fn parse_now(input: &str) -> anyhow::Result<u64> {
+    let input = input.trim();
+    if input.chars().all(|c| c.is_ascii_digit()) && !input.is_empty() {
+        return input
+            .parse()
+            .map_err(|_| anyhow!("Unix time out of range: {}", input));
+    }
+    let time = DateTime::parse_from_rfc3339(input).map_err(|_| {
+        anyhow!(
+            "expected Unix seconds or an RFC 3339 timestamp (e.g. 2030-01-01T00:00:00Z), got '{}'",
+            input
+        )
+    })?;
+    let secs = time.timestamp();
+    if secs < 0 {
+        bail!("time before 1970 is not supported: {}", input);
+    }
+    Ok(secs as u64)
+}
+
+/// Clock for commands: fixed at `--now` when given, else the system clock
+pub fn clock(now: Option<u64>) -> Box<dyn Clock> {
+    match now {
+        Some(now) => Box::new(FixedClock::new(now)),
+        None => Box::new(SystemClock),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_now() {
+        assert_eq!(parse_now("1800000000").unwrap(), 1800000000);
+        assert_eq!(parse_now("2027-01-15T08:00:00Z").unwrap(), 1800000000);
+        assert_eq!(parse_now("2027-01-15T10:00:00+02:00").unwrap(), 1800000000);
+        assert!(parse_now("").is_err());
+        assert!(parse_now("tomorrow").is_err());
+        assert!(parse_now("1969-12-31T23:59:59Z").is_err());
+        assert!(parse_now("99999999999999999999").is_err());
+    }
+
+    #[test]
+    fn test_clock_override() {
+        assert_eq!(clock(Some(42)).now_unix().unwrap(), 42);
+        assert!(clock(None).now_unix().unwrap() > 1700000000);
+    }
+}
@@ -0,0 +1,436 @@
+//! Conformance checks against the test vectors (SPEC §11)
+//!
+//! An implementation under test reads one JSON request per line on stdin and
+//! answers each with one JSON line on stdout, echoing `id`:
+//!
+//! - `{"op": "encode", "cert": hex}` → `{"payload_cbor": hex, "digest": hex}`
+//! - `{"op": "verify", "vendor_key": {"alg", "public"}, "cert": hex, "now": u64,
+//!   ["nonce": hex, "pop_sig": hex]}` → `{"result": "OK" | error code}`
+//! - `{"op": "pop", "user_key": {"alg", "public"}, "nonce": hex, "pop_sig": hex}`
+//!   → `{"result": "OK" | "E_POP_SIG"}`
+//!
+//! Unsupported operations answer `{"error": message}`.
+
+use crate::error::Result;
+use aegis_crm_core::cert::{decode_cert, verify_cert_with_key};
+use aegis_crm_core::crypto::sha256;
+use aegis_crm_core::keys::PublicKey;
+use aegis_crm_core::pop::verify_with_key as verify_pop;
+use aegis_crm_core::verify::verify_license_with_key;
+use aegis_crm_core::AegisError;
+use anyhow::{anyhow, Context};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Vectors generated by `generate_vectors`, built into the binary
+pub const BUILTIN_VECTORS: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../fixtures/vectors.json"
+));
+
+/// SPEC §11 compliance requirement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Requirement {
+    CanonicalCbor,
+    VendorSignature,
+    ProofOfPossession,
+    Expiry,
+    ErrorCodes,
+}
+
+impl Requirement {
+    pub const ALL: [Requirement; 5] = [
+        Requirement::CanonicalCbor,
+        Requirement::VendorSignature,
+        Requirement::ProofOfPossession,
+        Requirement::Expiry,
+        Requirement::ErrorCodes,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Requirement::CanonicalCbor => "CBOR canonical CERT format",
+            Requirement::VendorSignature => "Vendor signature verification",
+            Requirement::ProofOfPossession => "PoP challenge-response",
+            Requirement::Expiry => "Expiry check",
+            Requirement::ErrorCodes => "Standardized error codes",
+        }
+    }
+}
+
+/// One request to the implementation and what it must answer
+#[derive(Debug, Clone)]
+pub struct Case {
+    pub name: String,
+    pub request: Value,
+    expected: Expected,
+    requirement: Requirement,
+}
+
+#[derive(Debug, Clone)]
+enum Expected {
+    Encoding {
+        payload_cbor: String,
+        digest: String,
+    },
+    Result(String),
+}
+
+/// Outcome of one case for one requirement
+#[derive(Debug, Serialize)]
+pub struct CheckFailure {
+    pub case: String,
+    pub detail: String,
+}
+
+/// Results for one requirement
+#[derive(Debug, Serialize)]
+pub struct RequirementReport {
+    pub requirement: Requirement,
+    pub title: &'static str,
+    pub passed: usize,
+    pub total: usize,
+    pub failures: Vec<CheckFailure>,
+}
+
+impl RequirementReport {
+    pub fn is_met(&self) -> bool {
+        self.total > 0 && self.passed == self.total
+    }
+}
+
+fn field<'a>(value: &'a Value, name: &str) -> Result<&'a str> {
+    value[name]
+        .as_str()
+        .ok_or_else(|| anyhow!("vector is missing '{}'", name))
+}
+
+/// Build the cases from a vectors document (see `fixtures/vectors.json`)
+pub fn load_cases(vectors: &str) -> Result<Vec<Case>> {
+    let vectors: Value = serde_json::from_str(vectors).context("Failed to parse test vectors")?;
+    let section = |name: &str| -> Result<&Vec<Value>> {
+        vectors[name]
+            .as_array()
+            .ok_or_else(|| anyhow!("test vectors have no '{}' section", name))
+    };
+    let public_keys: Vec<(&str, Value)> = section("keys")?
+        .iter()
+        .map(|key| {
+            let name = field(key, "name")?;
+            let public = json!({ "alg": field(key, "alg")?, "public": field(key, "public")? });
+            Ok((name, public))
+        })
+        .collect::<Result<_>>()?;
+    let public_key = |name: &str| {
+        public_keys
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, public)| public.clone())
+            .ok_or_else(|| anyhow!("unknown key '{}' in test vectors", name))
+    };
+
+    let mut cases = Vec::new();
+    for vector in section("certificates")? {
+        cases.push(Case {
+            name: field(vector, "name")?.to_string(),
+            request: json!({ "op": "encode", "cert": field(vector, "cert_cbor")? }),
+            expected: Expected::Encoding {
+                payload_cbor: field(vector, "payload_cbor")?.to_string(),
+                digest: field(vector, "digest")?.to_string(),
+            },
+            requirement: Requirement::CanonicalCbor,
+        });
+    }
+
+    for vector in section("pop")? {
+        let name = field(vector, "name")?;
+        let user_key = public_key(field(vector, "user_key")?)?;
+        let sig = field(vector, "signature")?;
+        // Same signature with the last bit flipped
+        let mut forged = hex::decode(sig).context("invalid PoP signature in test vectors")?;
+        if let Some(last) = forged.last_mut() {
+            *last ^= 1;
+        }
+        for (suffix, pop_sig, result) in [
+            ("", sig.to_string(), "OK"),
+            ("_forged", hex::encode(forged), "E_POP_SIG"),
+        ] {
+            cases.push(Case {
+                name: format!("{}{}", name, suffix),
+                request: json!({
+                    "op": "pop",
+                    "user_key": user_key,
+                    "nonce": field(vector, "nonce")?,
+                    "pop_sig": pop_sig,
+                }),
+                expected: Expected::Result(result.to_string()),
+                requirement: Requirement::ProofOfPossession,
+            });
+        }
+    }
+
+    for vector in section("verification")? {
+        let result = field(vector, "result")?;
+        let mut request = json!({
+            "op": "verify",
+            "vendor_key": vector["vendor_key"],
+            "cert": field(vector, "cert_cbor")?,
+            "now": vector["now"],
+        });
+        let requirement = if vector.get("pop_sig").is_some() {
+            request["nonce"] = json!(field(vector, "nonce")?);
+            request["pop_sig"] = json!(field(vector, "pop_sig")?);
+            Requirement::ProofOfPossession
+        } else {
+            match result {
+                "E_CERT_PARSE" => Requirement::CanonicalCbor,
+                "E_CERT_EXPIRED" => Requirement::Expiry,
+                _ if vector["name"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .contains("expir") =>
+                {
+                    Requirement::Expiry
+                }
+                _ => Requirement::VendorSignature,
+            }
+        };
+        cases.push(Case {
+            name: field(vector, "name")?.to_string(),
+            request,
+            expected: Expected::Result(result.to_string()),
+            requirement,
+        });
+    }
+    Ok(cases)
+}
+
+/// Send every case through `call` and grade the answers per requirement.
+///
+/// A rejection with the wrong code still satisfies the functional
+/// requirement; the code itself is graded under [`Requirement::ErrorCodes`].
+pub fn run_cases(
+    cases: &[Case],
+    mut call: impl FnMut(&Value) -> Result<Value>,
+) -> Vec<RequirementReport> {
+    let mut reports: Vec<RequirementReport> = Requirement::ALL
+        .into_iter()
+        .map(|requirement| RequirementReport {
+            requirement,
+            title: requirement.title(),
+            passed: 0,
+            total: 0,
+            failures: Vec::new(),
+        })
+        .collect();
+    let mut record = |requirement: Requirement, case: &str, outcome: Option<String>| {
+        if let Some(report) = reports.iter_mut().find(|r| r.requirement == requirement) {
+            report.total += 1;
+            match outcome {
+                None => report.passed += 1,
+                Some(detail) => report.failures.push(CheckFailure {
+                    case: case.to_string(),
+                    detail,
+                }),
+            }
+        }
+    };
+
+    for (id, case) in cases.iter().enumerate() {
+        let mut request = case.request.clone();
+        request["id"] = json!(id);
+        let response = call(&request).and_then(|response| {
+            if let Some(error) = response.get("error") {
+                return Err(anyhow!("implementation error: {}", error));
+            }
+            if response["id"] != json!(id) {
+                return Err(anyhow!(
+                    "response id {} does not match {}",
+                    response["id"],
+                    id
+                ));
+            }
+            Ok(response)
+        });
+
+        match (&case.expected, response) {
+            (_, Err(err)) => {
+                record(case.requirement, &case.name, Some(err.to_string()));
+                if matches!(&case.expected, Expected::Result(r) if r != "OK") {
+                    record(Requirement::ErrorCodes, &case.name, Some(err.to_string()));
+                }
+            }
+            (
+                Expected::Encoding {
+                    payload_cbor,
+                    digest,
+                },
+                Ok(response),
+            ) => {
+                let outcome = if response["payload_cbor"].as_str() != Some(payload_cbor.as_str()) {
+                    Some("payload_cbor differs from the canonical encoding".to_string())
+                } else if response["digest"].as_str() != Some(digest.as_str()) {
+                    Some("digest differs".to_string())
+                } else {
+                    None
+                };
+                record(case.requirement, &case.name, outcome);
+            }
+            (Expected::Result(expected), Ok(response)) => {
+                let actual = response["result"].as_str().unwrap_or("<missing>");
+                let accepted = |result: &str| result == "OK";
+                let outcome = (accepted(actual) != accepted(expected)).then(|| {
+                    if accepted(expected) {
+                        format!("expected OK, got {}", actual)
+                    } else {
+                        format!("expected rejection ({}), got OK", expected)
+                    }
+                });
+                record(case.requirement, &case.name, outcome);
+                if !accepted(expected) {
+                    let outcome = (actual != expected)
+                        .then(|| format!("expected {}, got {}", expected, actual));
+                    record(Requirement::ErrorCodes, &case.name, outcome);
+                }
+            }
+        }
+    }
+    reports
+}
+
+/// Answer a request with `aegis-crm-core` (the reference implementation)
+pub fn reference_response(request: &Value) -> Value {
+    let answer = reference_answer(request);
+    let mut response = match answer {
+        Ok(response) => response,
+        Err(err) => json!({ "error": err.to_string() }),
+    };
+    response["id"] = request["id"].clone();
+    response
+}
+
+fn reference_answer(request: &Value) -> Result<Value> {
+    let bytes = |name: &str| -> Result<Vec<u8>> {
+        hex::decode(field(request, name)?).with_context(|| format!("'{}' is not hex", name))
+    };
+    let array = |name: &str| -> Result<[u8; 32]> {
+        bytes(name)?
+            .try_into()
+            .map_err(|_| anyhow!("'{}' must be 32 bytes", name))
+    };
+    let signature = |name: &str| -> Result<[u8; 64]> {
+        bytes(name)?
+            .try_into()
+            .map_err(|_| anyhow!("'{}' must be 64 bytes", name))
+    };
+    let key = |name: &str| -> std::result::Result<PublicKey, AegisError> {
+        let key = &request[name];
+        let alg = key["alg"].as_str().unwrap_or_default().parse()?;
+        let public = hex::decode(key["public"].as_str().unwrap_or_default())
+            .map_err(|_| AegisError::Key("invalid hex public key".to_string()))?;
+        PublicKey::new(alg, &public)
+    };
+    let result = |outcome: std::result::Result<(), AegisError>| json!({ "result": outcome.as_ref().map_or_else(AegisError::code, |_| "OK") });
+
+    match field(request, "op")? {
+        "encode" => {
+            let cert = decode_cert(&bytes("cert")?)?;
+            let mut payload = Vec::new();
+            ciborium::into_writer(&cert.payload, &mut payload)?;
+            Ok(json!({
+                "payload_cbor": hex::encode(&payload),
+                "digest": hex::encode(sha256(&payload)),
+            }))
+        }
+        "verify" => {
+            let now = request["now"]
+                .as_u64()
+                .ok_or_else(|| anyhow!("'now' must be an integer"))?;
+            let cert = bytes("cert")?;
+            let pop = match request.get("pop_sig") {
+                Some(_) => Some((array("nonce")?, signature("pop_sig")?)),
+                None => None,
+            };
+            Ok(result(key("vendor_key").and_then(|vendor_key| {
+                let cert = decode_cert(&cert)?;
+                match &pop {
+                    Some((nonce, pop_sig)) => {
+                        verify_license_with_key(&vendor_key, &cert, now, nonce, pop_sig)
+                    }
+                    None => verify_cert_with_key(&vendor_key, &cert, now),
+                }
+            })))
+        }
+        "pop" => {
+            let nonce = array("nonce")?;
+            let pop_sig = signature("pop_sig")?;
+            Ok(result(key("user_key").and_then(|user_key| {
+                verify_pop(&user_key, &nonce, &pop_sig)
+            })))
+        }
+        other => Err(anyhow!("unsupported op '{}'", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_implementation_conforms() {
+        let cases = load_cases(BUILTIN_VECTORS).unwrap();
+        let reports = run_cases(&cases, |request| Ok(reference_response(request)));
+        for report in &reports {
+            assert!(report.is_met(), "{}: {:?}", report.title, report.failures);
+        }
+        let total: usize = reports.iter().map(|r| r.total).sum();
+        assert!(total > cases.len());
+    }
+
+    #[test]
+    fn test_permissive_verifier_fails() {
+        let cases = load_cases(BUILTIN_VECTORS).unwrap();
+        // Accepts everything, as a verifier that skips the checks would
+        let reports = run_cases(&cases, |request| {
+            let mut response = reference_response(request);
+            if response.get("result").is_some() {
+                response["result"] = json!("OK");
+            }
+            Ok(response)
+        });
+        let met = |requirement: Requirement| {
+            reports
+                .iter()
+                .find(|r| r.requirement == requirement)
+                .unwrap()
+                .is_met()
+        };
+        assert!(!met(Requirement::VendorSignature));
+        assert!(!met(Requirement::ProofOfPossession));
+        assert!(!met(Requirement::Expiry));
+        assert!(!met(Requirement::ErrorCodes));
+        // Undecodable certificates are accepted too
+        assert!(!met(Requirement::CanonicalCbor));
+    }
+
+    #[test]
+    fn test_wrong_error_code_only_fails_error_codes() {
+        let cases = load_cases(BUILTIN_VECTORS).unwrap();
+        let reports = run_cases(&cases, |request| {
+            let mut response = reference_response(request);
+            if response["result"].as_str().is_some_and(|r| r != "OK") {
+                response["result"] = json!("E_GENERIC");
+            }
+            Ok(response)
+        });
+        for report in &reports {
+            assert_eq!(
+                report.is_met(),
+                report.requirement != Requirement::ErrorCodes,
+                "{}",
+                report.title
+            );
+        }
+    }
+}
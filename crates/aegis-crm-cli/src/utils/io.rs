@@ -1,29 +1,142 @@
 //! File I/O utilities
 
 use crate::error::Result;
+use crate::utils::qr::reject_png;
+use aegis_crm_core::crypto::SignatureAlgorithm;
+use aegis_crm_core::keys::{PrivateKey, PublicKey};
+use aegis_crm_core::pq::{PqAlgorithm, PqPrivateKey, PqPublicKey, PQ_SEED_LEN};
+use aegis_crm_core::uri::{is_aegis_uri, parse_uri, ActivationRequest, AegisUri};
 use anyhow::Context;
 use std::fs;
 use std::path::Path;
 
-/// Read hex string either directly or from a file path
-pub fn read_hex_or_file(input: &str) -> Result<Vec<u8>> {
-    // If it looks like a valid hex string, decode it directly
-    if (input.len() & 1) == 0 && input.chars().all(|c| c.is_ascii_hexdigit()) {
-        hex::decode(input).context("Invalid hex string")
+/// Split an optional `<alg>:` prefix off a key string (no prefix means secp256k1)
+fn split_key_prefix(input: &str) -> Result<(SignatureAlgorithm, &str)> {
+    match input.split_once(':') {
+        Some((alg, rest)) => Ok((alg.parse()?, rest)),
+        None => Ok((SignatureAlgorithm::Secp256k1, input)),
+    }
+}
+
+/// Read an algorithm-tagged key (`[<alg>:]<hex>`) either directly or from a file path
+pub fn read_key_or_file(input: &str) -> Result<(SignatureAlgorithm, Vec<u8>)> {
+    let inline = split_key_prefix(input)
+        .ok()
+        .filter(|(_, hex_str)| !hex_str.is_empty() && (hex_str.len() & 1) == 0)
+        .filter(|(_, hex_str)| hex_str.chars().all(|c| c.is_ascii_hexdigit()));
+    if let Some((alg, hex_str)) = inline {
+        return Ok((alg, hex::decode(hex_str).context("Invalid hex string")?));
+    }
+
+    let contents =
+        fs::read_to_string(input).with_context(|| format!("Failed to read file: {}", input))?;
+    let (alg, hex_str) = split_key_prefix(contents.trim())
+        .with_context(|| format!("Invalid key prefix in file: {}", input))?;
+    let bytes =
+        hex::decode(hex_str).with_context(|| format!("Invalid hex content in file: {}", input))?;
+    Ok((alg, bytes))
+}
+
+/// Read an algorithm-tagged private key
+pub fn read_private_key(input: &str) -> Result<PrivateKey> {
+    let (alg, bytes) = read_key_or_file(input)?;
+    let bytes: [u8; 32] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Private key must be 32 bytes, got {}", bytes.len()))?;
+    Ok(PrivateKey::new(alg, bytes))
+}
+
+/// Read an algorithm-tagged public key, or the key of an activation request
+pub fn read_public_key(input: &str) -> Result<PublicKey> {
+    if let Some(request) = read_activation_request(input)? {
+        return Ok(request.user_key);
+    }
+    let (alg, bytes) = read_key_or_file(input)?;
+    Ok(PublicKey::new(alg, &bytes)?)
+}
+
+/// Read an `aegis:request` URI given inline or in a text file;
+/// `None` when the input is not a URI
+pub fn read_activation_request(input: &str) -> Result<Option<ActivationRequest>> {
+    let text = if is_aegis_uri(input) {
+        input.to_string()
     } else {
-        // Otherwise treat as file path
-        let contents =
-            fs::read_to_string(input).with_context(|| format!("Failed to read file: {}", input))?;
+        let Ok(bytes) = fs::read(input) else {
+            return Ok(None);
+        };
+        reject_png(&bytes)?;
+        match String::from_utf8(bytes) {
+            Ok(text) if is_aegis_uri(&text) => text,
+            _ => return Ok(None),
+        }
+    };
+    match parse_uri(&text).context("Failed to parse activation request")? {
+        AegisUri::Request(request) => Ok(Some(request)),
+        AegisUri::License(_) => anyhow::bail!("Expected an activation request, found a license"),
+    }
+}
+
+/// Read a post-quantum key (`<alg>:<hex>`, prefix required) either directly or from a file path
+pub fn read_pq_key_or_file(input: &str) -> Result<(PqAlgorithm, Vec<u8>)> {
+    let inline = input
+        .split_once(':')
+        .filter(|(_, hex_str)| !hex_str.is_empty() && (hex_str.len() & 1) == 0)
+        .filter(|(_, hex_str)| hex_str.chars().all(|c| c.is_ascii_hexdigit()));
+    let contents = match inline {
+        Some(_) => input.to_string(),
+        None => {
+            fs::read_to_string(input).with_context(|| format!("Failed to read file: {}", input))?
+        }
+    };
+    let (alg, hex_str) = contents
+        .trim()
+        .split_once(':')
+        .context("PQ key must be prefixed with its algorithm (mldsa65:<hex>)")?;
+    let bytes = hex::decode(hex_str).context("Invalid hex in PQ key")?;
+    Ok((alg.parse()?, bytes))
+}
 
-        let hex_str = contents.trim();
-        hex::decode(hex_str).with_context(|| format!("Invalid hex content in file: {}", input))
+/// Read a post-quantum private key seed
+pub fn read_pq_private_key(input: &str) -> Result<PqPrivateKey> {
+    let (alg, bytes) = read_pq_key_or_file(input)?;
+    let seed: [u8; PQ_SEED_LEN] = bytes.as_slice().try_into().map_err(|_| {
+        anyhow::anyhow!(
+            "PQ private key must be {} bytes, got {}",
+            PQ_SEED_LEN,
+            bytes.len()
+        )
+    })?;
+    Ok(PqPrivateKey::new(alg, seed))
+}
+
+/// Read a post-quantum public key
+pub fn read_pq_public_key(input: &str) -> Result<PqPublicKey> {
+    let (alg, bytes) = read_pq_key_or_file(input)?;
+    Ok(PqPublicKey::new(alg, &bytes)?)
+}
+
+/// Write a post-quantum key file (always prefixed, `mldsa65:<hex>`)
+pub fn write_pq_key_file(path: &Path, alg: PqAlgorithm, data: &[u8]) -> Result<()> {
+    write_secret_file(path, &format!("{}:{}", alg, hex::encode(data)))
+}
+
+/// Write a key file, prefixing non-default algorithms (`ed25519:<hex>`)
+pub fn write_key_file(path: &Path, alg: SignatureAlgorithm, data: &[u8]) -> Result<()> {
+    if alg.is_default() {
+        return write_hex_file(path, data);
     }
+    write_secret_file(path, &format!("{}:{}", alg, hex::encode(data)))
 }
 
 /// Write hex-encoded data to file
 pub fn write_hex_file(path: &Path, data: &[u8]) -> Result<()> {
-    let hex_string = hex::encode(data);
-    fs::write(path, hex_string)
+    write_secret_file(path, &hex::encode(data))
+}
+
+/// Write a text file readable only by the owner
+fn write_secret_file(path: &Path, contents: &str) -> Result<()> {
+    fs::write(path, contents)
         .with_context(|| format!("Failed to write hex file: {}", path.display()))?;
 
     // Set restrictive permissions on Unix
@@ -71,3 +184,44 @@ pub fn ensure_directory(path: &Path) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_key_inline() {
+        let (alg, bytes) = read_key_or_file("0102").unwrap();
+        assert_eq!(alg, SignatureAlgorithm::Secp256k1);
+        assert_eq!(bytes, vec![1, 2]);
+
+        let (alg, bytes) = read_key_or_file("ed25519:0a0b").unwrap();
+        assert_eq!(alg, SignatureAlgorithm::Ed25519);
+        assert_eq!(bytes, vec![10, 11]);
+    }
+
+    #[test]
+    fn test_key_file_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        for alg in SignatureAlgorithm::ALL {
+            let path = dir.path().join(format!("{}.hex", alg));
+            write_key_file(&path, alg, &[0xab; 32]).unwrap();
+            let (read_alg, bytes) = read_key_or_file(path.to_str().unwrap()).unwrap();
+            assert_eq!(read_alg, alg);
+            assert_eq!(bytes, vec![0xab; 32]);
+        }
+        let v1 = fs::read_to_string(dir.path().join("secp256k1.hex")).unwrap();
+        assert!(!v1.contains(':'));
+    }
+
+    #[test]
+    fn test_pq_key_file_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pq.hex");
+        write_pq_key_file(&path, PqAlgorithm::MlDsa65, &[0xcd; 32]).unwrap();
+        let key = read_pq_private_key(path.to_str().unwrap()).unwrap();
+        assert_eq!(key.alg, PqAlgorithm::MlDsa65);
+        assert_eq!(key.seed, [0xcd; 32]);
+        assert!(read_pq_key_or_file(&"cd".repeat(32)).is_err());
+    }
+}
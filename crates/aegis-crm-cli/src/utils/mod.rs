@@ -1,4 +1,8 @@
 //! Utility modules
 
+pub mod cert;
+pub mod clock;
+pub mod conformance;
 pub mod io;
 pub mod payload;
+pub mod qr;
@@ -1,14 +1,53 @@
 //! Payload validation and processing
 
 use crate::error::Result;
+use aegis_crm_core::cert::{
+    Entitlement, FeatureGrant, LicensePayload, Limits, MetaValue, Metadata, PROTOCOL_VERSION,
+};
+use aegis_crm_core::clock::Clock;
+use aegis_crm_core::crypto::SignatureAlgorithm;
+use aegis_crm_core::features::{is_valid_pattern, FeatureCatalog};
+use aegis_crm_core::keys::PublicKey;
+use aegis_crm_core::policy::PolicySet;
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
 
-/// License tier types
+/// Feature entry from JSON: a bare name, or an object with its own validity window
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
-#[serde(rename_all = "snake_case")]
-pub enum LicenseTier {
-    LifetimePro,
-    Campus,
+#[serde(untagged)]
+pub enum FeatureJson {
+    Name(String),
+    Windowed {
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        not_before: Option<u64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        expires_at: Option<u64>,
+    },
+}
+
+impl FeatureJson {
+    /// Feature name regardless of form
+    pub fn name(&self) -> &str {
+        match self {
+            FeatureJson::Name(name) | FeatureJson::Windowed { name, .. } => name,
+        }
+    }
+
+    /// Convert to the core feature grant
+    pub fn to_grant(&self) -> FeatureGrant {
+        match self {
+            FeatureJson::Name(name) => FeatureGrant::new(name.as_str()),
+            FeatureJson::Windowed {
+                name,
+                not_before,
+                expires_at,
+            } => FeatureGrant::new(name.as_str()).with_window(*not_before, *expires_at),
+        }
+    }
 }
 
 /// License limits from JSON
@@ -20,57 +59,112 @@ pub struct LimitsJson {
     pub offline_grace_days: Option<u32>,
 }
 
-/// License metadata from JSON
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct MetadataJson {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub product: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub version: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub university: Option<String>,
+/// Metadata value from JSON: integer, string, or `{"hex": "..."}` for raw bytes
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum MetadataValueJson {
+    Int(i64),
+    Str(String),
+    Bytes {
+        #[serde(with = "hex::serde")]
+        hex: Vec<u8>,
+    },
 }
 
+impl From<&MetadataValueJson> for MetaValue {
+    fn from(value: &MetadataValueJson) -> Self {
+        match value {
+            MetadataValueJson::Int(i) => MetaValue::Int(*i),
+            MetadataValueJson::Str(s) => MetaValue::Str(s.clone()),
+            MetadataValueJson::Bytes { hex } => MetaValue::Bytes(hex.clone()),
+        }
+    }
+}
+
+impl From<&MetaValue> for MetadataValueJson {
+    fn from(value: &MetaValue) -> Self {
+        match value {
+            MetaValue::Int(i) => MetadataValueJson::Int(*i),
+            MetaValue::Str(s) => MetadataValueJson::Str(s.clone()),
+            MetaValue::Bytes(b) => MetadataValueJson::Bytes { hex: b.clone() },
+        }
+    }
+}
+
+/// License metadata from JSON (open key/value map)
+pub type MetadataJson = BTreeMap<String, MetadataValueJson>;
+
 /// License payload from JSON
 #[derive(Debug, Deserialize, Serialize)]
 pub struct LicensePayloadJson {
-    pub tier: LicenseTier,
+    pub tier: String,
     #[serde(rename = "expires_at", skip_serializing_if = "Option::is_none")]
     pub expires_at: Option<u64>,
     #[serde(default)]
-    pub features: Vec<String>,
+    pub features: Vec<FeatureJson>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub limits: Option<LimitsJson>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub entitlements: BTreeMap<String, Entitlement>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<MetadataJson>,
 }
 
 impl LicensePayloadJson {
-    /// Validate payload according to business rules
-    pub fn validate(&self) -> Result<()> {
-        // Campus tier MUST have expiry
-        if self.tier == LicenseTier::Campus && self.expires_at.is_none() {
-            anyhow::bail!(
-                "Campus tier requires expires_at field (must be a future Unix timestamp)"
-            );
+    /// Validate payload according to business rules, with expiry checked against `clock`
+    pub fn validate(&self, clock: &dyn Clock) -> Result<()> {
+        if self.tier.trim().is_empty() {
+            anyhow::bail!("tier must not be empty");
+        }
+
+        // Expiry must be in the future
+        if let Some(expiry) = self.expires_at {
+            let now = clock.now_unix()?;
+            if expiry <= now {
+                anyhow::bail!(
+                    "expires_at must be in the future (got: {}, now: {})",
+                    expiry,
+                    now
+                );
+            }
         }
 
-        // Validate campus expiry is in the future
-        if self.tier == LicenseTier::Campus {
-            if let Some(expiry) = self.expires_at {
-                let now = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)?
-                    .as_secs();
-                if expiry <= now {
-                    anyhow::bail!(
-                        "Campus tier expires_at must be in the future (got: {}, now: {})",
-                        expiry,
-                        now
+        // Validate feature names and per-feature windows
+        for feature in &self.features {
+            if !is_valid_pattern(feature.name()) {
+                anyhow::bail!(
+                    "Invalid feature name '{}' (use dot-separated segments, optionally ending in '.*')",
+                    feature.name()
+                );
+            }
+
+            feature.to_grant().check_window()?;
+
+            if let FeatureJson::Windowed {
+                name,
+                expires_at: Some(exp),
+                ..
+            } = feature
+            {
+                if self.expires_at.is_some_and(|cert_exp| *exp > cert_exp) {
+                    eprintln!(
+                        "⚠️  Warning: Feature '{}' expires after the certificate; certificate expiry still applies.",
+                        name
                     );
                 }
             }
         }
 
+        // Validate entitlement names
+        if self.entitlements.keys().any(|name| name.trim().is_empty()) {
+            anyhow::bail!("Entitlement names must not be empty");
+        }
+
+        // Validate metadata size limits
+        if let Some(meta) = self.core_metadata() {
+            meta.check_limits().map_err(|e| anyhow::anyhow!(e))?;
+        }
+
         // Validate features
         if self.features.is_empty() {
             eprintln!("⚠️  Warning: No features specified. License will have empty feature set.");
@@ -79,57 +173,131 @@ impl LicensePayloadJson {
         Ok(())
     }
 
+    /// Get metadata in core library format
+    pub fn core_metadata(&self) -> Option<Metadata> {
+        self.metadata.as_ref().map(|m| {
+            m.iter()
+                .map(|(k, v)| (k.clone(), MetaValue::from(v)))
+                .collect()
+        })
+    }
+
+    /// Get features in core library format
+    pub fn feature_grants(&self) -> Vec<FeatureGrant> {
+        self.features.iter().map(FeatureJson::to_grant).collect()
+    }
+
     /// Get tier as string for core library
     pub fn tier_string(&self) -> String {
-        match self.tier {
-            LicenseTier::LifetimePro => "lifetime_pro".to_string(),
-            LicenseTier::Campus => "campus".to_string(),
+        self.tier.clone()
+    }
+
+    /// Build the core library payload
+    pub fn to_core_payload(
+        &self,
+        user_key: &PublicKey,
+        license_id: [u8; 32],
+        issued_at: u64,
+    ) -> LicensePayload {
+        let limits = self.limits.as_ref().map(|l| Limits {
+            seat_max: l.seat_max,
+            offline_grace_days: l.offline_grace_days,
+        });
+        let metadata = self.core_metadata();
+
+        LicensePayload {
+            version: PROTOCOL_VERSION,
+            alg: SignatureAlgorithm::default(),
+            pq_alg: None,
+            product_id: self.tier_string(),
+            license_id,
+            issued_at,
+            expiry: self.expires_at,
+            user_pubkey: user_key.bytes.clone(),
+            user_alg: user_key.alg,
+            tier: self.tier_string(),
+            features: self.feature_grants(),
+            limits,
+            entitlements: self.entitlements.clone(),
+            metadata,
         }
     }
 }
 
+/// Load a tier policy file (TOML), or the built-in policy when no path is given
+pub fn load_policy(path: Option<&Path>) -> Result<PolicySet> {
+    let Some(path) = path else {
+        return Ok(PolicySet::builtin());
+    };
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read policy file: {}", path.display()))?;
+    toml::from_str(&contents)
+        .with_context(|| format!("Failed to parse policy file: {}", path.display()))
+}
+
+/// Load a feature catalog (JSON), or an empty catalog when no path is given
+pub fn load_catalog(path: Option<&Path>) -> Result<FeatureCatalog> {
+    let Some(path) = path else {
+        return Ok(FeatureCatalog::new());
+    };
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read catalog: {}", path.display()))?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse catalog: {}", path.display()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aegis_crm_core::clock::{FixedClock, SystemClock};
+
+    fn test_user_key() -> PublicKey {
+        PublicKey::secp256k1(&[0x02; 33])
+    }
 
     #[test]
     fn test_campus_requires_expiry() {
         let payload = LicensePayloadJson {
-            tier: LicenseTier::Campus,
+            tier: "campus".to_string(),
             expires_at: None,
             features: vec![],
             limits: None,
+            entitlements: BTreeMap::new(),
             metadata: None,
         };
 
-        assert!(payload.validate().is_err());
+        let mut core = payload.to_core_payload(&test_user_key(), [0u8; 32], 1700000000);
+        let err = load_policy(None).unwrap().apply(&mut core).unwrap_err();
+        assert!(err.to_string().contains("requires 'expires_at'"));
     }
 
     #[test]
     fn test_lifetime_pro_optional_expiry() {
         let payload = LicensePayloadJson {
-            tier: LicenseTier::LifetimePro,
+            tier: "lifetime_pro".to_string(),
             expires_at: None,
             features: vec![],
             limits: None,
+            entitlements: BTreeMap::new(),
             metadata: None,
         };
 
-        assert!(payload.validate().is_ok());
+        assert!(payload.validate(&SystemClock).is_ok());
     }
 
     #[test]
     fn test_features_all_wildcard() {
         let payload = LicensePayloadJson {
-            tier: LicenseTier::LifetimePro,
+            tier: "lifetime_pro".to_string(),
             expires_at: None,
-            features: vec!["ALL".to_string()],
+            features: vec![FeatureJson::Name("ALL".to_string())],
             limits: None,
+            entitlements: BTreeMap::new(),
             metadata: None,
         };
 
-        assert!(payload.validate().is_ok());
-        assert!(payload.features.contains(&"ALL".to_string()));
+        assert!(payload.validate(&SystemClock).is_ok());
+        assert!(payload.features.iter().any(|f| f.name() == "ALL"));
     }
 
     #[test]
@@ -141,13 +309,183 @@ mod tests {
             + 86400; // +1 day
 
         let payload = LicensePayloadJson {
-            tier: LicenseTier::Campus,
+            tier: "campus".to_string(),
             expires_at: Some(future),
-            features: vec!["education".to_string()],
+            features: vec![FeatureJson::Name("education".to_string())],
+            limits: None,
+            entitlements: BTreeMap::new(),
+            metadata: None,
+        };
+
+        assert!(payload.validate(&SystemClock).is_ok());
+    }
+
+    #[test]
+    fn test_expiry_checked_against_clock() {
+        let payload = LicensePayloadJson {
+            tier: "campus".to_string(),
+            expires_at: Some(1800000000),
+            features: vec![],
             limits: None,
+            entitlements: BTreeMap::new(),
             metadata: None,
         };
 
-        assert!(payload.validate().is_ok());
+        assert!(payload.validate(&FixedClock::new(1799999999)).is_ok());
+        let err = payload.validate(&FixedClock::new(1800000000)).unwrap_err();
+        assert!(err.to_string().contains("must be in the future"));
+    }
+
+    #[test]
+    fn test_windowed_feature_json() {
+        let json = r#"{
+            "tier": "lifetime_pro",
+            "features": ["base_access", {"name": "collaboration_tools", "expires_at": 1798761600}]
+        }"#;
+        let payload: LicensePayloadJson = serde_json::from_str(json).unwrap();
+        assert!(payload.validate(&SystemClock).is_ok());
+
+        let grants = payload.feature_grants();
+        assert!(!grants[0].is_windowed());
+        assert_eq!(grants[1].name, "collaboration_tools");
+        assert_eq!(grants[1].expiry, Some(1798761600));
+    }
+
+    #[test]
+    fn test_feature_window_order() {
+        let payload = LicensePayloadJson {
+            tier: "lifetime_pro".to_string(),
+            expires_at: None,
+            features: vec![FeatureJson::Windowed {
+                name: "trial".to_string(),
+                not_before: Some(1800000000),
+                expires_at: Some(1700000000),
+            }],
+            limits: None,
+            entitlements: BTreeMap::new(),
+            metadata: None,
+        };
+
+        let err = payload.validate(&SystemClock).unwrap_err();
+        assert!(err.to_string().contains("feature 'trial' not_before"));
+    }
+
+    #[test]
+    fn test_entitlements_json() {
+        let json = r#"{
+            "tier": "lifetime_pro",
+            "features": ["base_access"],
+            "entitlements": {
+                "max_projects": 10,
+                "api_rate": 500,
+                "cloud_sync": true,
+                "region": "eu",
+                "export_formats": ["pdf", "csv"]
+            }
+        }"#;
+        let payload: LicensePayloadJson = serde_json::from_str(json).unwrap();
+        assert!(payload.validate(&SystemClock).is_ok());
+        assert_eq!(
+            payload.entitlements.get("max_projects"),
+            Some(&Entitlement::Int(10))
+        );
+        assert_eq!(
+            payload.entitlements.get("export_formats"),
+            Some(&Entitlement::List(vec![
+                "pdf".to_string(),
+                "csv".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_entitlements_reject_float() {
+        let json = r#"{"tier": "lifetime_pro", "entitlements": {"ratio": 0.5}}"#;
+        assert!(serde_json::from_str::<LicensePayloadJson>(json).is_err());
+    }
+
+    #[test]
+    fn test_hierarchical_feature_names() {
+        let json = r#"{"tier": "lifetime_pro", "features": ["export.*", "editor.basic"]}"#;
+        let payload: LicensePayloadJson = serde_json::from_str(json).unwrap();
+        assert!(payload.validate(&SystemClock).is_ok());
+
+        let json = r#"{"tier": "lifetime_pro", "features": ["export.*.pdf"]}"#;
+        let payload: LicensePayloadJson = serde_json::from_str(json).unwrap();
+        assert!(payload.validate(&SystemClock).is_err());
+    }
+
+    #[test]
+    fn test_policy_toml() {
+        let policy: PolicySet = toml::from_str(
+            r#"
+            [tiers.campus]
+            required = ["expires_at", "metadata.university"]
+            max_duration_days = 366
+            allowed_features = ["base_access", "education.*"]
+
+            [tiers.campus.default_limits]
+            seat_max = 100
+            offline_grace_days = 14
+            "#,
+        )
+        .unwrap();
+
+        let json = r#"{
+            "tier": "campus",
+            "expires_at": 1730000000,
+            "features": ["base_access", "education.labs"],
+            "metadata": {"university": "Example University"}
+        }"#;
+        let payload: LicensePayloadJson = serde_json::from_str(json).unwrap();
+        let mut core = payload.to_core_payload(&test_user_key(), [0u8; 32], 1700000000);
+        policy.apply(&mut core).unwrap();
+        assert_eq!(core.limits.unwrap().seat_max, Some(100));
+
+        let mut core = payload.to_core_payload(&test_user_key(), [0u8; 32], 1600000000);
+        assert!(policy.apply(&mut core).is_err());
+
+        let mut core = payload.to_core_payload(&test_user_key(), [0u8; 32], 1700000000);
+        core.tier = "enterprise".to_string();
+        assert!(policy.apply(&mut core).is_err());
+    }
+
+    #[test]
+    fn test_generic_metadata_json() {
+        let json = r#"{
+            "tier": "lifetime_pro",
+            "metadata": {
+                "product": "Example Product",
+                "customer_id": 4711,
+                "hw_fingerprint": {"hex": "deadbeef"}
+            }
+        }"#;
+        let payload: LicensePayloadJson = serde_json::from_str(json).unwrap();
+        assert!(payload.validate(&SystemClock).is_ok());
+
+        let meta = payload.core_metadata().unwrap();
+        assert_eq!(meta.get_str("product"), Some("Example Product"));
+        assert_eq!(meta.get("customer_id"), Some(&MetaValue::Int(4711)));
+        assert_eq!(
+            meta.get("hw_fingerprint"),
+            Some(&MetaValue::Bytes(vec![0xde, 0xad, 0xbe, 0xef]))
+        );
+    }
+
+    #[test]
+    fn test_metadata_value_too_long() {
+        let payload = LicensePayloadJson {
+            tier: "lifetime_pro".to_string(),
+            expires_at: None,
+            features: vec![],
+            limits: None,
+            entitlements: BTreeMap::new(),
+            metadata: Some(BTreeMap::from([(
+                "notes".to_string(),
+                MetadataValueJson::Str("x".repeat(2000)),
+            )])),
+        };
+
+        assert!(payload.validate(&SystemClock).is_err());
     }
 }
@@ -0,0 +1,169 @@
+//! QR code rendering (PNG or ANSI terminal)
+//!
+//! Payloads are always encoded as a single byte-mode segment. Images are not
+//! read back: users scan them with a QR reader and pass on the `aegis:` URI.
+
+use crate::error::Result;
+use anyhow::{bail, Context};
+use image::Luma;
+use qrcode::bits::Bits;
+use qrcode::render::unicode::Dense1x2;
+use qrcode::{EcLevel, QrCode, Version};
+use std::path::Path;
+
+/// Pixels per module in PNG output
+const PNG_MODULE_PX: u32 = 8;
+/// Error correction used for rendering
+const EC_LEVEL: EcLevel = EcLevel::M;
+
+/// Build the smallest QR code holding `data` as one byte-mode segment
+pub fn encode_qr(data: &[u8]) -> Result<QrCode> {
+    for version in 1..=40 {
+        let mut bits = Bits::new(Version::Normal(version));
+        if bits.push_byte_data(data).is_ok() && bits.push_terminator(EC_LEVEL).is_ok() {
+            return QrCode::with_bits(bits, EC_LEVEL).context("Failed to build QR code");
+        }
+    }
+    bail!("{} bytes is too large for a QR code", data.len())
+}
+
+/// Reject terminal QR output together with `--json`, which must stay parseable
+pub fn check_qr_target(target: Option<&Path>, json: bool) -> Result<()> {
+    if json && target == Some(Path::new("-")) {
+        bail!("--qr - prints to the terminal and cannot be combined with --json");
+    }
+    Ok(())
+}
+
+/// Render `data` to a PNG file, or to the terminal when `target` is `-`
+pub fn write_qr(data: &[u8], target: &Path) -> Result<()> {
+    let code = encode_qr(data)?;
+    if target == Path::new("-") {
+        println!("{}", render_ansi(&code));
+        return Ok(());
+    }
+    code.render::<Luma<u8>>()
+        .module_dimensions(PNG_MODULE_PX, PNG_MODULE_PX)
+        .build()
+        .save(target)
+        .with_context(|| format!("Failed to write QR image: {}", target.display()))
+}
+
+/// Half-block rendering with explicit black-on-white colors, so the code
+/// scans on dark terminal themes too
+pub fn render_ansi(code: &QrCode) -> String {
+    code.render::<Dense1x2>()
+        .quiet_zone(true)
+        .build()
+        .lines()
+        .map(|line| format!("\x1b[30;47m{}\x1b[0m", line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Whether `bytes` is a PNG file
+pub fn is_png(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"\x89PNG\r\n\x1a\n")
+}
+
+/// Refuse QR code images: they are not decoded here, since reading them
+/// reliably needs a full scanner with error correction
+pub fn reject_png(bytes: &[u8]) -> Result<()> {
+    if is_png(bytes) {
+        bail!("QR code images are not decoded; scan the code with a QR reader and pass the resulting aegis: URI instead");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aegis_crm_core::cert::decode_cert;
+    use aegis_crm_core::uri::license_uri;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    /// Read a rendered PNG back with an independent QR decoder
+    fn scan(path: &Path) -> Vec<u8> {
+        let image = image::open(path).unwrap().to_luma8();
+        let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(
+            image.width() as usize,
+            image.height() as usize,
+            |x, y| image.get_pixel(x as u32, y as u32).0[0],
+        );
+        let grids = prepared.detect_grids();
+        assert_eq!(grids.len(), 1);
+        let mut data = Vec::new();
+        grids[0].decode_to(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_issue_qr_scans_back() {
+        let fixture =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../../fixtures/license.cert.base64");
+        let cert = STANDARD
+            .decode(std::fs::read_to_string(fixture).unwrap().trim())
+            .unwrap();
+        let uri = license_uri(&decode_cert(&cert).unwrap()).unwrap();
+
+        // `issue --qr` renders the raw certificate, `issue --qr --qr-uri` its aegis: URI
+        let dir = tempfile::tempdir().unwrap();
+        for data in [cert, uri.into_bytes()] {
+            let path = dir.path().join("license.png");
+            write_qr(&data, &path).unwrap();
+            assert_eq!(scan(&path), data);
+        }
+    }
+
+    #[test]
+    fn test_png_matches_module_grid() {
+        let dir = tempfile::tempdir().unwrap();
+        // Versions 1 (no alignment), 7+ (version info) and 10+ (16-bit length)
+        for len in [10usize, 200, 400, 1200] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 37 % 251) as u8).collect();
+            let path = dir.path().join(format!("{}.png", len));
+            write_qr(&data, &path).unwrap();
+            let bytes = std::fs::read(&path).unwrap();
+            assert!(is_png(&bytes));
+
+            // Whole-pixel modules inside a 4-module quiet zone
+            let code = encode_qr(&data).unwrap();
+            let image = image::load_from_memory(&bytes).unwrap().to_luma8();
+            let width = code.width() as u32;
+            assert_eq!(image.dimensions().0, (width + 8) * PNG_MODULE_PX);
+            for y in 0..width {
+                for x in 0..width {
+                    let pixel = image.get_pixel(
+                        (x + 4) * PNG_MODULE_PX + PNG_MODULE_PX / 2,
+                        (y + 4) * PNG_MODULE_PX + PNG_MODULE_PX / 2,
+                    );
+                    let dark = code[(x as usize, y as usize)] == qrcode::Color::Dark;
+                    assert_eq!(pixel.0[0] < 128, dark);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_png_input_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("code.png");
+        write_qr(b"aegis:license?c=AA", &path).unwrap();
+        let err = reject_png(&std::fs::read(&path).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("aegis: URI"));
+        assert!(reject_png(b"aegis:license?c=AA").is_ok());
+    }
+
+    #[test]
+    fn test_ansi_rendering() {
+        let code = encode_qr(b"aegis:request?alg=secp256k1&upk=02").unwrap();
+        let text = render_ansi(&code);
+        assert!(text.lines().all(|line| line.starts_with("\x1b[30;47m")));
+        assert!(text.contains('\u{2588}') || text.contains('\u{2580}'));
+    }
+
+    #[test]
+    fn test_too_large_rejected() {
+        assert!(encode_qr(&[0u8; 4000]).is_err());
+    }
+}
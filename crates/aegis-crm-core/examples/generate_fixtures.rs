@@ -2,10 +2,11 @@
 
 use aegis_crm_core::{
     cert::{encode_cert, issue_cert, LicensePayload, Limits, Metadata, PROTOCOL_VERSION},
-    crypto::pubkey_from_privkey_inner,
+    crypto::{pubkey_from_privkey_inner, SignatureAlgorithm},
     pop::{prove, PopSignature},
 };
 use base64::{engine::general_purpose::STANDARD, Engine};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
@@ -36,22 +37,26 @@ fn main() {
     // Lifetime Pro payload
     let payload_lifetime = LicensePayload {
         version: PROTOCOL_VERSION,
+        alg: SignatureAlgorithm::Secp256k1,
+        pq_alg: None,
         product_id: "example_product".to_string(),
         license_id,
         issued_at: 1768227654,
         expiry: None,
-        user_pubkey: user_pub,
+        user_pubkey: user_pub.to_vec(),
+        user_alg: SignatureAlgorithm::Secp256k1,
         tier: "lifetime_pro".to_string(),
-        features: vec!["base_access".to_string(), "premium_features".to_string()],
+        features: vec!["base_access".into(), "premium_features".into()],
         limits: Some(Limits {
             seat_max: Some(1),
             offline_grace_days: None,
         }),
-        metadata: Some(Metadata {
-            product: Some("Example Product".to_string()),
-            version: Some("1.0".to_string()),
-            university: None,
-        }),
+        entitlements: BTreeMap::new(),
+        metadata: Some(
+            Metadata::new()
+                .with("product", "Example Product")
+                .with("version", "1.0"),
+        ),
     };
 
     let cert = issue_cert(&vendor_priv, payload_lifetime).expect("Failed to issue cert");
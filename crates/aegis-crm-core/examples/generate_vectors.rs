@@ -0,0 +1,404 @@
+//! Generate the cross-language known-answer test vectors (`fixtures/vectors.json`)
+//!
+//! Everything is fixed: keys, license IDs, nonces and timestamps. ECDSA
+//! (RFC 6979) and Ed25519 signatures are deterministic, so the output is
+//! byte-stable; BIP340 Schnorr is left out because its signatures mix in
+//! fresh auxiliary randomness.
+
+use aegis_crm_core::{
+    cert::{
+        encode_cert, issue_cert_with_policy, Entitlement, FeatureGrant, LicenseCert,
+        LicensePayload, Limits, Metadata, PROTOCOL_VERSION,
+    },
+    crypto::{sha256, SignatureAlgorithm},
+    keys::{PrivateKey, PublicKey},
+    policy::PolicySet,
+    pop::prove_with_key,
+};
+use ciborium::Value;
+use serde_json::{json, Value as Json};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+const VENDOR_SEED: [u8; 32] = seed(0x01);
+const USER_SEED: [u8; 32] = seed(0x21);
+const OTHER_SEED: [u8; 32] = seed(0x41);
+
+const ISSUED_AT: u64 = 1_768_227_654;
+const EXPIRES_AT: u64 = 1_800_000_000;
+/// Verification time for certificates that do not expire
+const VERIFY_AT: u64 = 1_790_000_000;
+
+const ALGS: [SignatureAlgorithm; 3] = [
+    SignatureAlgorithm::Secp256k1,
+    SignatureAlgorithm::P256,
+    SignatureAlgorithm::Ed25519,
+];
+
+/// `start, start + 1, ..., start + 31`
+const fn seed(start: u8) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    let mut i = 0;
+    while i < 32 {
+        bytes[i] = start + i as u8;
+        i += 1;
+    }
+    bytes
+}
+
+fn main() {
+    println!("Generating test vectors...");
+
+    let mut keys = Vec::new();
+    let mut certificates = Vec::new();
+    let mut pops = Vec::new();
+    let mut issued = BTreeMap::new();
+
+    for alg in ALGS {
+        let vendor = PrivateKey::new(alg, VENDOR_SEED);
+        let user = PrivateKey::new(alg, USER_SEED);
+        keys.push(key_json(&format!("vendor_{}", alg), &vendor));
+        keys.push(key_json(&format!("user_{}", alg), &user));
+
+        let payload = sample_payload(alg, &user, "perpetual");
+        let cert = issue(&vendor, payload);
+        certificates.push(cert_json(&format!("{}_perpetual", alg), alg, &cert));
+        issued.insert(alg.name(), cert);
+
+        let nonce = [0xaa; 32];
+        let pop_sig = prove_with_key(&user, &nonce).expect("Failed to prove");
+        pops.push(json!({
+            "name": format!("{}_pop", alg),
+            "user_key": format!("user_{}", alg),
+            "nonce": hex::encode(nonce),
+            "digest": hex::encode(sha256(&nonce)),
+            "signature": hex::encode(pop_sig),
+        }));
+    }
+
+    // Every optional field, with an expiry
+    let vendor = PrivateKey::secp256k1(VENDOR_SEED);
+    let user = PrivateKey::secp256k1(USER_SEED);
+    let mut payload = sample_payload(SignatureAlgorithm::Secp256k1, &user, "full");
+    payload.expiry = Some(EXPIRES_AT);
+    payload.tier = "campus".to_string();
+    payload.features = vec![
+        "base".into(),
+        FeatureGrant::new("beta.sync").with_window(Some(ISSUED_AT), Some(EXPIRES_AT - 1)),
+    ];
+    payload.limits = Some(Limits {
+        seat_max: Some(5),
+        offline_grace_days: Some(14),
+    });
+    payload.entitlements = BTreeMap::from([
+        ("max_projects".to_string(), Entitlement::Int(10)),
+        (
+            "export_formats".to_string(),
+            Entitlement::List(vec!["pdf".to_string(), "csv".to_string()]),
+        ),
+        ("sso".to_string(), Entitlement::Bool(true)),
+    ]);
+    payload.metadata = Some(
+        Metadata::new()
+            .with("customer", "ACME Corp")
+            .with("seats", 5),
+    );
+    let full = issue(&vendor, payload);
+    certificates.push(cert_json(
+        "secp256k1_full",
+        SignatureAlgorithm::Secp256k1,
+        &full,
+    ));
+
+    let verification = verification_cases(&issued, &full);
+
+    let vectors = json!({
+        "description": "Aegis CRM Standard v1.0 known-answer test vectors. Test keys only, never use them in production.",
+        "generator": "cargo run -p aegis-crm-core --example generate_vectors",
+        "notes": [
+            "payload_cbor is the CBOR encoding of the certificate map without sig (and without pqsig).",
+            "cert_cbor is written as an indefinite-length map; decoders must accept both map forms.",
+            "digest = SHA-256(payload_cbor).",
+            "signature is computed with digest as the message: ECDSA-SHA256 (RFC 6979 nonce, low-S, r||s) or Ed25519 over the 32 digest bytes.",
+            "PoP signs SHA-256(nonce) the same way with the user key.",
+            "Verification: parse the vendor key (E_KEY), decode cert (E_CERT_PARSE), check v (E_UNSUPPORTED_VER), alg and signature (E_CERT_SIG), expiry with now <= exp (E_CERT_EXPIRED), then PoP if given (E_POP_SIG).",
+        ],
+        "keys": keys,
+        "certificates": certificates,
+        "pop": pops,
+        "verification": verification,
+    });
+
+    let path = Path::new("fixtures").join("vectors.json");
+    let mut text = serde_json::to_string_pretty(&vectors).expect("Failed to serialize vectors");
+    text.push('\n');
+    fs::write(&path, text).expect("Failed to write vectors.json");
+
+    println!("✅ Test vectors written to {}", path.display());
+}
+
+fn sample_payload(alg: SignatureAlgorithm, user: &PrivateKey, label: &str) -> LicensePayload {
+    let user_key = user.public_key().expect("Failed to derive user key");
+    LicensePayload {
+        version: PROTOCOL_VERSION,
+        alg,
+        pq_alg: None,
+        product_id: "example_product".to_string(),
+        license_id: sha256(format!("aegis test vector {} {}", alg, label).as_bytes()),
+        issued_at: ISSUED_AT,
+        expiry: None,
+        user_pubkey: user_key.bytes,
+        user_alg: user_key.alg,
+        tier: "lifetime_pro".to_string(),
+        features: vec!["base_access".into(), "premium_features".into()],
+        limits: None,
+        entitlements: BTreeMap::new(),
+        metadata: None,
+    }
+}
+
+fn issue(vendor: &PrivateKey, payload: LicensePayload) -> LicenseCert {
+    issue_cert_with_policy(vendor, payload, &PolicySet::builtin()).expect("Failed to issue cert")
+}
+
+/// Re-sign `payload` directly, bypassing issuance checks (for invalid certificates)
+fn sign_raw(vendor: &PrivateKey, payload: LicensePayload) -> LicenseCert {
+    let vendor_sig = vendor
+        .alg
+        .scheme()
+        .sign(&vendor.bytes, &sha256(&cbor(&payload)))
+        .expect("Failed to sign");
+    LicenseCert {
+        payload,
+        vendor_sig,
+        pq_sig: None,
+    }
+}
+
+fn cbor<T: serde::Serialize>(value: &T) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(value, &mut bytes).expect("Failed to encode CBOR");
+    bytes
+}
+
+fn encoded(cert: &LicenseCert) -> Vec<u8> {
+    encode_cert(cert).expect("Failed to encode cert")
+}
+
+fn key_json(name: &str, key: &PrivateKey) -> Json {
+    let public = key.public_key().expect("Failed to derive public key");
+    json!({
+        "name": name,
+        "alg": key.alg.name(),
+        "private": hex::encode(key.bytes),
+        "public": hex::encode(public.bytes),
+    })
+}
+
+fn cert_json(name: &str, alg: SignatureAlgorithm, cert: &LicenseCert) -> Json {
+    let payload_cbor = cbor(&cert.payload);
+    json!({
+        "name": name,
+        "vendor_key": format!("vendor_{}", alg),
+        "payload_cbor": hex::encode(&payload_cbor),
+        "digest": hex::encode(sha256(&payload_cbor)),
+        "signature": hex::encode(cert.vendor_sig),
+        "cert_cbor": hex::encode(encoded(cert)),
+    })
+}
+
+/// Replace one field of an encoded certificate map
+fn with_field(cert: &[u8], key: &str, value: Value) -> Vec<u8> {
+    let mut map: Value = ciborium::from_reader(cert).expect("Failed to decode cert");
+    let entries = map.as_map_mut().expect("Certificate is not a map");
+    let entry = entries
+        .iter_mut()
+        .find(|(k, _)| k.as_text() == Some(key))
+        .expect("Missing certificate field");
+    entry.1 = value;
+    cbor(&map)
+}
+
+/// Negate `s`, giving the malleable twin of a valid secp256k1 signature
+fn high_s(sig: &[u8; 64]) -> [u8; 64] {
+    let signature = k256::ecdsa::Signature::from_slice(sig).expect("Invalid signature");
+    let (r, s) = signature.split_scalars();
+    let flipped = k256::ecdsa::Signature::from_scalars(r.to_bytes(), (-*s).to_bytes())
+        .expect("Invalid signature");
+    flipped.to_bytes().into()
+}
+
+fn verification_cases(issued: &BTreeMap<&str, LicenseCert>, full: &LicenseCert) -> Vec<Json> {
+    let secp = SignatureAlgorithm::Secp256k1;
+    let vendor = PrivateKey::secp256k1(VENDOR_SEED);
+    let user = PrivateKey::secp256k1(USER_SEED);
+    let other = PrivateKey::secp256k1(OTHER_SEED);
+    let vendor_pub = vendor.public_key().expect("Failed to derive vendor key");
+    let perpetual = &issued[secp.name()];
+    let perpetual_cbor = encoded(perpetual);
+
+    let nonce = [0xaa; 32];
+    let pop = prove_with_key(&user, &nonce).expect("Failed to prove");
+    let pop_other_nonce = prove_with_key(&user, &[0xbb; 32]).expect("Failed to prove");
+    let pop_other_key = prove_with_key(&other, &nonce).expect("Failed to prove");
+
+    let mut v2 = perpetual.payload.clone();
+    v2.version = 2;
+    let mut high_s_cert = perpetual.clone();
+    high_s_cert.vendor_sig = high_s(&perpetual.vendor_sig);
+
+    let case = |name: &str, key: &PublicKey, cert: &[u8], now: u64, result: &str| {
+        json!({
+            "name": name,
+            "vendor_key": { "alg": key.alg.name(), "public": hex::encode(&key.bytes) },
+            "cert_cbor": hex::encode(cert),
+            "now": now,
+            "result": result,
+        })
+    };
+    let with_pop = |mut case: Json, sig: &[u8; 64]| {
+        case["nonce"] = json!(hex::encode(nonce));
+        case["pop_sig"] = json!(hex::encode(sig));
+        case
+    };
+
+    let mut cases = Vec::new();
+    for alg in ALGS {
+        let key = PrivateKey::new(alg, VENDOR_SEED)
+            .public_key()
+            .expect("Failed to derive vendor key");
+        let cert = encoded(&issued[alg.name()]);
+        cases.push(case(
+            &format!("{}_valid", alg),
+            &key,
+            &cert,
+            VERIFY_AT,
+            "OK",
+        ));
+    }
+    cases.extend([
+        with_pop(
+            case(
+                "valid_with_pop",
+                &vendor_pub,
+                &perpetual_cbor,
+                VERIFY_AT,
+                "OK",
+            ),
+            &pop,
+        ),
+        case(
+            "valid_at_expiry",
+            &vendor_pub,
+            &encoded(full),
+            EXPIRES_AT,
+            "OK",
+        ),
+        case(
+            "expired",
+            &vendor_pub,
+            &encoded(full),
+            EXPIRES_AT + 1,
+            "E_CERT_EXPIRED",
+        ),
+        case(
+            "not_cbor",
+            &vendor_pub,
+            &[0xff, 0x00],
+            VERIFY_AT,
+            "E_CERT_PARSE",
+        ),
+        case(
+            "truncated",
+            &vendor_pub,
+            &perpetual_cbor[..perpetual_cbor.len() - 10],
+            VERIFY_AT,
+            "E_CERT_PARSE",
+        ),
+        case(
+            "missing_signature",
+            &vendor_pub,
+            &cbor(&perpetual.payload),
+            VERIFY_AT,
+            "E_CERT_PARSE",
+        ),
+        case(
+            "short_signature",
+            &vendor_pub,
+            &with_field(
+                &perpetual_cbor,
+                "sig",
+                Value::Bytes(perpetual.vendor_sig[..63].to_vec()),
+            ),
+            VERIFY_AT,
+            "E_CERT_PARSE",
+        ),
+        case(
+            "unsupported_version",
+            &vendor_pub,
+            &encoded(&sign_raw(&vendor, v2)),
+            VERIFY_AT,
+            "E_UNSUPPORTED_VER",
+        ),
+        case(
+            "tampered_tier",
+            &vendor_pub,
+            &with_field(&perpetual_cbor, "tier", Value::Text("enterprise".into())),
+            VERIFY_AT,
+            "E_CERT_SIG",
+        ),
+        case(
+            "wrong_vendor_key",
+            &other.public_key().expect("Failed to derive key"),
+            &perpetual_cbor,
+            VERIFY_AT,
+            "E_CERT_SIG",
+        ),
+        case(
+            "algorithm_mismatch",
+            &vendor_pub,
+            &encoded(&issued[SignatureAlgorithm::P256.name()]),
+            VERIFY_AT,
+            "E_CERT_SIG",
+        ),
+        case(
+            "high_s_signature",
+            &vendor_pub,
+            &encoded(&high_s_cert),
+            VERIFY_AT,
+            "E_CERT_SIG",
+        ),
+        case(
+            "invalid_vendor_key",
+            &PublicKey {
+                alg: secp,
+                bytes: vendor_pub.bytes[1..].to_vec(),
+            },
+            &perpetual_cbor,
+            VERIFY_AT,
+            "E_KEY",
+        ),
+        with_pop(
+            case(
+                "pop_wrong_nonce",
+                &vendor_pub,
+                &perpetual_cbor,
+                VERIFY_AT,
+                "E_POP_SIG",
+            ),
+            &pop_other_nonce,
+        ),
+        with_pop(
+            case(
+                "pop_wrong_key",
+                &vendor_pub,
+                &perpetual_cbor,
+                VERIFY_AT,
+                "E_POP_SIG",
+            ),
+            &pop_other_key,
+        ),
+    ]);
+    cases
+}
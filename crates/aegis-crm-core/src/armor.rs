@@ -0,0 +1,339 @@
+//! ASCII armor for copy/paste delivery of license certificates.
+//!
+//! ```text
+//! -----BEGIN AEGIS LICENSE-----
+//! Product: my_app
+//! Tier: lifetime_pro
+//! Expires: never
+//!
+//! <base64, 64 columns>
+//! =<CRC-24 of the decoded bytes, base64>
+//! -----END AEGIS LICENSE-----
+//! ```
+//!
+//! Headers are informational and not covered by any signature; the decoder
+//! rejects headers that disagree with the certificate. The CRC is the
+//! OpenPGP CRC-24 (RFC 4880 §6.1) and catches corruption before CBOR decoding.
+
+#[cfg(feature = "cli-helpers")]
+use crate::cert::encode_cert;
+use crate::cert::{decode_cert, LicenseCert, LicensePayload};
+use crate::errors::AegisError;
+use crate::prelude::*;
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+/// First line of an armored certificate.
+pub const ARMOR_BEGIN: &str = "-----BEGIN AEGIS LICENSE-----";
+/// Last line of an armored certificate.
+pub const ARMOR_END: &str = "-----END AEGIS LICENSE-----";
+/// Base64 characters per body line.
+pub const ARMOR_LINE_LEN: usize = 64;
+
+/// Decoded armor block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Armored {
+    /// `Key: Value` header lines, in order
+    pub headers: Vec<(String, String)>,
+    /// Decoded body bytes (CRC already checked)
+    pub data: Vec<u8>,
+}
+
+impl Armored {
+    /// First header value for `key` (case-insensitive).
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Whether `text` contains an armor block.
+pub fn is_armored(text: &str) -> bool {
+    text.contains(ARMOR_BEGIN)
+}
+
+/// Armor arbitrary bytes with the given headers.
+#[cfg(feature = "cli-helpers")]
+pub fn armor(data: &[u8], headers: &[(&str, String)]) -> String {
+    let body = STANDARD.encode(data);
+    let mut out = String::with_capacity(body.len() * 65 / 64 + 128);
+    out.push_str(ARMOR_BEGIN);
+    out.push('\n');
+    for (key, value) in headers {
+        out.push_str(key);
+        out.push_str(": ");
+        out.push_str(value);
+        out.push('\n');
+    }
+    out.push('\n');
+    for line in body.as_bytes().chunks(ARMOR_LINE_LEN) {
+        // base64 output is ASCII
+        out.push_str(core::str::from_utf8(line).unwrap_or_default());
+        out.push('\n');
+    }
+    out.push('=');
+    out.push_str(&STANDARD.encode(&crc24(data).to_be_bytes()[1..]));
+    out.push('\n');
+    out.push_str(ARMOR_END);
+    out.push('\n');
+    out
+}
+
+/// Parse the first armor block in `text` and check its CRC.
+///
+/// Surrounding text, indentation, CRLF line endings and wrapping at any
+/// width are tolerated, since pasted licenses are routinely reflowed.
+pub fn dearmor(text: &str) -> Result<Armored, AegisError> {
+    let start = text
+        .find(ARMOR_BEGIN)
+        .ok_or_else(|| AegisError::Armor("missing BEGIN line".to_string()))?;
+    let rest = &text[start + ARMOR_BEGIN.len()..];
+    let end = rest
+        .find(ARMOR_END)
+        .ok_or_else(|| AegisError::Armor("missing END line".to_string()))?;
+
+    let mut lines = rest[..end].lines().map(str::trim).peekable();
+    // Remainder of the BEGIN line
+    lines.next();
+
+    let mut headers = Vec::new();
+    while let Some(line) = lines.peek() {
+        if line.is_empty() {
+            lines.next();
+            break;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            // No header block
+            break;
+        };
+        headers.push((key.trim().to_string(), value.trim().to_string()));
+        lines.next();
+    }
+
+    let mut body = String::new();
+    let mut checksum = None;
+    for line in lines {
+        if let Some(crc) = line.strip_prefix('=') {
+            checksum = Some(crc.to_string());
+            break;
+        }
+        body.extend(line.chars().filter(|c| !c.is_whitespace()));
+    }
+
+    let data = STANDARD
+        .decode(&body)
+        .map_err(|_| AegisError::Armor("invalid base64 body".to_string()))?;
+    let checksum = checksum.ok_or_else(|| AegisError::Armor("missing checksum".to_string()))?;
+    let expected = STANDARD
+        .decode(&checksum)
+        .ok()
+        .filter(|crc| crc.len() == 3)
+        .ok_or_else(|| AegisError::Armor("invalid checksum line".to_string()))?;
+    if expected != crc24(&data).to_be_bytes()[1..] {
+        return Err(AegisError::Armor(
+            "checksum mismatch, the license text is corrupted".to_string(),
+        ));
+    }
+
+    Ok(Armored { headers, data })
+}
+
+/// Armor a certificate with `Product`, `Tier` and `Expires` headers.
+#[cfg(feature = "cli-helpers")]
+pub fn encode_cert_armored(cert: &LicenseCert) -> Result<String, AegisError> {
+    let payload = &cert.payload;
+    let headers = [
+        ("Product", payload.product_id.clone()),
+        ("Tier", payload.tier.clone()),
+        ("Expires", expires_header(payload.expiry)),
+    ];
+    Ok(armor(&encode_cert(cert)?, &headers))
+}
+
+/// Decode an armored certificate, rejecting headers that contradict it.
+pub fn decode_cert_armored(text: &str) -> Result<LicenseCert, AegisError> {
+    let armored = dearmor(text)?;
+    let cert = decode_cert(&armored.data)?;
+    check_headers(&armored, &cert.payload)?;
+    Ok(cert)
+}
+
+/// Decode a certificate given as raw CBOR, base64 text or ASCII armor.
+pub fn decode_cert_auto(bytes: &[u8]) -> Result<LicenseCert, AegisError> {
+    let text = core::str::from_utf8(bytes).ok();
+    if let Some(text) = text.filter(|text| is_armored(text)) {
+        return decode_cert_armored(text);
+    }
+    decode_cert(bytes).or_else(|err| {
+        let compact: String = text.ok_or(err)?.split_whitespace().collect();
+        let data = STANDARD
+            .decode(compact)
+            .map_err(|_| AegisError::parse("not CBOR, base64 or ASCII armor"))?;
+        decode_cert(&data)
+    })
+}
+
+/// Check the informational headers against the certificate payload.
+pub fn check_headers(armored: &Armored, payload: &LicensePayload) -> Result<(), AegisError> {
+    let expected = [
+        ("Product", payload.product_id.clone()),
+        ("Tier", payload.tier.clone()),
+        ("Expires", expires_header(payload.expiry)),
+    ];
+    for (key, value) in expected {
+        if armored.header(key).is_some_and(|v| v != value) {
+            return Err(AegisError::Armor(format!(
+                "{} header does not match the certificate",
+                key
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn expires_header(expiry: Option<u64>) -> String {
+    expiry.map_or_else(|| "never".to_string(), |exp| exp.to_string())
+}
+
+/// OpenPGP CRC-24 (RFC 4880 §6.1).
+fn crc24(data: &[u8]) -> u32 {
+    const INIT: u32 = 0x00B7_04CE;
+    const POLY: u32 = 0x0186_4CFB;
+    let mut crc = INIT;
+    for &byte in data {
+        crc ^= u32::from(byte) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x0100_0000 != 0 {
+                crc ^= POLY;
+            }
+        }
+    }
+    crc & 0x00FF_FFFF
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cert::{issue_cert, LicensePayload, PROTOCOL_VERSION};
+    use crate::crypto::SignatureAlgorithm;
+    use crate::keys::try_keygen;
+    use std::collections::BTreeMap;
+
+    fn sample_cert() -> LicenseCert {
+        let vendor = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let user = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let payload = LicensePayload {
+            version: PROTOCOL_VERSION,
+            alg: SignatureAlgorithm::Secp256k1,
+            pq_alg: None,
+            product_id: "test_app".to_string(),
+            license_id: [3u8; 32],
+            issued_at: 1700000000,
+            expiry: Some(1900000000),
+            user_pubkey: user.pubkey.bytes,
+            user_alg: SignatureAlgorithm::Secp256k1,
+            tier: "lifetime_pro".to_string(),
+            features: vec!["base".into(), "export.pdf".into()],
+            limits: None,
+            entitlements: BTreeMap::new(),
+            metadata: None,
+        };
+        issue_cert(&vendor.privkey.bytes, payload).unwrap()
+    }
+
+    #[test]
+    fn test_crc24_check_value() {
+        // CRC-24/OPENPGP check value for "123456789"
+        assert_eq!(crc24(b"123456789"), 0x21CF02);
+    }
+
+    #[test]
+    fn test_armor_roundtrip() {
+        let cert = sample_cert();
+        let text = encode_cert_armored(&cert).unwrap();
+        assert!(text.starts_with(ARMOR_BEGIN));
+        assert!(text.contains("Product: test_app\nTier: lifetime_pro\nExpires: 1900000000\n\n"));
+        assert!(text.lines().all(|line| line.len() <= ARMOR_LINE_LEN));
+        assert_eq!(
+            decode_cert_armored(&text).unwrap().vendor_sig,
+            cert.vendor_sig
+        );
+    }
+
+    #[test]
+    fn test_decode_cert_auto_detects_encoding() {
+        let cert = sample_cert();
+        let raw = encode_cert(&cert).unwrap();
+        let base64 = STANDARD.encode(&raw);
+        let armored = encode_cert_armored(&cert).unwrap();
+        for input in [raw, base64.into_bytes(), armored.into_bytes()] {
+            assert_eq!(
+                decode_cert_auto(&input).unwrap().vendor_sig,
+                cert.vendor_sig
+            );
+        }
+        assert!(matches!(
+            decode_cert_auto(b"not a license"),
+            Err(AegisError::CertParse { .. })
+        ));
+    }
+
+    #[test]
+    fn test_dearmor_tolerates_mangled_whitespace() {
+        let cert = sample_cert();
+        let text = encode_cert_armored(&cert).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        let crc_line = lines.iter().position(|l| l.starts_with('=')).unwrap();
+        let body = lines[5..crc_line].concat();
+        // Reflowed at 40 columns, indented, CRLF, inside an email
+        let reflowed: Vec<String> = body
+            .as_bytes()
+            .chunks(40)
+            .map(|c| format!("    {}", std::str::from_utf8(c).unwrap()))
+            .collect();
+        let mangled = format!(
+            "Hi, here is your license:\r\n\r\n{}\r\n  Product: test_app\r\n\r\n{}\r\n  {}\r\n{}\r\nThanks!",
+            ARMOR_BEGIN,
+            reflowed.join("\r\n"),
+            lines[crc_line],
+            ARMOR_END
+        );
+        assert_eq!(
+            decode_cert_armored(&mangled).unwrap().vendor_sig,
+            cert.vendor_sig
+        );
+    }
+
+    #[test]
+    fn test_dearmor_detects_corruption() {
+        let text = encode_cert_armored(&sample_cert()).unwrap();
+        let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+        // Flip one character in the body
+        let body = &mut lines[5];
+        let c = if body.starts_with('A') { "B" } else { "A" };
+        body.replace_range(0..1, c);
+        let corrupted = lines.join("\n");
+        assert!(matches!(dearmor(&corrupted), Err(AegisError::Armor(_))));
+
+        let no_crc: String = text
+            .lines()
+            .filter(|l| !l.starts_with('='))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(matches!(dearmor(&no_crc), Err(AegisError::Armor(_))));
+        assert!(matches!(dearmor("no armor"), Err(AegisError::Armor(_))));
+    }
+
+    #[test]
+    fn test_mismatched_header_rejected() {
+        let text = encode_cert_armored(&sample_cert()).unwrap();
+        let forged = text.replace("Tier: lifetime_pro", "Tier: enterprise");
+        assert!(dearmor(&forged).is_ok());
+        assert!(matches!(
+            decode_cert_armored(&forged),
+            Err(AegisError::Armor(_))
+        ));
+    }
+}
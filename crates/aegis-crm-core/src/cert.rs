@@ -1,9 +1,39 @@
 //! License certificate issuance and verification.
 
-use crate::crypto::{sha256, sign_compact, verify_compact};
+use crate::crypto::{schnorr_verify_batch, sha256, SignatureAlgorithm};
 use crate::errors::AegisError;
+use crate::features::{pattern_matches, FeatureCatalog, FeatureSet};
+#[cfg(feature = "issue")]
+use crate::keys::PrivateKey;
+use crate::keys::PublicKey;
+#[cfg(feature = "issue")]
+use crate::policy::PolicySet;
+use crate::pq::PqAlgorithm;
+#[cfg(all(feature = "pq", feature = "issue"))]
+use crate::pq::PqPrivateKey;
+#[cfg(feature = "pq")]
+use crate::pq::PqPublicKey;
+use crate::prelude::*;
+use alloc::collections::BTreeMap;
+use ciborium::Value;
+use core::fmt;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
+mod cose;
+mod jwt;
+mod key;
+
+#[cfg(feature = "issue")]
+pub use cose::{cose_to_cert, encode_cert_cose};
+pub use cose::{decode_cert_cose, key_id, verify_cert_cose, CoseCert, COSE_SIGN1_TAG};
+pub use jwt::{decode_cert_jwt, verify_cert_jwt, JwtCert};
+#[cfg(feature = "issue")]
+pub use jwt::{encode_cert_jwt, jwt_to_cert};
+#[cfg(feature = "cli-helpers")]
+pub use key::encode_cert_key;
+pub use key::{decode_cert_key, KEY_GROUP_LEN};
+
 pub const PROTOCOL_VERSION: u16 = 1;
 
 /// License tier limits
@@ -15,15 +45,336 @@ pub struct Limits {
     pub offline_grace_days: Option<u32>,
 }
 
-/// License metadata
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub struct Metadata {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub product: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub version: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub university: Option<String>,
+/// Maximum number of metadata entries in a certificate.
+pub const MAX_METADATA_ENTRIES: usize = 32;
+/// Maximum metadata key length in bytes.
+pub const MAX_METADATA_KEY_LEN: usize = 64;
+/// Maximum metadata value length in bytes (strings and byte strings).
+pub const MAX_METADATA_VALUE_LEN: usize = 1024;
+
+/// Signed metadata value
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum MetaValue {
+    Int(i64),
+    Str(String),
+    Bytes(#[serde(with = "serde_bytes")] Vec<u8>),
+}
+
+// Decoded by hand so byte strings that happen to be valid UTF-8 stay bytes.
+impl<'de> Deserialize<'de> for MetaValue {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct MetaValueVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for MetaValueVisitor {
+            type Value = MetaValue;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an integer, text string or byte string")
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<MetaValue, E> {
+                Ok(MetaValue::Int(v))
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<MetaValue, E> {
+                i64::try_from(v)
+                    .map(MetaValue::Int)
+                    .map_err(|_| E::custom("metadata integer out of range"))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<MetaValue, E> {
+                Ok(MetaValue::Str(v.to_string()))
+            }
+
+            fn visit_string<E: serde::de::Error>(self, v: String) -> Result<MetaValue, E> {
+                Ok(MetaValue::Str(v))
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<MetaValue, E> {
+                Ok(MetaValue::Bytes(v.to_vec()))
+            }
+
+            fn visit_byte_buf<E: serde::de::Error>(self, v: Vec<u8>) -> Result<MetaValue, E> {
+                Ok(MetaValue::Bytes(v))
+            }
+        }
+
+        deserializer.deserialize_any(MetaValueVisitor)
+    }
+}
+
+impl From<&str> for MetaValue {
+    fn from(value: &str) -> Self {
+        MetaValue::Str(value.to_string())
+    }
+}
+
+impl From<String> for MetaValue {
+    fn from(value: String) -> Self {
+        MetaValue::Str(value)
+    }
+}
+
+impl From<i64> for MetaValue {
+    fn from(value: i64) -> Self {
+        MetaValue::Int(value)
+    }
+}
+
+impl From<Vec<u8>> for MetaValue {
+    fn from(value: Vec<u8>) -> Self {
+        MetaValue::Bytes(value)
+    }
+}
+
+impl fmt::Display for MetaValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetaValue::Int(i) => write!(f, "{}", i),
+            MetaValue::Str(s) => write!(f, "{}", s),
+            MetaValue::Bytes(bytes) => {
+                write!(f, "0x")?;
+                bytes.iter().try_for_each(|b| write!(f, "{:02x}", b))
+            }
+        }
+    }
+}
+
+/// License metadata: open key/value map, encoded in canonical CBOR key order
+/// (shorter keys first, then bytewise). Certificates carrying the v1.0
+/// `product`/`version`/`university` fields decode and verify unchanged.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Metadata(BTreeMap<String, MetaValue>);
+
+impl Metadata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder-style insert
+    pub fn with(mut self, key: impl Into<String>, value: impl Into<MetaValue>) -> Self {
+        self.insert(key, value);
+        self
+    }
+
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<MetaValue>) {
+        self.0.insert(key.into(), value.into());
+    }
+
+    pub fn get(&self, key: &str) -> Option<&MetaValue> {
+        self.0.get(key)
+    }
+
+    /// Get a string value
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        match self.get(key)? {
+            MetaValue::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.0.contains_key(key)
+    }
+
+    /// Entries in canonical CBOR order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &MetaValue)> {
+        let mut entries: Vec<_> = self.0.iter().map(|(k, v)| (k.as_str(), v)).collect();
+        entries.sort_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+        entries.into_iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Check entry count, key and value size limits
+    pub fn check_limits(&self) -> Result<(), String> {
+        if self.0.len() > MAX_METADATA_ENTRIES {
+            return Err(format!(
+                "metadata has {} entries (max {})",
+                self.0.len(),
+                MAX_METADATA_ENTRIES
+            ));
+        }
+        for (key, value) in &self.0 {
+            if key.is_empty() || key.len() > MAX_METADATA_KEY_LEN {
+                return Err(format!(
+                    "metadata key '{}' must be 1-{} bytes",
+                    key, MAX_METADATA_KEY_LEN
+                ));
+            }
+            let len = match value {
+                MetaValue::Int(_) => 0,
+                MetaValue::Str(s) => s.len(),
+                MetaValue::Bytes(b) => b.len(),
+            };
+            if len > MAX_METADATA_VALUE_LEN {
+                return Err(format!(
+                    "metadata value '{}' is {} bytes (max {})",
+                    key, len, MAX_METADATA_VALUE_LEN
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for Metadata {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+impl<K: Into<String>, V: Into<MetaValue>> FromIterator<(K, V)> for Metadata {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        )
+    }
+}
+
+/// A single feature entitlement, optionally limited to its own validity window.
+///
+/// Features without a window are encoded as a plain string, so certificates that
+/// only carry plain features are byte-identical to the original format.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(from = "FeatureGrantRepr", into = "FeatureGrantRepr")]
+pub struct FeatureGrant {
+    pub name: String,
+    pub not_before: Option<u64>,
+    pub expiry: Option<u64>,
+}
+
+impl FeatureGrant {
+    /// Feature granted for the whole lifetime of the certificate.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            not_before: None,
+            expiry: None,
+        }
+    }
+
+    /// Restrict the feature to `[not_before, expiry]` (either bound optional).
+    pub fn with_window(mut self, not_before: Option<u64>, expiry: Option<u64>) -> Self {
+        self.not_before = not_before;
+        self.expiry = expiry;
+        self
+    }
+
+    /// Whether the feature carries its own validity window.
+    pub fn is_windowed(&self) -> bool {
+        self.not_before.is_some() || self.expiry.is_some()
+    }
+
+    /// Reject empty or inverted windows (`not_before` must be earlier than `expiry`).
+    pub fn check_window(&self) -> Result<(), AegisError> {
+        match (self.not_before, self.expiry) {
+            (Some(nbf), Some(exp)) if nbf >= exp => Err(AegisError::payload_field(
+                "feat",
+                format!(
+                    "feature '{}' not_before must be earlier than expiry (got: {} >= {})",
+                    self.name, nbf, exp
+                ),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check if the feature window covers `now_unix`
+    pub fn is_active_at(&self, now_unix: u64) -> bool {
+        let started = !matches!(self.not_before, Some(nbf) if now_unix < nbf);
+        let ended = matches!(self.expiry, Some(exp) if now_unix > exp);
+        started && !ended
+    }
+}
+
+impl From<&str> for FeatureGrant {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<String> for FeatureGrant {
+    fn from(name: String) -> Self {
+        Self::new(name)
+    }
+}
+
+/// Wire form of [`FeatureGrant`]: a bare name, or a map when windowed.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum FeatureGrantRepr {
+    Name(String),
+    Windowed {
+        #[serde(rename = "n")]
+        name: String,
+        #[serde(rename = "nbf", default, skip_serializing_if = "Option::is_none")]
+        not_before: Option<u64>,
+        #[serde(rename = "exp", default, skip_serializing_if = "Option::is_none")]
+        expiry: Option<u64>,
+    },
+}
+
+impl From<FeatureGrantRepr> for FeatureGrant {
+    fn from(repr: FeatureGrantRepr) -> Self {
+        match repr {
+            FeatureGrantRepr::Name(name) => Self::new(name),
+            FeatureGrantRepr::Windowed {
+                name,
+                not_before,
+                expiry,
+            } => Self {
+                name,
+                not_before,
+                expiry,
+            },
+        }
+    }
+}
+
+impl From<FeatureGrant> for FeatureGrantRepr {
+    fn from(grant: FeatureGrant) -> Self {
+        if grant.is_windowed() {
+            Self::Windowed {
+                name: grant.name,
+                not_before: grant.not_before,
+                expiry: grant.expiry,
+            }
+        } else {
+            Self::Name(grant.name)
+        }
+    }
+}
+
+/// Typed entitlement parameter (e.g. `max_projects = 10`, `export_formats = [pdf, csv]`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum Entitlement {
+    Bool(bool),
+    Int(i64),
+    Str(String),
+    List(Vec<String>),
+}
+
+impl fmt::Display for Entitlement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Entitlement::Bool(b) => write!(f, "{}", b),
+            Entitlement::Int(i) => write!(f, "{}", i),
+            Entitlement::Str(s) => write!(f, "{}", s),
+            Entitlement::List(items) => write!(f, "[{}]", items.join(", ")),
+        }
+    }
 }
 
 fn default_tier() -> String {
@@ -34,6 +385,16 @@ fn default_tier() -> String {
 pub struct LicensePayload {
     #[serde(rename = "v")]
     pub version: u16,
+    /// Vendor signature algorithm; set by the issuer, omitted for secp256k1.
+    #[serde(
+        rename = "alg",
+        default,
+        skip_serializing_if = "SignatureAlgorithm::is_default"
+    )]
+    pub alg: SignatureAlgorithm,
+    /// Post-quantum algorithm of a hybrid certificate; set by the issuer.
+    #[serde(rename = "pqalg", default, skip_serializing_if = "Option::is_none")]
+    pub pq_alg: Option<PqAlgorithm>,
     #[serde(rename = "pid")]
     pub product_id: String,
     #[serde(rename = "lid", with = "serde_bytes")]
@@ -43,21 +404,97 @@ pub struct LicensePayload {
     #[serde(rename = "exp")]
     pub expiry: Option<u64>,
     #[serde(rename = "upk", with = "serde_bytes")]
-    pub user_pubkey: [u8; 33],
+    pub user_pubkey: Vec<u8>,
+    /// User key algorithm for PoP, omitted for secp256k1.
+    #[serde(
+        rename = "ualg",
+        default,
+        skip_serializing_if = "SignatureAlgorithm::is_default"
+    )]
+    pub user_alg: SignatureAlgorithm,
     #[serde(rename = "tier", default = "default_tier")]
     pub tier: String,
     #[serde(rename = "feat", default)]
-    pub features: Vec<String>,
+    pub features: Vec<FeatureGrant>,
     #[serde(rename = "lim", skip_serializing_if = "Option::is_none")]
     pub limits: Option<Limits>,
+    #[serde(rename = "ent", default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub entitlements: BTreeMap<String, Entitlement>,
     #[serde(rename = "meta", skip_serializing_if = "Option::is_none")]
     pub metadata: Option<Metadata>,
 }
 
 impl LicensePayload {
-    /// Check if payload has a specific feature (supports "ALL" wildcard)
+    /// User public key tagged with its algorithm
+    pub fn user_key(&self) -> Result<PublicKey, AegisError> {
+        PublicKey::new(self.user_alg, &self.user_pubkey)
+    }
+
+    /// Check if payload lists a specific feature (supports "ALL" and `prefix.*` grants).
+    ///
+    /// Per-feature windows are ignored, so an expired trial still counts as granted.
+    #[deprecated(note = "ignores per-feature windows; use `has_feature_at`")]
     pub fn has_feature(&self, feature: &str) -> bool {
-        self.features.iter().any(|f| f == "ALL" || f == feature)
+        self.features
+            .iter()
+            .any(|f| pattern_matches(&f.name, feature))
+    }
+
+    /// Check if a specific feature is granted at `now_unix`, honouring per-feature windows
+    pub fn has_feature_at(&self, feature: &str, now_unix: u64) -> bool {
+        self.features
+            .iter()
+            .any(|f| pattern_matches(&f.name, feature) && f.is_active_at(now_unix))
+    }
+
+    /// Resolve the features active at `now_unix` through the vendor's implication catalog
+    pub fn resolve_features(&self, catalog: &FeatureCatalog, now_unix: u64) -> FeatureSet {
+        catalog.resolve(&self.features, now_unix)
+    }
+
+    /// Look up a raw entitlement parameter
+    pub fn entitlement(&self, name: &str) -> Option<&Entitlement> {
+        self.entitlements.get(name)
+    }
+
+    /// Get a non-negative integer entitlement
+    pub fn get_u64(&self, name: &str) -> Option<u64> {
+        match self.entitlement(name)? {
+            Entitlement::Int(i) => u64::try_from(*i).ok(),
+            _ => None,
+        }
+    }
+
+    /// Get a signed integer entitlement
+    pub fn get_i64(&self, name: &str) -> Option<i64> {
+        match self.entitlement(name)? {
+            Entitlement::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Get a boolean entitlement
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        match self.entitlement(name)? {
+            Entitlement::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Get a string entitlement
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        match self.entitlement(name)? {
+            Entitlement::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Get a list entitlement
+    pub fn get_list(&self, name: &str) -> Option<&[String]> {
+        match self.entitlement(name)? {
+            Entitlement::List(items) => Some(items),
+            _ => None,
+        }
     }
 }
 
@@ -67,62 +504,402 @@ pub struct LicenseCert {
     pub payload: LicensePayload,
     #[serde(rename = "sig", with = "serde_bytes")]
     pub vendor_sig: [u8; 64],
+    /// ML-DSA signature over the canonical payload (hybrid certificates only).
+    #[serde(
+        rename = "pqsig",
+        default,
+        with = "serde_bytes",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pq_sig: Option<Vec<u8>>,
 }
 
+/// Issue a certificate under the built-in tier policy (see [`PolicySet::builtin`]).
+#[cfg(feature = "issue")]
 pub fn issue_cert(
     vendor_privkey: &[u8; 32],
     payload: LicensePayload,
 ) -> Result<LicenseCert, AegisError> {
-    // Validate campus tier has expiry
-    if payload.tier == "campus" && payload.expiry.is_none() {
-        return Err(AegisError::CertParse);
-    }
+    issue_cert_with_policy(
+        &PrivateKey::secp256k1(*vendor_privkey),
+        payload,
+        &PolicySet::builtin(),
+    )
+}
 
-    let mut payload_bytes = Vec::new();
-    ciborium::into_writer(&payload, &mut payload_bytes).map_err(|_| AegisError::CertParse)?;
-    let digest = sha256(&payload_bytes);
-    let vendor_sig = sign_compact(vendor_privkey, &digest)?;
+/// Issue a certificate after applying tier defaults and checking the payload against `policy`.
+///
+/// The payload `alg` is set from `vendor_key`.
+#[cfg(feature = "issue")]
+pub fn issue_cert_with_policy(
+    vendor_key: &PrivateKey,
+    mut payload: LicensePayload,
+    policy: &PolicySet,
+) -> Result<LicenseCert, AegisError> {
+    prepare_payload(vendor_key, &mut payload, policy)?;
+    payload.pq_alg = None;
+    sign_payload(vendor_key, payload)
+}
+
+/// Sign an already validated payload whose `alg` matches `vendor_key`.
+#[cfg(feature = "issue")]
+fn sign_payload(
+    vendor_key: &PrivateKey,
+    payload: LicensePayload,
+) -> Result<LicenseCert, AegisError> {
+    let digest = payload_digest(&payload)?;
+    let vendor_sig = vendor_key.alg.scheme().sign(&vendor_key.bytes, &digest)?;
     Ok(LicenseCert {
         payload,
         vendor_sig,
+        pq_sig: None,
     })
 }
 
+/// Issue a hybrid certificate signed with both `vendor_key` and the ML-DSA `pq_key`.
+///
+/// Intended for long-lived (perpetual) licenses that must stay unforgeable
+/// even if the classical vendor key is broken.
+#[cfg(all(feature = "pq", feature = "issue"))]
+pub fn issue_cert_hybrid(
+    vendor_key: &PrivateKey,
+    pq_key: &PqPrivateKey,
+    mut payload: LicensePayload,
+    policy: &PolicySet,
+) -> Result<LicenseCert, AegisError> {
+    prepare_payload(vendor_key, &mut payload, policy)?;
+    payload.pq_alg = Some(pq_key.alg);
+
+    let payload_bytes = payload_bytes(&payload)?;
+    let vendor_sig = vendor_key
+        .alg
+        .scheme()
+        .sign(&vendor_key.bytes, &sha256(&payload_bytes))?;
+    let pq_sig = pq_key.sign(&payload_bytes)?;
+    Ok(LicenseCert {
+        payload,
+        vendor_sig,
+        pq_sig: Some(pq_sig),
+    })
+}
+
+/// Validate `payload` for issuance and stamp the vendor algorithm.
+#[cfg(feature = "issue")]
+fn prepare_payload(
+    vendor_key: &PrivateKey,
+    payload: &mut LicensePayload,
+    policy: &PolicySet,
+) -> Result<(), AegisError> {
+    if let Some(meta) = &payload.metadata {
+        meta.check_limits().map_err(AegisError::Payload)?;
+    }
+    for feature in &payload.features {
+        feature.check_window()?;
+    }
+    policy.apply(payload)?;
+    payload.user_key()?;
+    payload.alg = vendor_key.alg;
+    Ok(())
+}
+
+/// Verify a secp256k1-signed certificate (v1.0 API).
 pub fn verify_cert(
     vendor_pubkey: &[u8; 33],
     cert: &LicenseCert,
     now_unix: u64,
 ) -> Result<(), AegisError> {
-    if cert.payload.version != PROTOCOL_VERSION {
+    verify_cert_with_key(&PublicKey::secp256k1(vendor_pubkey), cert, now_unix)
+}
+
+/// Verify a certificate against an algorithm-tagged vendor key.
+///
+/// The certificate `alg` must match the key algorithm. ECDSA signatures must
+/// be low-S (see [`normalize_cert`]). Hybrid certificates fail with
+/// [`AegisError::PqKeyRequired`]; check them with `verify_cert_hybrid`.
+pub fn verify_cert_with_key(
+    vendor_key: &PublicKey,
+    cert: &LicenseCert,
+    now_unix: u64,
+) -> Result<(), AegisError> {
+    verify_cert_signature(vendor_key, cert)?;
+    check_expiry(&cert.payload, now_unix)
+}
+
+/// Verify many certificates from one vendor, returning one result per certificate.
+///
+/// Schnorr-signed certificates are checked with a single batch verification;
+/// if the batch fails, each signature is re-checked to find the bad ones.
+/// Other algorithms are verified one by one.
+pub fn verify_certs_batch(
+    vendor_key: &PublicKey,
+    certs: &[LicenseCert],
+    now_unix: u64,
+) -> Vec<Result<(), AegisError>> {
+    let digests: Vec<Result<[u8; 32], AegisError>> = certs
+        .iter()
+        .map(|cert| signed_digest(vendor_key, cert))
+        .collect();
+
+    let batch_ok = vendor_key.alg == SignatureAlgorithm::Schnorr && {
+        let items: Vec<(&[u8], &[u8; 32], &[u8; 64])> = digests
+            .iter()
+            .zip(certs)
+            .filter_map(|(digest, cert)| {
+                let digest = digest.as_ref().ok()?;
+                Some((vendor_key.bytes.as_slice(), digest, &cert.vendor_sig))
+            })
+            .collect();
+        !items.is_empty() && schnorr_verify_batch(&items).is_ok()
+    };
+
+    digests
+        .into_iter()
+        .zip(certs)
+        .map(|(digest, cert)| {
+            let digest = digest?;
+            if !batch_ok {
+                verify_signature(vendor_key, &digest, cert)?;
+            }
+            require_classical(cert)?;
+            check_expiry(&cert.payload, now_unix)
+        })
+        .collect()
+}
+
+/// Rewrite the vendor signature into its canonical (low-S) form.
+///
+/// Returns `true` if the signature changed, i.e. the certificate was not
+/// normalized and would be rejected by verification. Does not check the signature.
+pub fn normalize_cert(cert: &mut LicenseCert) -> Result<bool, AegisError> {
+    let normalized = cert.payload.alg.scheme().normalize(&cert.vendor_sig)?;
+    let changed = normalized != cert.vendor_sig;
+    cert.vendor_sig = normalized;
+    Ok(changed)
+}
+
+/// Check version and algorithm, then compute the digest the vendor signed.
+fn signed_digest(vendor_key: &PublicKey, cert: &LicenseCert) -> Result<[u8; 32], AegisError> {
+    check_header(vendor_key, &cert.payload)?;
+    payload_digest(&cert.payload)
+}
+
+/// Check the protocol version and that the payload names the vendor key algorithm.
+fn check_header(vendor_key: &PublicKey, payload: &LicensePayload) -> Result<(), AegisError> {
+    if payload.version != PROTOCOL_VERSION {
         return Err(AegisError::UnsupportedVersion);
     }
-    let mut payload_bytes = Vec::new();
-    ciborium::into_writer(&cert.payload, &mut payload_bytes).map_err(|_| AegisError::CertParse)?;
-    let digest = sha256(&payload_bytes);
-    verify_compact(vendor_pubkey, &digest, &cert.vendor_sig)
-        .map_err(|_| AegisError::CertSignature)?;
-    if let Some(exp) = cert.payload.expiry {
-        if now_unix > exp {
-            return Err(AegisError::CertExpired);
-        }
+    if payload.alg != vendor_key.alg {
+        return Err(AegisError::CertSignature);
     }
     Ok(())
 }
 
+/// Check the version, algorithm and vendor signature, ignoring expiry.
+///
+/// Hybrid certificates are rejected, since their ML-DSA signature goes unchecked.
+pub(crate) fn verify_cert_signature(
+    vendor_key: &PublicKey,
+    cert: &LicenseCert,
+) -> Result<(), AegisError> {
+    verify_classical_signature(vendor_key, cert)?;
+    require_classical(cert)
+}
+
+/// Check the version, algorithm and vendor signature of any certificate, hybrid or not.
+pub(crate) fn verify_classical_signature(
+    vendor_key: &PublicKey,
+    cert: &LicenseCert,
+) -> Result<(), AegisError> {
+    let digest = signed_digest(vendor_key, cert)?;
+    verify_signature(vendor_key, &digest, cert)
+}
+
+/// Reject certificates that carry a PQ signature or name a PQ algorithm.
+fn require_classical(cert: &LicenseCert) -> Result<(), AegisError> {
+    if cert.payload.pq_alg.is_some() || cert.pq_sig.is_some() {
+        return Err(AegisError::PqKeyRequired);
+    }
+    Ok(())
+}
+
+fn verify_signature(
+    vendor_key: &PublicKey,
+    digest: &[u8; 32],
+    cert: &LicenseCert,
+) -> Result<(), AegisError> {
+    vendor_key
+        .alg
+        .scheme()
+        .verify(&vendor_key.bytes, digest, &cert.vendor_sig)
+        .map_err(|_| AegisError::CertSignature)
+}
+
+pub(crate) fn check_expiry(payload: &LicensePayload, now_unix: u64) -> Result<(), AegisError> {
+    match payload.expiry {
+        Some(exp) if now_unix > exp => Err(AegisError::CertExpired),
+        _ => Ok(()),
+    }
+}
+
+/// Verify a hybrid certificate: both the classical and the ML-DSA signature must be valid.
+///
+/// Certificates without a PQ signature are rejected.
+#[cfg(feature = "pq")]
+pub fn verify_cert_hybrid(
+    vendor_key: &PublicKey,
+    pq_key: &PqPublicKey,
+    cert: &LicenseCert,
+    now_unix: u64,
+) -> Result<(), AegisError> {
+    verify_classical_signature(vendor_key, cert)?;
+    check_expiry(&cert.payload, now_unix)?;
+    verify_pq_signature(pq_key, cert)
+}
+
+/// Check the ML-DSA signature of a hybrid certificate.
+#[cfg(feature = "pq")]
+pub(crate) fn verify_pq_signature(
+    pq_key: &PqPublicKey,
+    cert: &LicenseCert,
+) -> Result<(), AegisError> {
+    if cert.payload.pq_alg != Some(pq_key.alg) {
+        return Err(AegisError::CertSignature);
+    }
+    let pq_sig = cert.pq_sig.as_ref().ok_or(AegisError::CertSignature)?;
+    pq_key
+        .verify(&payload_bytes(&cert.payload)?, pq_sig)
+        .map_err(|_| AegisError::CertSignature)
+}
+
+/// Canonical CBOR payload (the ML-DSA and COSE signed message).
+fn payload_bytes(payload: &LicensePayload) -> Result<Vec<u8>, AegisError> {
+    let mut payload_bytes = Vec::new();
+    ciborium::into_writer(payload, &mut payload_bytes)
+        .map_err(|e| AegisError::parse(format!("failed to encode payload: {}", e)))?;
+    Ok(payload_bytes)
+}
+
+/// SHA-256 of the canonical CBOR payload (the classical signed message).
+fn payload_digest(payload: &LicensePayload) -> Result<[u8; 32], AegisError> {
+    Ok(sha256(&payload_bytes(payload)?))
+}
+
 pub fn encode_cert(cert: &LicenseCert) -> Result<Vec<u8>, AegisError> {
     let mut cbor = Vec::new();
-    ciborium::into_writer(cert, &mut cbor).map_err(|_| AegisError::CertParse)?;
+    ciborium::into_writer(cert, &mut cbor)
+        .map_err(|e| AegisError::parse(format!("failed to encode certificate: {}", e)))?;
     Ok(cbor)
 }
 
 pub fn decode_cert(cbor: &[u8]) -> Result<LicenseCert, AegisError> {
-    ciborium::from_reader(cbor).map_err(|_| AegisError::CertParse)
+    let cert: LicenseCert = decode_fields(cbor, CERT_REQUIRED)?;
+    check_decoded(&cert.payload)?;
+    Ok(cert)
+}
+
+/// Limits enforced on every decoded payload.
+fn check_decoded(payload: &LicensePayload) -> Result<(), AegisError> {
+    if let Some(meta) = &payload.metadata {
+        meta.check_limits()
+            .map_err(|reason| AegisError::parse_field("meta", reason))?;
+    }
+    Ok(())
+}
+
+/// Payload fields without a default.
+const PAYLOAD_REQUIRED: &[&str] = &["v", "pid", "lid", "iat", "upk"];
+const CERT_REQUIRED: &[&str] = &["v", "pid", "lid", "iat", "upk", "sig"];
+
+/// Decode a canonical CBOR payload (as embedded in COSE and JWS certificates).
+fn decode_payload(cbor: &[u8]) -> Result<LicensePayload, AegisError> {
+    let payload = decode_fields(cbor, PAYLOAD_REQUIRED)?;
+    check_decoded(&payload)?;
+    Ok(payload)
+}
+
+/// Decode a CBOR map into `T`, naming the offending field on failure.
+fn decode_fields<T: DeserializeOwned>(cbor: &[u8], required: &[&str]) -> Result<T, AegisError> {
+    ciborium::from_reader(cbor).map_err(|err| match ciborium::from_reader::<Value, _>(cbor) {
+        Ok(Value::Map(entries)) => diagnose_fields(&entries, required, de_reason(&err)),
+        Ok(_) => AegisError::parse("expected a CBOR map"),
+        Err(err) => AegisError::parse(format!("invalid CBOR: {}", de_reason(&err))),
+    })
+}
+
+/// Deserialize already parsed map entries into `T`, naming the offending field on failure.
+fn from_fields<T: DeserializeOwned>(
+    entries: Vec<(Value, Value)>,
+    required: &[&str],
+) -> Result<T, AegisError> {
+    let map = Value::Map(entries);
+    map.deserialized().map_err(|err| {
+        let entries = map.as_map().map(Vec::as_slice).unwrap_or_default();
+        diagnose_fields(entries, required, value_reason(err))
+    })
+}
+
+/// Find the field that made a map fail to deserialize by checking each entry
+/// against its expected type; falls back to the decoder's message.
+fn diagnose_fields(entries: &[(Value, Value)], required: &[&str], fallback: String) -> AegisError {
+    for (key, value) in entries {
+        let Some(field) = key.as_text() else {
+            return AegisError::parse("map keys must be text");
+        };
+        if let Some(reason) = field_error(field, value) {
+            return AegisError::parse_field(field, reason);
+        }
+    }
+    for field in required {
+        if !entries.iter().any(|(key, _)| key.as_text() == Some(field)) {
+            return AegisError::parse_field(*field, "missing");
+        }
+    }
+    AegisError::parse(fallback)
+}
+
+/// Why `value` is not a valid `field`, or `None` if it is (or is unknown).
+fn field_error(field: &str, value: &Value) -> Option<String> {
+    fn check<T: DeserializeOwned>(value: &Value) -> Option<String> {
+        value.deserialized::<T>().err().map(value_reason)
+    }
+    match field {
+        "v" => check::<u16>(value),
+        "alg" | "ualg" => check::<SignatureAlgorithm>(value),
+        "pqalg" => check::<PqAlgorithm>(value),
+        "pid" | "tier" => check::<String>(value),
+        "lid" => check::<serde_bytes::ByteArray<32>>(value),
+        "iat" => check::<u64>(value),
+        "exp" => check::<Option<u64>>(value),
+        "upk" | "pqsig" => check::<serde_bytes::ByteBuf>(value),
+        "feat" => check::<Vec<FeatureGrant>>(value),
+        "lim" => check::<Limits>(value),
+        "ent" => check::<BTreeMap<String, Entitlement>>(value),
+        "meta" => check::<Metadata>(value),
+        "sig" => check::<serde_bytes::ByteArray<64>>(value),
+        _ => None,
+    }
+}
+
+fn value_reason(err: ciborium::value::Error) -> String {
+    match err {
+        ciborium::value::Error::Custom(reason) => reason,
+    }
+}
+
+/// Readable message of a CBOR decoding error.
+fn de_reason<E: fmt::Debug>(err: &ciborium::de::Error<E>) -> String {
+    match err {
+        ciborium::de::Error::Io(err) => format!("read error: {:?}", err),
+        ciborium::de::Error::Syntax(offset) => format!("syntax error at byte {}", offset),
+        ciborium::de::Error::Semantic(_, reason) => reason.clone(),
+        ciborium::de::Error::RecursionLimitExceeded => "nesting too deep".to_string(),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::keys::vendor_keygen;
+    use crate::keys::try_vendor_keygen;
     use getrandom::getrandom;
 
     fn create_test_payload() -> LicensePayload {
@@ -130,21 +907,25 @@ mod tests {
         getrandom(&mut license_id).unwrap();
         LicensePayload {
             version: PROTOCOL_VERSION,
+            alg: SignatureAlgorithm::Secp256k1,
+            pq_alg: None,
             product_id: "test".to_string(),
             license_id,
             issued_at: 1700000000,
             expiry: Some(2000000000),
-            user_pubkey: [0x02; 33],
+            user_pubkey: crate::keys::try_user_keygen().unwrap().pubkey.to_vec(),
+            user_alg: SignatureAlgorithm::Secp256k1,
             tier: "lifetime_pro".to_string(),
-            features: vec!["base".to_string(), "premium".to_string()],
+            features: vec!["base".into(), "premium".into()],
             limits: None,
+            entitlements: BTreeMap::new(),
             metadata: None,
         }
     }
 
     #[test]
     fn test_issue_cert() {
-        let vendor = vendor_keygen();
+        let vendor = try_vendor_keygen().unwrap();
         let payload = create_test_payload();
         let cert = issue_cert(&vendor.privkey, payload).unwrap();
         assert_eq!(cert.vendor_sig.len(), 64);
@@ -152,14 +933,14 @@ mod tests {
 
     #[test]
     fn test_verify_cert_valid() {
-        let vendor = vendor_keygen();
+        let vendor = try_vendor_keygen().unwrap();
         let cert = issue_cert(&vendor.privkey, create_test_payload()).unwrap();
         verify_cert(&vendor.pubkey, &cert, 1800000000).unwrap();
     }
 
     #[test]
     fn test_verify_cert_expired() {
-        let vendor = vendor_keygen();
+        let vendor = try_vendor_keygen().unwrap();
         let cert = issue_cert(&vendor.privkey, create_test_payload()).unwrap();
         assert!(matches!(
             verify_cert(&vendor.pubkey, &cert, 2100000000),
@@ -169,7 +950,7 @@ mod tests {
 
     #[test]
     fn test_verify_cert_tampered() {
-        let vendor = vendor_keygen();
+        let vendor = try_vendor_keygen().unwrap();
         let mut cert = issue_cert(&vendor.privkey, create_test_payload()).unwrap();
         cert.vendor_sig[0] ^= 0xFF;
         assert!(matches!(
@@ -180,7 +961,7 @@ mod tests {
 
     #[test]
     fn test_encode_decode_roundtrip() {
-        let vendor = vendor_keygen();
+        let vendor = try_vendor_keygen().unwrap();
         let cert = issue_cert(&vendor.privkey, create_test_payload()).unwrap();
         let cbor = encode_cert(&cert).unwrap();
         let decoded = decode_cert(&cbor).unwrap();
@@ -191,44 +972,492 @@ mod tests {
 
     #[test]
     fn test_cbor_stability() {
-        let vendor = vendor_keygen();
+        let vendor = try_vendor_keygen().unwrap();
         let cert = issue_cert(&vendor.privkey, create_test_payload()).unwrap();
         let cbor1 = encode_cert(&cert).unwrap();
         let cbor2 = encode_cert(&cert).unwrap();
         assert_eq!(cbor1, cbor2);
     }
 
+    #[test]
+    fn test_decode_errors_name_field() {
+        let vendor = try_vendor_keygen().unwrap();
+        let cert = issue_cert(&vendor.privkey, create_test_payload()).unwrap();
+        let Value::Map(entries) = Value::serialized(&cert).unwrap() else {
+            panic!("certificate is not a map");
+        };
+        let with = |field: &str, value: Option<Value>| {
+            let mut entries: Vec<_> = entries
+                .iter()
+                .filter(|(k, _)| k.as_text() != Some(field))
+                .cloned()
+                .collect();
+            entries.extend(value.map(|v| (Value::from(field), v)));
+            let mut cbor = Vec::new();
+            ciborium::into_writer(&Value::Map(entries), &mut cbor).unwrap();
+            decode_cert(&cbor).unwrap_err()
+        };
+        let field_of = |err: AegisError| match err {
+            AegisError::CertParse { field, .. } => field,
+            other => panic!("unexpected error: {}", other),
+        };
+
+        assert_eq!(
+            field_of(with("lid", Some(Value::Bytes(vec![0; 31])))),
+            Some("lid".to_string())
+        );
+        assert_eq!(
+            field_of(with("iat", Some(Value::from("yesterday")))),
+            Some("iat".to_string())
+        );
+        assert_eq!(field_of(with("sig", None)), Some("sig".to_string()));
+        let err = with("pid", None);
+        assert_eq!(err.code(), "E_CERT_PARSE");
+        assert!(err.to_string().contains("field 'pid': missing"));
+        assert_eq!(field_of(decode_cert(&[0xff, 0x00]).unwrap_err()), None);
+    }
+
+    #[test]
+    fn test_normalize_cert() {
+        let vendor = try_vendor_keygen().unwrap();
+        let mut cert = issue_cert(&vendor.privkey, create_test_payload()).unwrap();
+        assert!(!normalize_cert(&mut cert).unwrap());
+
+        // s -> n - s yields the malleated high-S twin of the signature
+        let s = <k256::Scalar as k256::elliptic_curve::PrimeField>::from_repr(
+            <[u8; 32]>::try_from(&cert.vendor_sig[32..]).unwrap().into(),
+        )
+        .unwrap();
+        let high_s = -s;
+        let original = cert.vendor_sig;
+        cert.vendor_sig[32..].copy_from_slice(&high_s.to_bytes());
+        assert!(matches!(
+            verify_cert(&vendor.pubkey, &cert, 1800000000),
+            Err(AegisError::CertSignature)
+        ));
+
+        assert!(normalize_cert(&mut cert).unwrap());
+        assert_eq!(cert.vendor_sig, original);
+        verify_cert(&vendor.pubkey, &cert, 1800000000).unwrap();
+    }
+
+    #[test]
+    fn test_verify_certs_batch() {
+        for alg in [SignatureAlgorithm::Schnorr, SignatureAlgorithm::Ed25519] {
+            let vendor = crate::keys::try_keygen(alg).unwrap();
+            let issue = |expiry| {
+                let mut payload = create_test_payload();
+                payload.expiry = expiry;
+                issue_cert_with_policy(&vendor.privkey, payload, &PolicySet::builtin()).unwrap()
+            };
+            let mut certs = vec![issue(None), issue(Some(1750000000)), issue(None)];
+            let results = verify_certs_batch(&vendor.pubkey, &certs, 1800000000);
+            assert!(results[0].is_ok());
+            assert!(matches!(results[1], Err(AegisError::CertExpired)));
+            assert!(results[2].is_ok());
+
+            certs[2].vendor_sig[5] ^= 0x01;
+            certs[0].payload.version = 2;
+            let results = verify_certs_batch(&vendor.pubkey, &certs, 1800000000);
+            assert!(matches!(results[0], Err(AegisError::UnsupportedVersion)));
+            assert!(matches!(results[1], Err(AegisError::CertExpired)));
+            assert!(matches!(results[2], Err(AegisError::CertSignature)));
+        }
+    }
+
+    #[test]
+    fn test_classic_cert_has_no_pq_fields() {
+        let vendor = try_vendor_keygen().unwrap();
+        let cert = issue_cert(&vendor.privkey, create_test_payload()).unwrap();
+        let value: ciborium::Value =
+            ciborium::from_reader(encode_cert(&cert).unwrap().as_slice()).unwrap();
+        assert!(value
+            .as_map()
+            .unwrap()
+            .iter()
+            .all(|(k, _)| !matches!(k.as_text(), Some("pqalg" | "pqsig"))));
+    }
+
+    #[cfg(feature = "pq")]
+    mod hybrid {
+        use super::*;
+        use crate::keys::try_keygen;
+        use crate::pq::pq_keygen;
+
+        fn issue_hybrid() -> (PublicKey, PqPublicKey, LicenseCert) {
+            let vendor = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+            let pq = pq_keygen(PqAlgorithm::MlDsa65).unwrap();
+            let cert = issue_cert_hybrid(
+                &vendor.privkey,
+                &pq.privkey,
+                create_test_payload(),
+                &PolicySet::builtin(),
+            )
+            .unwrap();
+            (vendor.pubkey, pq.pubkey, cert)
+        }
+
+        #[test]
+        fn test_hybrid_roundtrip() {
+            let (vendor, pq, cert) = issue_hybrid();
+            assert_eq!(cert.payload.pq_alg, Some(PqAlgorithm::MlDsa65));
+            let decoded = decode_cert(&encode_cert(&cert).unwrap()).unwrap();
+            verify_cert_hybrid(&vendor, &pq, &decoded, 1800000000).unwrap();
+            // Classical verification must not silently skip the ML-DSA signature.
+            assert!(matches!(
+                verify_cert_with_key(&vendor, &decoded, 1800000000),
+                Err(AegisError::PqKeyRequired)
+            ));
+            assert!(matches!(
+                verify_certs_batch(&vendor, &[decoded], 1800000000)[0],
+                Err(AegisError::PqKeyRequired)
+            ));
+        }
+
+        #[test]
+        fn test_hybrid_requires_pq_signature() {
+            let (vendor, pq, mut cert) = issue_hybrid();
+            cert.pq_sig = None;
+            assert!(matches!(
+                verify_cert_hybrid(&vendor, &pq, &cert, 1800000000),
+                Err(AegisError::CertSignature)
+            ));
+        }
+
+        #[test]
+        fn test_hybrid_rejects_tampered_or_foreign_pq_signature() {
+            let (vendor, pq, mut cert) = issue_hybrid();
+            let other = pq_keygen(PqAlgorithm::MlDsa65).unwrap().pubkey;
+            assert!(verify_cert_hybrid(&vendor, &other, &cert, 1800000000).is_err());
+            cert.pq_sig.as_mut().unwrap()[0] ^= 0xFF;
+            assert!(matches!(
+                verify_cert_hybrid(&vendor, &pq, &cert, 1800000000),
+                Err(AegisError::CertSignature)
+            ));
+        }
+
+        #[test]
+        fn test_stripping_pq_marker_breaks_classical_signature() {
+            let (vendor, pq, mut cert) = issue_hybrid();
+            cert.payload.pq_alg = None;
+            cert.pq_sig = None;
+            assert!(verify_cert_with_key(&vendor, &cert, 1800000000).is_err());
+            assert!(verify_cert_hybrid(&vendor, &pq, &cert, 1800000000).is_err());
+        }
+
+        #[test]
+        fn test_hybrid_verify_rejects_classic_cert() {
+            let (_, pq, _) = issue_hybrid();
+            let vendor_priv = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+            let cert = issue_cert_with_policy(
+                &vendor_priv.privkey,
+                create_test_payload(),
+                &PolicySet::builtin(),
+            )
+            .unwrap();
+            assert!(verify_cert_hybrid(&vendor_priv.pubkey, &pq, &cert, 1800000000).is_err());
+        }
+    }
+
     #[test]
     fn test_has_feature_all_wildcard() {
         let mut payload = create_test_payload();
-        payload.features = vec!["ALL".to_string()];
-        assert!(payload.has_feature("any_feature"));
-        assert!(payload.has_feature("another_feature"));
+        payload.features = vec!["ALL".into()];
+        assert!(payload.has_feature_at("any_feature", 1800000000));
+        assert!(payload.has_feature_at("another_feature", 1800000000));
     }
 
     #[test]
     fn test_has_feature_explicit() {
         let payload = create_test_payload(); // has "base" and "premium"
-        assert!(payload.has_feature("base"));
-        assert!(payload.has_feature("premium"));
-        assert!(!payload.has_feature("enterprise"));
+        assert!(payload.has_feature_at("base", 1800000000));
+        assert!(payload.has_feature_at("premium", 1800000000));
+        assert!(!payload.has_feature_at("enterprise", 1800000000));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_has_feature_at_window() {
+        let mut payload = create_test_payload();
+        payload
+            .features
+            .push(FeatureGrant::new("collab").with_window(Some(1800000000), Some(1900000000)));
+        // The deprecated window-unaware check still grants it outside the window
+        assert!(payload.has_feature("collab"));
+        assert!(!payload.has_feature_at("collab", 1700000000));
+        assert!(payload.has_feature_at("collab", 1850000000));
+        assert!(!payload.has_feature_at("collab", 1900000001));
+        assert!(payload.has_feature_at("base", 1900000001));
+    }
+
+    #[test]
+    fn test_windowed_feature_roundtrip() {
+        let vendor = try_vendor_keygen().unwrap();
+        let mut payload = create_test_payload();
+        payload
+            .features
+            .push(FeatureGrant::new("trial").with_window(None, Some(1900000000)));
+        let cert = issue_cert(&vendor.privkey, payload).unwrap();
+        let decoded = decode_cert(&encode_cert(&cert).unwrap()).unwrap();
+        assert_eq!(decoded.payload.features, cert.payload.features);
+        verify_cert(&vendor.pubkey, &decoded, 1800000000).unwrap();
+    }
+
+    #[test]
+    fn test_issue_rejects_empty_feature_window() {
+        let vendor = try_vendor_keygen().unwrap();
+        for (nbf, exp) in [(1900000000, 1800000000), (1800000000, 1800000000)] {
+            let mut payload = create_test_payload();
+            payload
+                .features
+                .push(FeatureGrant::new("trial").with_window(Some(nbf), Some(exp)));
+            let err = issue_cert(&vendor.privkey, payload).unwrap_err();
+            assert!(matches!(err, AegisError::Payload(_)));
+            assert!(err.to_string().contains("field 'feat': feature 'trial'"));
+        }
+    }
+
+    #[test]
+    fn test_plain_features_encode_as_strings() {
+        let payload = create_test_payload();
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&payload, &mut bytes).unwrap();
+        let value: ciborium::Value = ciborium::from_reader(bytes.as_slice()).unwrap();
+        let feat = value
+            .as_map()
+            .unwrap()
+            .iter()
+            .find(|(k, _)| k.as_text() == Some("feat"))
+            .map(|(_, v)| v.clone())
+            .unwrap();
+        assert!(feat.as_array().unwrap().iter().all(|f| f.is_text()));
+    }
+
+    #[test]
+    fn test_has_feature_prefix_grant() {
+        let mut payload = create_test_payload();
+        payload.features = vec!["export.*".into()];
+        assert!(payload.has_feature_at("export.pdf", 1800000000));
+        assert!(payload.has_feature_at("export.csv", 1800000000));
+        assert!(!payload.has_feature_at("import.csv", 1800000000));
+    }
+
+    #[test]
+    fn test_resolve_features_with_catalog() {
+        let mut payload = create_test_payload();
+        payload.features = vec!["enterprise".into()];
+        let catalog = FeatureCatalog::new().with_rule("enterprise", ["sso", "audit_log"]);
+        assert!(!payload.has_feature_at("sso", 1800000000));
+        let resolved = payload.resolve_features(&catalog, 1800000000);
+        assert!(resolved.contains("sso"));
+        assert!(resolved.contains("audit_log"));
+    }
+
+    #[test]
+    fn test_typed_entitlements() {
+        let vendor = try_vendor_keygen().unwrap();
+        let mut payload = create_test_payload();
+        payload
+            .entitlements
+            .insert("max_projects".to_string(), Entitlement::Int(10));
+        payload
+            .entitlements
+            .insert("cloud_sync".to_string(), Entitlement::Bool(true));
+        payload.entitlements.insert(
+            "export_formats".to_string(),
+            Entitlement::List(vec!["pdf".to_string(), "csv".to_string()]),
+        );
+        payload
+            .entitlements
+            .insert("region".to_string(), Entitlement::Str("eu".to_string()));
+
+        let cert = issue_cert(&vendor.privkey, payload).unwrap();
+        let decoded = decode_cert(&encode_cert(&cert).unwrap()).unwrap();
+        verify_cert(&vendor.pubkey, &decoded, 1800000000).unwrap();
+
+        let p = &decoded.payload;
+        assert_eq!(p.get_u64("max_projects"), Some(10));
+        assert_eq!(p.get_bool("cloud_sync"), Some(true));
+        assert_eq!(p.get_str("region"), Some("eu"));
+        assert_eq!(
+            p.get_list("export_formats"),
+            Some(&["pdf".to_string(), "csv".to_string()][..])
+        );
+        assert_eq!(p.get_u64("region"), None);
+        assert_eq!(p.get_u64("missing"), None);
+    }
+
+    #[test]
+    fn test_negative_int_not_u64() {
+        let mut payload = create_test_payload();
+        payload
+            .entitlements
+            .insert("offset".to_string(), Entitlement::Int(-5));
+        assert_eq!(payload.get_u64("offset"), None);
+        assert_eq!(payload.get_i64("offset"), Some(-5));
+    }
+
+    #[test]
+    fn test_legacy_metadata_encoding_unchanged() {
+        #[derive(Serialize)]
+        struct LegacyMetadata {
+            product: Option<String>,
+            version: Option<String>,
+            university: Option<String>,
+        }
+        let legacy = LegacyMetadata {
+            product: Some("Example Product".to_string()),
+            version: Some("1.0".to_string()),
+            university: Some("Example University".to_string()),
+        };
+        let meta = Metadata::new()
+            .with("university", "Example University")
+            .with("version", "1.0")
+            .with("product", "Example Product");
+
+        let mut legacy_bytes = Vec::new();
+        ciborium::into_writer(&legacy, &mut legacy_bytes).unwrap();
+        let mut meta_bytes = Vec::new();
+        ciborium::into_writer(&meta, &mut meta_bytes).unwrap();
+        assert_eq!(legacy_bytes, meta_bytes);
+
+        let decoded: Metadata = ciborium::from_reader(legacy_bytes.as_slice()).unwrap();
+        assert_eq!(decoded.get_str("university"), Some("Example University"));
+    }
+
+    #[test]
+    fn test_metadata_typed_values_roundtrip() {
+        let vendor = try_vendor_keygen().unwrap();
+        let mut payload = create_test_payload();
+        payload.metadata = Some(
+            Metadata::new()
+                .with("customer", "ACME Corp")
+                .with("order", 42i64)
+                .with("hw_id", vec![0xde, 0xad, 0xbe, 0xef])
+                .with("seed", b"ascii".to_vec()),
+        );
+        let cert = issue_cert(&vendor.privkey, payload).unwrap();
+        let decoded = decode_cert(&encode_cert(&cert).unwrap()).unwrap();
+        verify_cert(&vendor.pubkey, &decoded, 1800000000).unwrap();
+
+        let meta = decoded.payload.metadata.unwrap();
+        assert_eq!(meta.get_str("customer"), Some("ACME Corp"));
+        assert_eq!(meta.get("order"), Some(&MetaValue::Int(42)));
+        assert_eq!(
+            meta.get("hw_id"),
+            Some(&MetaValue::Bytes(vec![0xde, 0xad, 0xbe, 0xef]))
+        );
+        assert_eq!(meta.get("seed"), Some(&MetaValue::Bytes(b"ascii".to_vec())));
+    }
+
+    #[test]
+    fn test_metadata_size_limits() {
+        let vendor = try_vendor_keygen().unwrap();
+        let mut payload = create_test_payload();
+        payload.metadata =
+            Some(Metadata::new().with("blob", vec![0u8; MAX_METADATA_VALUE_LEN + 1]));
+        assert!(matches!(
+            issue_cert(&vendor.privkey, payload.clone()),
+            Err(AegisError::Payload(_))
+        ));
+
+        payload.metadata = Some(
+            (0..=MAX_METADATA_ENTRIES)
+                .map(|i| (format!("k{}", i), "v"))
+                .collect(),
+        );
+        assert!(matches!(
+            issue_cert(&vendor.privkey, payload),
+            Err(AegisError::Payload(_))
+        ));
+    }
+
+    #[test]
+    fn test_issue_verify_all_algorithms() {
+        for vendor_alg in SignatureAlgorithm::ALL {
+            for user_alg in SignatureAlgorithm::ALL {
+                let vendor = crate::keys::try_keygen(vendor_alg).unwrap();
+                let user = crate::keys::try_keygen(user_alg).unwrap();
+                let mut payload = create_test_payload();
+                payload.user_pubkey = user.pubkey.bytes.clone();
+                payload.user_alg = user_alg;
+
+                let cert = issue_cert_with_policy(&vendor.privkey, payload, &PolicySet::builtin())
+                    .unwrap();
+                assert_eq!(cert.payload.alg, vendor_alg);
+                let decoded = decode_cert(&encode_cert(&cert).unwrap()).unwrap();
+                verify_cert_with_key(&vendor.pubkey, &decoded, 1800000000).unwrap();
+                assert_eq!(decoded.payload.user_key().unwrap(), user.pubkey);
+            }
+        }
+    }
+
+    #[test]
+    fn test_algorithm_mismatch_rejected() {
+        let vendor = crate::keys::try_keygen(SignatureAlgorithm::Ed25519).unwrap();
+        let cert = issue_cert_with_policy(
+            &vendor.privkey,
+            create_test_payload(),
+            &PolicySet::builtin(),
+        )
+        .unwrap();
+
+        // Same key bytes presented as a different algorithm must not verify
+        let mut wrong = vendor.pubkey.clone();
+        wrong.alg = SignatureAlgorithm::P256;
+        assert!(matches!(
+            verify_cert_with_key(&wrong, &cert, 1800000000),
+            Err(AegisError::CertSignature)
+        ));
+
+        // Downgrading the signed alg field breaks the signature
+        let mut tampered = cert.clone();
+        tampered.payload.alg = SignatureAlgorithm::Secp256k1;
+        assert!(verify_cert_with_key(&vendor.pubkey, &tampered, 1800000000).is_err());
+    }
+
+    #[test]
+    fn test_default_alg_not_encoded() {
+        let payload = create_test_payload();
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&payload, &mut bytes).unwrap();
+        let value: ciborium::Value = ciborium::from_reader(bytes.as_slice()).unwrap();
+        let keys: Vec<_> = value
+            .as_map()
+            .unwrap()
+            .iter()
+            .filter_map(|(k, _)| k.as_text())
+            .collect();
+        assert!(!keys.contains(&"alg"));
+        assert!(!keys.contains(&"ualg"));
+    }
+
+    #[test]
+    fn test_user_key_length_checked_at_issue() {
+        let vendor = try_vendor_keygen().unwrap();
+        let mut payload = create_test_payload();
+        payload.user_alg = SignatureAlgorithm::Ed25519;
+        assert!(matches!(
+            issue_cert(&vendor.privkey, payload),
+            Err(AegisError::Key(_))
+        ));
     }
 
     #[test]
     fn test_campus_requires_expiry() {
-        let vendor = vendor_keygen();
+        let vendor = try_vendor_keygen().unwrap();
         let mut payload = create_test_payload();
         payload.tier = "campus".to_string();
         payload.expiry = None;
         assert!(matches!(
             issue_cert(&vendor.privkey, payload),
-            Err(AegisError::CertParse)
+            Err(AegisError::PolicyViolation(_))
         ));
     }
 
     #[test]
     fn test_campus_with_expiry_ok() {
-        let vendor = vendor_keygen();
+        let vendor = try_vendor_keygen().unwrap();
         let mut payload = create_test_payload();
         payload.tier = "campus".to_string();
         payload.expiry = Some(2000000000);
@@ -0,0 +1,328 @@
+//! COSE_Sign1 (RFC 9052) encoding of license certificates.
+//!
+//! The COSE payload is the canonical CBOR [`LicensePayload`]; the protected
+//! header carries the algorithm (`ES256K`, `ES256` or `EdDSA`) and a key ID
+//! derived from the vendor public key. COSE signs its own `Sig_structure`, so
+//! converting between formats re-signs with the vendor key after checking the
+//! existing signature.
+
+use super::{check_expiry, check_header, decode_payload, LicensePayload};
+#[cfg(feature = "issue")]
+use super::{payload_bytes, sign_payload, verify_cert_signature, LicenseCert};
+use crate::crypto::{sha256, SignatureAlgorithm};
+use crate::errors::AegisError;
+#[cfg(feature = "issue")]
+use crate::keys::PrivateKey;
+use crate::keys::PublicKey;
+use crate::prelude::*;
+use ciborium::Value;
+
+/// CBOR tag of a COSE_Sign1 message.
+pub const COSE_SIGN1_TAG: u64 = 18;
+
+const HEADER_ALG: i64 = 1;
+const HEADER_KID: i64 = 4;
+
+/// COSE algorithm identifier (IANA registry), if the algorithm has one.
+fn cose_alg_id(alg: SignatureAlgorithm) -> Option<i64> {
+    match alg {
+        SignatureAlgorithm::Secp256k1 => Some(-47), // ES256K (RFC 8812)
+        SignatureAlgorithm::P256 => Some(-7),       // ES256
+        SignatureAlgorithm::Ed25519 => Some(-8),    // EdDSA
+        SignatureAlgorithm::Schnorr => None,
+    }
+}
+
+fn alg_from_cose_id(id: i64) -> Option<SignatureAlgorithm> {
+    SignatureAlgorithm::ALL
+        .into_iter()
+        .find(|alg| cose_alg_id(*alg) == Some(id))
+}
+
+/// Key ID for the COSE `kid` header: first 8 bytes of SHA-256 of the vendor public key.
+pub fn key_id(vendor_key: &PublicKey) -> Vec<u8> {
+    sha256(&vendor_key.bytes)[..8].to_vec()
+}
+
+/// Decoded COSE_Sign1 certificate.
+#[derive(Debug, Clone)]
+pub struct CoseCert {
+    pub payload: LicensePayload,
+    /// Algorithm from the protected header
+    pub alg: SignatureAlgorithm,
+    /// Key ID from the protected header
+    pub kid: Option<Vec<u8>>,
+    pub signature: [u8; 64],
+    protected: Vec<u8>,
+    payload_bytes: Vec<u8>,
+}
+
+/// Encode a native certificate as a tagged COSE_Sign1 message.
+///
+/// The certificate must carry a valid signature from `vendor_key`; hybrid
+/// certificates are rejected because COSE_Sign1 holds a single signature.
+#[cfg(feature = "issue")]
+pub fn encode_cert_cose(
+    vendor_key: &PrivateKey,
+    cert: &LicenseCert,
+) -> Result<Vec<u8>, AegisError> {
+    if cert.pq_sig.is_some() || cert.payload.pq_alg.is_some() {
+        return Err(AegisError::Payload(
+            "hybrid certificates cannot be encoded as COSE_Sign1".to_string(),
+        ));
+    }
+    let alg_id = cose_alg_id(vendor_key.alg)
+        .ok_or_else(|| AegisError::Crypto(format!("No COSE algorithm for {}", vendor_key.alg)))?;
+    let public_key = vendor_key.public_key()?;
+    verify_cert_signature(&public_key, cert)?;
+
+    let protected = to_cbor(&Value::Map(vec![
+        (Value::from(HEADER_ALG), Value::from(alg_id)),
+        (Value::from(HEADER_KID), Value::Bytes(key_id(&public_key))),
+    ]))?;
+    let payload_bytes = payload_bytes(&cert.payload)?;
+    let signature = vendor_key.alg.scheme().sign_message(
+        &vendor_key.bytes,
+        &sig_structure(&protected, &payload_bytes)?,
+    )?;
+
+    to_cbor(&Value::Tag(
+        COSE_SIGN1_TAG,
+        Box::new(Value::Array(vec![
+            Value::Bytes(protected),
+            Value::Map(Vec::new()),
+            Value::Bytes(payload_bytes),
+            Value::Bytes(signature.to_vec()),
+        ])),
+    ))
+}
+
+/// Decode a COSE_Sign1 certificate (tagged or untagged) without verifying it.
+pub fn decode_cert_cose(cbor: &[u8]) -> Result<CoseCert, AegisError> {
+    let value: Value = ciborium::from_reader(cbor)
+        .map_err(|_| AegisError::parse("COSE_Sign1 is not valid CBOR"))?;
+    let value = match value {
+        Value::Tag(COSE_SIGN1_TAG, inner) => *inner,
+        Value::Tag(tag, _) => {
+            return Err(AegisError::parse(format!("unexpected CBOR tag {}", tag)))
+        }
+        untagged => untagged,
+    };
+    let [protected, unprotected, payload, signature]: [Value; 4] = value
+        .into_array()
+        .ok()
+        .and_then(|items| items.try_into().ok())
+        .ok_or_else(|| AegisError::parse("COSE_Sign1 must be an array of 4 items"))?;
+    if !unprotected.is_map() {
+        return Err(AegisError::parse_field("unprotected", "expected a map"));
+    }
+    let protected = protected
+        .into_bytes()
+        .map_err(|_| AegisError::parse_field("protected", "expected a byte string"))?;
+    // A nil payload would be a detached signature, which certificates never use.
+    let payload_bytes = payload
+        .into_bytes()
+        .map_err(|_| AegisError::parse_field("payload", "expected a byte string"))?;
+    let signature: [u8; 64] = signature
+        .into_bytes()
+        .ok()
+        .and_then(|sig| sig.try_into().ok())
+        .ok_or_else(|| AegisError::parse_field("signature", "expected 64 bytes"))?;
+
+    let headers = ciborium::from_reader::<Value, _>(protected.as_slice())
+        .ok()
+        .and_then(|headers| headers.into_map().ok())
+        .ok_or_else(|| AegisError::parse_field("protected", "expected an encoded map"))?;
+    let header = |label: i64| {
+        headers
+            .iter()
+            .find(|(k, _)| k.as_integer() == Some(label.into()))
+            .map(|(_, v)| v)
+    };
+    let alg = header(HEADER_ALG)
+        .and_then(Value::as_integer)
+        .and_then(|id| i64::try_from(id).ok())
+        .and_then(alg_from_cose_id)
+        .ok_or_else(|| AegisError::parse_field("alg", "missing or unsupported algorithm"))?;
+    let kid = match header(HEADER_KID) {
+        Some(kid) => Some(
+            kid.as_bytes()
+                .ok_or_else(|| AegisError::parse_field("kid", "expected a byte string"))?
+                .clone(),
+        ),
+        None => None,
+    };
+
+    let payload = decode_payload(&payload_bytes)?;
+
+    Ok(CoseCert {
+        payload,
+        alg,
+        kid,
+        signature,
+        protected,
+        payload_bytes,
+    })
+}
+
+/// Verify a COSE certificate; same checks and errors as [`super::verify_cert_with_key`].
+pub fn verify_cert_cose(
+    vendor_key: &PublicKey,
+    cert: &CoseCert,
+    now_unix: u64,
+) -> Result<(), AegisError> {
+    verify_cose_signature(vendor_key, cert)?;
+    check_expiry(&cert.payload, now_unix)
+}
+
+/// Convert a COSE certificate back to the native format, re-signing with `vendor_key`.
+#[cfg(feature = "issue")]
+pub fn cose_to_cert(vendor_key: &PrivateKey, cert: &CoseCert) -> Result<LicenseCert, AegisError> {
+    verify_cose_signature(&vendor_key.public_key()?, cert)?;
+    sign_payload(vendor_key, cert.payload.clone())
+}
+
+fn verify_cose_signature(vendor_key: &PublicKey, cert: &CoseCert) -> Result<(), AegisError> {
+    check_header(vendor_key, &cert.payload)?;
+    if cert.alg != vendor_key.alg {
+        return Err(AegisError::CertSignature);
+    }
+    vendor_key
+        .alg
+        .scheme()
+        .verify_message(
+            &vendor_key.bytes,
+            &sig_structure(&cert.protected, &cert.payload_bytes)?,
+            &cert.signature,
+        )
+        .map_err(|_| AegisError::CertSignature)
+}
+
+/// `Sig_structure` for COSE_Sign1 with empty external AAD (RFC 9052 §4.4).
+fn sig_structure(protected: &[u8], payload: &[u8]) -> Result<Vec<u8>, AegisError> {
+    to_cbor(&Value::Array(vec![
+        Value::Text("Signature1".to_string()),
+        Value::Bytes(protected.to_vec()),
+        Value::Bytes(Vec::new()),
+        Value::Bytes(payload.to_vec()),
+    ]))
+}
+
+fn to_cbor(value: &Value) -> Result<Vec<u8>, AegisError> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(value, &mut bytes)
+        .map_err(|e| AegisError::parse(format!("failed to encode COSE structure: {}", e)))?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cert::{issue_cert_with_policy, verify_cert_with_key, PROTOCOL_VERSION};
+    use crate::keys::{try_keygen, Keypair};
+    use crate::policy::PolicySet;
+    use std::collections::BTreeMap;
+
+    fn issue(alg: SignatureAlgorithm, expiry: Option<u64>) -> (Keypair, LicenseCert) {
+        let vendor = try_keygen(alg).unwrap();
+        let user = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let payload = LicensePayload {
+            version: PROTOCOL_VERSION,
+            alg,
+            pq_alg: None,
+            product_id: "test".to_string(),
+            license_id: [7u8; 32],
+            issued_at: 1700000000,
+            expiry,
+            user_pubkey: user.pubkey.bytes,
+            user_alg: SignatureAlgorithm::Secp256k1,
+            tier: "lifetime_pro".to_string(),
+            features: vec!["base".into()],
+            limits: None,
+            entitlements: BTreeMap::new(),
+            metadata: None,
+        };
+        let cert = issue_cert_with_policy(&vendor.privkey, payload, &PolicySet::builtin()).unwrap();
+        (vendor, cert)
+    }
+
+    #[test]
+    fn test_cose_roundtrip_verifies_like_native() {
+        for alg in [
+            SignatureAlgorithm::Secp256k1,
+            SignatureAlgorithm::P256,
+            SignatureAlgorithm::Ed25519,
+        ] {
+            let (vendor, cert) = issue(alg, Some(1900000000));
+            let cose =
+                decode_cert_cose(&encode_cert_cose(&vendor.privkey, &cert).unwrap()).unwrap();
+            assert_eq!(cose.payload, cert.payload);
+            assert_eq!(cose.alg, alg);
+            assert_eq!(cose.kid, Some(key_id(&vendor.pubkey)));
+            verify_cert_cose(&vendor.pubkey, &cose, 1800000000).unwrap();
+            assert!(matches!(
+                verify_cert_cose(&vendor.pubkey, &cose, 2000000000),
+                Err(AegisError::CertExpired)
+            ));
+
+            let native = cose_to_cert(&vendor.privkey, &cose).unwrap();
+            verify_cert_with_key(&vendor.pubkey, &native, 1800000000).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_cose_structure() {
+        let (vendor, cert) = issue(SignatureAlgorithm::Secp256k1, None);
+        let bytes = encode_cert_cose(&vendor.privkey, &cert).unwrap();
+        // Tag 18 followed by a 4-element array
+        assert_eq!(&bytes[..2], &[0xd2, 0x84]);
+        let cose = decode_cert_cose(&bytes).unwrap();
+        let headers: Value = ciborium::from_reader(cose.protected.as_slice()).unwrap();
+        let (label, alg) = &headers.as_map().unwrap()[0];
+        assert_eq!(label.as_integer(), Some(1.into()));
+        assert_eq!(alg.as_integer(), Some((-47).into()));
+    }
+
+    #[test]
+    fn test_cose_tampered_payload_rejected() {
+        let (vendor, cert) = issue(SignatureAlgorithm::Secp256k1, None);
+        let mut cose =
+            decode_cert_cose(&encode_cert_cose(&vendor.privkey, &cert).unwrap()).unwrap();
+        cose.payload_bytes[10] ^= 0x01;
+        assert!(matches!(
+            verify_cert_cose(&vendor.pubkey, &cose, 1800000000),
+            Err(AegisError::CertSignature)
+        ));
+        let other = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let cose = decode_cert_cose(&encode_cert_cose(&vendor.privkey, &cert).unwrap()).unwrap();
+        assert!(verify_cert_cose(&other.pubkey, &cose, 1800000000).is_err());
+    }
+
+    #[test]
+    fn test_cose_export_requires_valid_signature() {
+        let (vendor, mut cert) = issue(SignatureAlgorithm::Secp256k1, None);
+        let other = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        assert!(encode_cert_cose(&other.privkey, &cert).is_err());
+        cert.vendor_sig[3] ^= 0x01;
+        assert!(encode_cert_cose(&vendor.privkey, &cert).is_err());
+    }
+
+    #[test]
+    fn test_schnorr_has_no_cose_algorithm() {
+        let (vendor, cert) = issue(SignatureAlgorithm::Schnorr, None);
+        assert!(matches!(
+            encode_cert_cose(&vendor.privkey, &cert),
+            Err(AegisError::Crypto(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_rejects_native_cert() {
+        let (_, cert) = issue(SignatureAlgorithm::Secp256k1, None);
+        let native = crate::cert::encode_cert(&cert).unwrap();
+        assert!(matches!(
+            decode_cert_cose(&native),
+            Err(AegisError::CertParse { .. })
+        ));
+    }
+}
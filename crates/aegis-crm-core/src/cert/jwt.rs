@@ -0,0 +1,358 @@
+//! Compact JWS (JWT) encoding of license certificates for JOSE tooling.
+//!
+//! Claims: `sub` (user public key, hex), `iat`, `exp` (omitted when
+//! perpetual), `jti` (license ID, hex), `pid`, `tier`, `features` (grant
+//! names), and `lic`, the base64url canonical CBOR payload that makes the
+//! token convertible back without loss. The header carries `alg` (`ES256K`,
+//! `ES256` or `EdDSA`) and `kid` (hex of [`key_id`]).
+
+#[cfg(feature = "issue")]
+use super::cose::key_id;
+use super::{check_expiry, check_header, decode_payload, LicensePayload};
+#[cfg(feature = "issue")]
+use super::{payload_bytes, sign_payload, verify_cert_signature, LicenseCert};
+use crate::crypto::SignatureAlgorithm;
+use crate::errors::AegisError;
+#[cfg(feature = "issue")]
+use crate::keys::PrivateKey;
+use crate::keys::PublicKey;
+use crate::prelude::*;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{Deserialize, Serialize};
+
+/// JOSE algorithm name (RFC 7518 / RFC 8812), if the algorithm has one.
+fn jose_alg(alg: SignatureAlgorithm) -> Option<&'static str> {
+    match alg {
+        SignatureAlgorithm::Secp256k1 => Some("ES256K"),
+        SignatureAlgorithm::P256 => Some("ES256"),
+        SignatureAlgorithm::Ed25519 => Some("EdDSA"),
+        SignatureAlgorithm::Schnorr => None,
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JwtHeader {
+    alg: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    typ: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kid: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JwtClaims {
+    sub: String,
+    iat: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    exp: Option<u64>,
+    jti: String,
+    pid: String,
+    tier: String,
+    features: Vec<String>,
+    lic: String,
+}
+
+impl JwtClaims {
+    fn from_payload(payload: &LicensePayload, lic: &[u8]) -> Self {
+        Self {
+            sub: hex::encode(&payload.user_pubkey),
+            iat: payload.issued_at,
+            exp: payload.expiry,
+            jti: hex::encode(payload.license_id),
+            pid: payload.product_id.clone(),
+            tier: payload.tier.clone(),
+            features: payload.features.iter().map(|f| f.name.clone()).collect(),
+            lic: URL_SAFE_NO_PAD.encode(lic),
+        }
+    }
+}
+
+/// Decoded JWS certificate.
+#[derive(Debug, Clone)]
+pub struct JwtCert {
+    pub payload: LicensePayload,
+    /// Algorithm from the JOSE header
+    pub alg: SignatureAlgorithm,
+    /// Key ID from the JOSE header
+    pub kid: Option<String>,
+    pub signature: [u8; 64],
+    signing_input: String,
+}
+
+/// Encode a native certificate as a compact JWS signed with `vendor_key`.
+///
+/// The certificate must carry a valid signature from `vendor_key`; hybrid
+/// certificates are rejected because a JWS holds a single signature.
+#[cfg(feature = "issue")]
+pub fn encode_cert_jwt(vendor_key: &PrivateKey, cert: &LicenseCert) -> Result<String, AegisError> {
+    if cert.pq_sig.is_some() || cert.payload.pq_alg.is_some() {
+        return Err(AegisError::Payload(
+            "hybrid certificates cannot be encoded as JWS".to_string(),
+        ));
+    }
+    let alg = jose_alg(vendor_key.alg)
+        .ok_or_else(|| AegisError::Crypto(format!("No JOSE algorithm for {}", vendor_key.alg)))?;
+    let public_key = vendor_key.public_key()?;
+    verify_cert_signature(&public_key, cert)?;
+
+    let header = JwtHeader {
+        alg: alg.to_string(),
+        typ: Some("JWT".to_string()),
+        kid: Some(hex::encode(key_id(&public_key))),
+    };
+    let claims = JwtClaims::from_payload(&cert.payload, &payload_bytes(&cert.payload)?);
+    let signing_input = format!("{}.{}", to_base64_json(&header)?, to_base64_json(&claims)?);
+    let signature = vendor_key
+        .alg
+        .scheme()
+        .sign_message(&vendor_key.bytes, signing_input.as_bytes())?;
+    Ok(format!(
+        "{}.{}",
+        signing_input,
+        URL_SAFE_NO_PAD.encode(signature)
+    ))
+}
+
+/// Decode a compact JWS certificate without verifying it.
+///
+/// The standard claims must agree with the embedded `lic` payload.
+pub fn decode_cert_jwt(token: &str) -> Result<JwtCert, AegisError> {
+    let token = token.trim();
+    let mut parts = token.split('.');
+    let (Some(header), Some(claims), Some(signature), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(AegisError::parse("JWS must have 3 dot-separated parts"));
+    };
+
+    let header: JwtHeader = from_base64_json(header, "header")?;
+    let alg = SignatureAlgorithm::ALL
+        .into_iter()
+        .find(|alg| jose_alg(*alg) == Some(header.alg.as_str()))
+        .ok_or_else(|| {
+            AegisError::parse_field("alg", format!("unsupported algorithm '{}'", header.alg))
+        })?;
+    let claims: JwtClaims = from_base64_json(claims, "claims")?;
+    let signature: [u8; 64] = URL_SAFE_NO_PAD
+        .decode(signature)
+        .ok()
+        .and_then(|sig| sig.try_into().ok())
+        .ok_or_else(|| AegisError::parse_field("signature", "expected 64 base64url bytes"))?;
+
+    let lic = URL_SAFE_NO_PAD
+        .decode(&claims.lic)
+        .map_err(|_| AegisError::parse_field("lic", "invalid base64url"))?;
+    let payload = decode_payload(&lic)?;
+    let expected = JwtClaims::from_payload(&payload, &lic);
+    if (
+        &claims.sub,
+        claims.iat,
+        claims.exp,
+        &claims.jti,
+        &claims.pid,
+    ) != (
+        &expected.sub,
+        expected.iat,
+        expected.exp,
+        &expected.jti,
+        &expected.pid,
+    ) || claims.tier != expected.tier
+        || claims.features != expected.features
+    {
+        return Err(AegisError::parse_field(
+            "claims",
+            "standard claims disagree with the lic payload",
+        ));
+    }
+
+    let signing_input = token[..token.rfind('.').unwrap_or_default()].to_string();
+    Ok(JwtCert {
+        payload,
+        alg,
+        kid: header.kid,
+        signature,
+        signing_input,
+    })
+}
+
+/// Verify a JWS certificate; same checks and errors as [`super::verify_cert_with_key`].
+pub fn verify_cert_jwt(
+    vendor_key: &PublicKey,
+    cert: &JwtCert,
+    now_unix: u64,
+) -> Result<(), AegisError> {
+    verify_jwt_signature(vendor_key, cert)?;
+    check_expiry(&cert.payload, now_unix)
+}
+
+/// Convert a JWS certificate back to the native format, re-signing with `vendor_key`.
+#[cfg(feature = "issue")]
+pub fn jwt_to_cert(vendor_key: &PrivateKey, cert: &JwtCert) -> Result<LicenseCert, AegisError> {
+    verify_jwt_signature(&vendor_key.public_key()?, cert)?;
+    sign_payload(vendor_key, cert.payload.clone())
+}
+
+fn verify_jwt_signature(vendor_key: &PublicKey, cert: &JwtCert) -> Result<(), AegisError> {
+    check_header(vendor_key, &cert.payload)?;
+    if cert.alg != vendor_key.alg {
+        return Err(AegisError::CertSignature);
+    }
+    vendor_key
+        .alg
+        .scheme()
+        .verify_message(
+            &vendor_key.bytes,
+            cert.signing_input.as_bytes(),
+            &cert.signature,
+        )
+        .map_err(|_| AegisError::CertSignature)
+}
+
+#[cfg(feature = "issue")]
+fn to_base64_json<T: Serialize>(value: &T) -> Result<String, AegisError> {
+    let json = serde_json::to_vec(value)
+        .map_err(|e| AegisError::parse(format!("failed to encode JSON: {}", e)))?;
+    Ok(URL_SAFE_NO_PAD.encode(json))
+}
+
+/// Decode the base64url JSON `part` of a JWS (`header` or `claims`).
+fn from_base64_json<T: for<'de> Deserialize<'de>>(
+    encoded: &str,
+    part: &str,
+) -> Result<T, AegisError> {
+    let json = URL_SAFE_NO_PAD
+        .decode(encoded)
+        .map_err(|_| AegisError::parse_field(part, "invalid base64url"))?;
+    serde_json::from_slice(&json).map_err(|e| AegisError::parse_field(part, e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cert::{issue_cert_with_policy, verify_cert_with_key, PROTOCOL_VERSION};
+    use crate::keys::{try_keygen, Keypair};
+    use crate::policy::PolicySet;
+    use std::collections::BTreeMap;
+
+    fn issue(alg: SignatureAlgorithm, expiry: Option<u64>) -> (Keypair, LicenseCert) {
+        let vendor = try_keygen(alg).unwrap();
+        let user = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let payload = LicensePayload {
+            version: PROTOCOL_VERSION,
+            alg,
+            pq_alg: None,
+            product_id: "web_app".to_string(),
+            license_id: [9u8; 32],
+            issued_at: 1700000000,
+            expiry,
+            user_pubkey: user.pubkey.bytes,
+            user_alg: SignatureAlgorithm::Secp256k1,
+            tier: "lifetime_pro".to_string(),
+            features: vec!["base".into(), "export.*".into()],
+            limits: None,
+            entitlements: BTreeMap::new(),
+            metadata: None,
+        };
+        let cert = issue_cert_with_policy(&vendor.privkey, payload, &PolicySet::builtin()).unwrap();
+        (vendor, cert)
+    }
+
+    fn claims_of(token: &str) -> serde_json::Value {
+        let claims = token.split('.').nth(1).unwrap();
+        serde_json::from_slice(&URL_SAFE_NO_PAD.decode(claims).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_jwt_roundtrip_verifies_like_native() {
+        for alg in [
+            SignatureAlgorithm::Secp256k1,
+            SignatureAlgorithm::P256,
+            SignatureAlgorithm::Ed25519,
+        ] {
+            let (vendor, cert) = issue(alg, Some(1900000000));
+            let token = encode_cert_jwt(&vendor.privkey, &cert).unwrap();
+            let jwt = decode_cert_jwt(&token).unwrap();
+            assert_eq!(jwt.payload, cert.payload);
+            verify_cert_jwt(&vendor.pubkey, &jwt, 1800000000).unwrap();
+            assert!(matches!(
+                verify_cert_jwt(&vendor.pubkey, &jwt, 2000000000),
+                Err(AegisError::CertExpired)
+            ));
+            let native = jwt_to_cert(&vendor.privkey, &jwt).unwrap();
+            verify_cert_with_key(&vendor.pubkey, &native, 1800000000).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_jwt_standard_claims() {
+        let (vendor, cert) = issue(SignatureAlgorithm::Secp256k1, None);
+        let token = encode_cert_jwt(&vendor.privkey, &cert).unwrap();
+        let claims = claims_of(&token);
+        assert_eq!(claims["sub"], hex::encode(&cert.payload.user_pubkey));
+        assert_eq!(claims["jti"], hex::encode(cert.payload.license_id));
+        assert_eq!(claims["iat"], 1700000000);
+        assert!(claims.get("exp").is_none());
+        assert_eq!(claims["pid"], "web_app");
+        assert_eq!(claims["features"], serde_json::json!(["base", "export.*"]));
+    }
+
+    #[test]
+    fn test_es256k_signature_is_standard() {
+        use k256::ecdsa::signature::Verifier;
+        let (vendor, cert) = issue(SignatureAlgorithm::Secp256k1, None);
+        let token = encode_cert_jwt(&vendor.privkey, &cert).unwrap();
+        let (signing_input, sig) = token.rsplit_once('.').unwrap();
+        let key = k256::ecdsa::VerifyingKey::from_sec1_bytes(&vendor.pubkey.bytes).unwrap();
+        let sig =
+            k256::ecdsa::Signature::from_slice(&URL_SAFE_NO_PAD.decode(sig).unwrap()).unwrap();
+        key.verify(signing_input.as_bytes(), &sig).unwrap();
+    }
+
+    #[test]
+    fn test_schnorr_has_no_jose_alg() {
+        let (vendor, cert) = issue(SignatureAlgorithm::Schnorr, None);
+        assert!(matches!(
+            encode_cert_jwt(&vendor.privkey, &cert),
+            Err(AegisError::Crypto(_))
+        ));
+    }
+
+    #[test]
+    fn test_jwt_tampering_rejected() {
+        let (vendor, cert) = issue(SignatureAlgorithm::Secp256k1, None);
+        let token = encode_cert_jwt(&vendor.privkey, &cert).unwrap();
+        let parts: Vec<&str> = token.split('.').collect();
+
+        // Standard claims that disagree with the embedded payload
+        let mut claims = claims_of(&token);
+        claims["pid"] = "other_app".into();
+        let forged = format!(
+            "{}.{}.{}",
+            parts[0],
+            URL_SAFE_NO_PAD.encode(serde_json::to_vec(&claims).unwrap()),
+            parts[2]
+        );
+        assert!(matches!(
+            decode_cert_jwt(&forged),
+            Err(AegisError::CertParse { .. })
+        ));
+
+        // Certificates from another vendor are not re-signed
+        let (_, other) = issue(SignatureAlgorithm::Secp256k1, None);
+        assert!(matches!(
+            encode_cert_jwt(&vendor.privkey, &other),
+            Err(AegisError::CertSignature)
+        ));
+
+        // Flipped signature bit
+        let mut sig = URL_SAFE_NO_PAD.decode(parts[2]).unwrap();
+        sig[5] ^= 0x01;
+        let bad_sig = format!("{}.{}.{}", parts[0], parts[1], URL_SAFE_NO_PAD.encode(sig));
+        let jwt = decode_cert_jwt(&bad_sig).unwrap();
+        assert!(matches!(
+            verify_cert_jwt(&vendor.pubkey, &jwt, 1800000000),
+            Err(AegisError::CertSignature)
+        ));
+        assert!(decode_cert_jwt("not.a.jwt").is_err());
+    }
+}
@@ -0,0 +1,394 @@
+//! Human-typable license keys.
+//!
+//! A key is a compact positional CBOR re-encoding of the certificate,
+//! written in Crockford base32 as dash-separated groups of five data
+//! characters plus one check character:
+//!
+//! ```text
+//! 0C3ZE6-1HB4RK-...-7Q
+//! ```
+//!
+//! The check character is Luhn mod 32 over the group, seeded with the group
+//! index, so a mistyped character is reported with the group it is in.
+//! Decoding is case-insensitive, maps `O` to `0` and `I`/`L` to `1`, and
+//! ignores dashes and whitespace. Feature grants found in the caller's bit
+//! table (see [`FeatureCatalog::bits`](crate::features::FeatureCatalog))
+//! are stored as a bitmask; the signature still covers the canonical
+//! payload, which is rebuilt exactly on decode.
+
+use super::{check_decoded, from_fields, LicenseCert, LicensePayload, PAYLOAD_REQUIRED};
+use crate::errors::AegisError;
+use crate::prelude::*;
+use ciborium::Value;
+
+/// Binary layout version, stored as the first byte of the key.
+const KEY_VERSION: u8 = 1;
+/// Payload fields stored by position, in order; `null` marks an absent field.
+const POSITIONAL: [&str; 9] = [
+    "v", "alg", "pid", "lid", "iat", "exp", "upk", "ualg", "tier",
+];
+/// Data characters per group, excluding the check character.
+pub const KEY_GROUP_LEN: usize = 5;
+/// Crockford base32 alphabet.
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Encode a certificate as a license key.
+///
+/// `feature_bits` lists feature names in bitmask order (at most 64 are
+/// used). Grants are stored as a bitmask when every grant is an
+/// unwindowed name from the table, in table order; otherwise as names.
+#[cfg(feature = "cli-helpers")]
+pub fn encode_cert_key(cert: &LicenseCert, feature_bits: &[String]) -> Result<String, AegisError> {
+    if cert.pq_sig.is_some() || cert.payload.pq_alg.is_some() {
+        return Err(AegisError::Payload(
+            "hybrid certificates cannot be encoded as license keys".to_string(),
+        ));
+    }
+    let Ok(Value::Map(mut entries)) = Value::serialized(&cert.payload) else {
+        return Err(AegisError::parse("payload does not serialize to a map"));
+    };
+    let mut take = |name: &str| {
+        entries
+            .iter()
+            .position(|(k, _)| k.as_text() == Some(name))
+            .map(|i| entries.remove(i).1)
+            .unwrap_or(Value::Null)
+    };
+
+    let mut items: Vec<Value> = POSITIONAL.iter().map(|name| take(name)).collect();
+    let features = take("feat");
+    items.push(match feature_mask(&cert.payload, feature_bits) {
+        Some(mask) => Value::from(mask),
+        None => features,
+    });
+    items.push(Value::Bytes(cert.vendor_sig.to_vec()));
+    if !entries.is_empty() {
+        items.push(Value::Map(entries));
+    }
+
+    let mut bytes = vec![KEY_VERSION];
+    ciborium::into_writer(&Value::Array(items), &mut bytes)
+        .map_err(|e| AegisError::parse(format!("failed to encode license key: {}", e)))?;
+    Ok(to_groups(&bytes))
+}
+
+/// Decode a license key without verifying it.
+///
+/// `feature_bits` must be the table the key was encoded with.
+pub fn decode_cert_key(key: &str, feature_bits: &[String]) -> Result<LicenseCert, AegisError> {
+    let bytes = from_groups(key)?;
+    let (&version, body) = bytes
+        .split_first()
+        .ok_or_else(|| AegisError::LicenseKey("empty key".to_string()))?;
+    if version != KEY_VERSION {
+        return Err(AegisError::LicenseKey(format!(
+            "unsupported key version {}",
+            version
+        )));
+    }
+    let Ok(Value::Array(items)) = ciborium::from_reader::<Value, _>(body) else {
+        return Err(AegisError::parse("license key body is not a CBOR array"));
+    };
+    if !(POSITIONAL.len() + 2..=POSITIONAL.len() + 3).contains(&items.len()) {
+        return Err(AegisError::parse(format!(
+            "license key has {} items, expected {} or {}",
+            items.len(),
+            POSITIONAL.len() + 2,
+            POSITIONAL.len() + 3
+        )));
+    }
+
+    let mut items = items.into_iter();
+    let mut entries = Vec::new();
+    for (name, value) in POSITIONAL.iter().zip(items.by_ref()) {
+        if !value.is_null() {
+            entries.push((Value::from(*name), value));
+        }
+    }
+    let features = match items.next() {
+        Some(Value::Integer(mask)) => {
+            let mask = u64::try_from(mask)
+                .map_err(|_| AegisError::parse_field("feat", "bitmask out of range"))?;
+            Value::Array(mask_names(mask, feature_bits)?)
+        }
+        Some(features) => features,
+        None => return Err(AegisError::parse_field("feat", "missing")),
+    };
+    entries.push((Value::from("feat"), features));
+    let vendor_sig = match items.next() {
+        Some(Value::Bytes(sig)) => sig
+            .try_into()
+            .map_err(|_| AegisError::parse_field("sig", "expected 64 bytes"))?,
+        _ => return Err(AegisError::parse_field("sig", "expected a byte string")),
+    };
+    match items.next() {
+        Some(Value::Map(extra)) => entries.extend(extra),
+        Some(_) => return Err(AegisError::parse("trailing license key item must be a map")),
+        None => {}
+    }
+
+    let payload: LicensePayload = from_fields(entries, PAYLOAD_REQUIRED)?;
+    check_decoded(&payload)?;
+    Ok(LicenseCert {
+        payload,
+        vendor_sig,
+        pq_sig: None,
+    })
+}
+
+#[cfg(feature = "cli-helpers")]
+fn feature_mask(payload: &LicensePayload, feature_bits: &[String]) -> Option<u64> {
+    let mut mask = 0u64;
+    let mut last = None;
+    for grant in &payload.features {
+        if grant.is_windowed() {
+            return None;
+        }
+        let bit = feature_bits
+            .iter()
+            .take(64)
+            .position(|name| *name == grant.name)?;
+        // Bitmask order is table order, so grants must already follow it
+        if last.is_some_and(|last| bit <= last) {
+            return None;
+        }
+        last = Some(bit);
+        mask |= 1 << bit;
+    }
+    Some(mask)
+}
+
+fn mask_names(mask: u64, feature_bits: &[String]) -> Result<Vec<Value>, AegisError> {
+    (0..64)
+        .filter(|bit| mask & (1 << bit) != 0)
+        .map(|bit| {
+            feature_bits
+                .get(bit)
+                .map(|name| Value::from(name.as_str()))
+                .ok_or_else(|| {
+                    AegisError::LicenseKey(format!("feature bit {} is not in the bit table", bit))
+                })
+        })
+        .collect()
+}
+
+/// Luhn mod 32 check value for one group, seeded with its index.
+fn check_value(index: usize, values: &[u8]) -> u8 {
+    let mut sum = index % 32;
+    for (i, &value) in values.iter().rev().enumerate() {
+        let addend = usize::from(value) * if i % 2 == 0 { 2 } else { 1 };
+        sum += addend / 32 + addend % 32;
+    }
+    ((32 - sum % 32) % 32) as u8
+}
+
+#[cfg(feature = "cli-helpers")]
+fn to_groups(bytes: &[u8]) -> String {
+    let mut values = Vec::with_capacity(bytes.len() * 8 / 5 + 1);
+    let (mut acc, mut bits) = (0u16, 0);
+    for &byte in bytes {
+        acc = (acc << 8) | u16::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            values.push(((acc >> bits) & 0x1f) as u8);
+        }
+    }
+    if bits > 0 {
+        values.push(((acc << (5 - bits)) & 0x1f) as u8);
+    }
+
+    let groups: Vec<String> = values
+        .chunks(KEY_GROUP_LEN)
+        .enumerate()
+        .map(|(index, group)| {
+            group
+                .iter()
+                .chain(core::iter::once(&check_value(index, group)))
+                .map(|&v| char::from(ALPHABET[usize::from(v)]))
+                .collect()
+        })
+        .collect();
+    groups.join("-")
+}
+
+fn from_groups(key: &str) -> Result<Vec<u8>, AegisError> {
+    let chars: Vec<char> = key
+        .chars()
+        .filter(|c| *c != '-' && !c.is_whitespace())
+        .collect();
+    let mut values = Vec::with_capacity(chars.len());
+    for (index, group) in chars.chunks(KEY_GROUP_LEN + 1).enumerate() {
+        let text: String = group.iter().collect();
+        if group.len() < 2 {
+            return Err(AegisError::LicenseKey(format!(
+                "group {} ({}) is truncated",
+                index + 1,
+                text
+            )));
+        }
+        let mut group_values = Vec::with_capacity(group.len());
+        for &c in group {
+            let value = symbol_value(c).ok_or_else(|| {
+                AegisError::LicenseKey(format!(
+                    "invalid character '{}' in group {} ({})",
+                    c,
+                    index + 1,
+                    text
+                ))
+            })?;
+            group_values.push(value);
+        }
+        let (check, data) = group_values.split_last().unwrap_or((&0, &[]));
+        if check_value(index, data) != *check {
+            return Err(AegisError::LicenseKey(format!(
+                "check character mismatch in group {} ({}), likely a typo",
+                index + 1,
+                text
+            )));
+        }
+        values.extend_from_slice(data);
+    }
+
+    let mut bytes = Vec::with_capacity(values.len() * 5 / 8);
+    let (mut acc, mut bits) = (0u16, 0);
+    for value in values {
+        acc = (acc << 5) | u16::from(value);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((acc >> bits) as u8);
+        }
+        acc &= (1 << bits) - 1;
+    }
+    if acc != 0 {
+        return Err(AegisError::LicenseKey("non-zero padding bits".to_string()));
+    }
+    Ok(bytes)
+}
+
+fn symbol_value(c: char) -> Option<u8> {
+    let c = match c.to_ascii_uppercase() {
+        'O' => '0',
+        'I' | 'L' => '1',
+        c => c,
+    };
+    ALPHABET
+        .iter()
+        .position(|&a| char::from(a) == c)
+        .map(|v| v as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cert::{
+        encode_cert, issue_cert_with_policy, verify_cert_with_key, FeatureGrant, PROTOCOL_VERSION,
+    };
+    use crate::crypto::SignatureAlgorithm;
+    use crate::keys::{try_keygen, Keypair};
+    use crate::policy::PolicySet;
+    use std::collections::BTreeMap;
+
+    fn bits() -> Vec<String> {
+        ["base", "pro", "export.*", "sso"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    fn issue(features: Vec<FeatureGrant>) -> (Keypair, LicenseCert) {
+        let vendor = try_keygen(SignatureAlgorithm::Ed25519).unwrap();
+        let user = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let payload = LicensePayload {
+            version: PROTOCOL_VERSION,
+            alg: SignatureAlgorithm::Ed25519,
+            pq_alg: None,
+            product_id: "app".to_string(),
+            license_id: [5u8; 32],
+            issued_at: 1700000000,
+            expiry: Some(1900000000),
+            user_pubkey: user.pubkey.bytes,
+            user_alg: SignatureAlgorithm::Secp256k1,
+            tier: "lifetime_pro".to_string(),
+            features,
+            limits: None,
+            entitlements: BTreeMap::new(),
+            metadata: None,
+        };
+        let cert = issue_cert_with_policy(&vendor.privkey, payload, &PolicySet::builtin()).unwrap();
+        (vendor, cert)
+    }
+
+    #[test]
+    fn test_key_roundtrip_with_bitmask() {
+        let (vendor, cert) = issue(vec!["base".into(), "export.*".into()]);
+        let key = encode_cert_key(&cert, &bits()).unwrap();
+        assert!(key.split('-').all(|group| group.len() <= KEY_GROUP_LEN + 1));
+        let decoded = decode_cert_key(&key, &bits()).unwrap();
+        assert_eq!(encode_cert(&decoded).unwrap(), encode_cert(&cert).unwrap());
+        verify_cert_with_key(&vendor.pubkey, &decoded, 1800000000).unwrap();
+
+        // Without the bitmask the key carries feature names and is longer
+        let named = encode_cert_key(&cert, &[]).unwrap();
+        assert!(named.len() > key.len());
+        let decoded = decode_cert_key(&named, &[]).unwrap();
+        assert_eq!(encode_cert(&decoded).unwrap(), encode_cert(&cert).unwrap());
+        assert!(key.len() < encode_cert(&cert).unwrap().len() * 2);
+    }
+
+    #[test]
+    fn test_key_falls_back_to_names() {
+        // Windowed grants and out-of-order grants cannot use the bitmask
+        for features in [
+            vec![FeatureGrant::new("base").with_window(None, Some(1850000000))],
+            vec!["sso".into(), "base".into()],
+            vec!["base".into(), "unknown".into()],
+        ] {
+            let (_, cert) = issue(features);
+            let key = encode_cert_key(&cert, &bits()).unwrap();
+            let decoded = decode_cert_key(&key, &bits()).unwrap();
+            assert_eq!(encode_cert(&decoded).unwrap(), encode_cert(&cert).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_key_is_forgiving_to_type() {
+        let (_, cert) = issue(vec!["base".into()]);
+        let key = encode_cert_key(&cert, &bits()).unwrap();
+        let typed = key
+            .to_lowercase()
+            .replace('0', "o")
+            .replace('1', "l")
+            .replace('-', " ");
+        let decoded = decode_cert_key(&typed, &bits()).unwrap();
+        assert_eq!(decoded.vendor_sig, cert.vendor_sig);
+    }
+
+    #[test]
+    fn test_key_typo_is_located() {
+        let (_, cert) = issue(vec!["base".into()]);
+        let key = encode_cert_key(&cert, &bits()).unwrap();
+        let mut groups: Vec<String> = key.split('-').map(str::to_string).collect();
+        let c = groups[3].remove(2);
+        groups[3].insert(2, if c == 'X' { 'Y' } else { 'X' });
+        match decode_cert_key(&groups.join("-"), &bits()) {
+            Err(AegisError::LicenseKey(msg)) => assert!(msg.contains("group 4"), "{}", msg),
+            other => panic!("unexpected {:?}", other.map(|_| ())),
+        }
+        assert!(matches!(
+            decode_cert_key("ABCDE-U", &bits()),
+            Err(AegisError::LicenseKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_unknown_feature_bit_rejected() {
+        let (_, cert) = issue(vec!["sso".into()]);
+        let key = encode_cert_key(&cert, &bits()).unwrap();
+        assert!(matches!(
+            decode_cert_key(&key, &bits()[..2]),
+            Err(AegisError::LicenseKey(_))
+        ));
+    }
+}
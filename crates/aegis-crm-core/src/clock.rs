@@ -0,0 +1,116 @@
+//! Time sources for issuance and verification.
+//!
+//! Everything that needs "now" takes a [`Clock`], so tests and what-if checks
+//! can pin or shift time instead of reading the system clock directly.
+
+use crate::errors::AegisError;
+use crate::prelude::*;
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Source of the current Unix time in seconds.
+pub trait Clock {
+    fn now_unix(&self) -> Result<u64, AegisError>;
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now_unix(&self) -> Result<u64, AegisError> {
+        (**self).now_unix()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Box<C> {
+    fn now_unix(&self) -> Result<u64, AegisError> {
+        (**self).now_unix()
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<C: Clock + ?Sized> Clock for alloc::sync::Arc<C> {
+    fn now_unix(&self) -> Result<u64, AegisError> {
+        (**self).now_unix()
+    }
+}
+
+/// The operating system clock (needs the `std` feature).
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now_unix(&self) -> Result<u64, AegisError> {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .map_err(|e| AegisError::Clock(format!("system clock before Unix epoch: {}", e)))
+    }
+}
+
+/// A clock stopped at a given time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock(pub u64);
+
+impl FixedClock {
+    pub fn new(now_unix: u64) -> Self {
+        Self(now_unix)
+    }
+}
+
+impl Clock for FixedClock {
+    fn now_unix(&self) -> Result<u64, AegisError> {
+        Ok(self.0)
+    }
+}
+
+/// Another clock shifted by a signed number of seconds (saturating at 0).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OffsetClock<C> {
+    inner: C,
+    offset_secs: i64,
+}
+
+impl<C: Clock> OffsetClock<C> {
+    pub fn new(inner: C, offset_secs: i64) -> Self {
+        Self { inner, offset_secs }
+    }
+}
+
+impl<C: Clock> Clock for OffsetClock<C> {
+    fn now_unix(&self) -> Result<u64, AegisError> {
+        Ok(self
+            .inner
+            .now_unix()?
+            .saturating_add_signed(self.offset_secs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_and_offset_clocks() {
+        let fixed = FixedClock::new(1_700_000_000);
+        assert_eq!(fixed.now_unix().unwrap(), 1_700_000_000);
+        assert_eq!(
+            OffsetClock::new(fixed, 86_400).now_unix().unwrap(),
+            1_700_086_400
+        );
+        assert_eq!(
+            OffsetClock::new(fixed, -86_400).now_unix().unwrap(),
+            1_699_913_600
+        );
+        assert_eq!(OffsetClock::new(FixedClock(5), -10).now_unix().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_system_clock_and_indirection() {
+        let now = SystemClock.now_unix().unwrap();
+        assert!(now > 1_700_000_000);
+        let boxed: Box<dyn Clock> = Box::new(OffsetClock::new(SystemClock, 3600));
+        assert!(boxed.now_unix().unwrap() >= now + 3600);
+        let shared = std::sync::Arc::new(FixedClock::new(7));
+        assert_eq!(OffsetClock::new(&shared, 1).now_unix().unwrap(), 8);
+    }
+}
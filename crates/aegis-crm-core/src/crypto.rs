@@ -1,10 +1,402 @@
 //! Cryptographic primitives for Aegis CRM.
+//!
+//! Certificates and PoP can be signed with any [`SignatureAlgorithm`];
+//! secp256k1 ECDSA stays the default and is what v1.0 certificates use.
+//! Every scheme takes 32-byte private keys and produces 64-byte signatures.
+//! ECDSA signatures are always produced and required in low-S form, so each
+//! signed message has exactly one valid signature encoding.
 
 use crate::errors::AegisError;
-use k256::ecdsa::{signature::Signer, signature::Verifier, Signature, SigningKey, VerifyingKey};
-use k256::EncodedPoint;
+use crate::prelude::*;
+use core::fmt;
+use core::str::FromStr;
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
+/// Signature algorithm used for a key or certificate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureAlgorithm {
+    /// ECDSA over secp256k1 (SPEC §2, v1.0 default)
+    #[default]
+    Secp256k1,
+    /// ECDSA over NIST P-256
+    P256,
+    /// Ed25519 (pure EdDSA over the 32-byte digest)
+    Ed25519,
+    /// BIP340 Schnorr over secp256k1 (x-only keys, batch-verifiable)
+    Schnorr,
+}
+
+impl SignatureAlgorithm {
+    pub const ALL: [SignatureAlgorithm; 4] = [
+        SignatureAlgorithm::Secp256k1,
+        SignatureAlgorithm::P256,
+        SignatureAlgorithm::Ed25519,
+        SignatureAlgorithm::Schnorr,
+    ];
+
+    /// Lowercase name used in certificates and key files
+    pub fn name(self) -> &'static str {
+        match self {
+            SignatureAlgorithm::Secp256k1 => "secp256k1",
+            SignatureAlgorithm::P256 => "p256",
+            SignatureAlgorithm::Ed25519 => "ed25519",
+            SignatureAlgorithm::Schnorr => "schnorr",
+        }
+    }
+
+    /// Whether this is the v1.0 default (omitted from encoded certificates)
+    pub fn is_default(&self) -> bool {
+        *self == SignatureAlgorithm::default()
+    }
+
+    /// Implementation of this algorithm
+    pub fn scheme(self) -> &'static dyn SignatureScheme {
+        match self {
+            SignatureAlgorithm::Secp256k1 => &Secp256k1Scheme,
+            SignatureAlgorithm::P256 => &P256Scheme,
+            SignatureAlgorithm::Ed25519 => &Ed25519Scheme,
+            SignatureAlgorithm::Schnorr => &SchnorrScheme,
+        }
+    }
+}
+
+impl fmt::Display for SignatureAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for SignatureAlgorithm {
+    type Err = AegisError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SignatureAlgorithm::ALL
+            .into_iter()
+            .find(|alg| alg.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| AegisError::Crypto(format!("Unknown signature algorithm: {}", s)))
+    }
+}
+
+/// Signing and verification of messages.
+///
+/// Certificates and PoP sign a 32-byte digest; COSE and JWS sign their own
+/// signing input. ECDSA schemes hash the message with SHA-256 before signing.
+pub trait SignatureScheme: Sync {
+    fn algorithm(&self) -> SignatureAlgorithm;
+
+    /// Encoded public key length (33 for compressed SEC1, 32 for Ed25519 and Schnorr).
+    fn public_key_len(&self) -> usize;
+
+    /// Derive the encoded public key from a private key.
+    fn public_key(&self, privkey: &[u8; 32]) -> Result<Vec<u8>, AegisError>;
+
+    /// Sign a message, returning a compact 64-byte signature.
+    #[cfg(feature = "pop")]
+    fn sign_message(&self, privkey: &[u8; 32], message: &[u8]) -> Result<[u8; 64], AegisError>;
+
+    /// Verify a compact signature over a message.
+    ///
+    /// Malleable encodings (high-S ECDSA) are rejected.
+    fn verify_message(
+        &self,
+        pubkey: &[u8],
+        message: &[u8],
+        sig: &[u8; 64],
+    ) -> Result<(), AegisError>;
+
+    /// Sign a digest, returning a compact 64-byte signature.
+    #[cfg(feature = "pop")]
+    fn sign(&self, privkey: &[u8; 32], digest: &[u8; 32]) -> Result<[u8; 64], AegisError> {
+        self.sign_message(privkey, digest)
+    }
+
+    /// Verify a compact signature over a digest.
+    fn verify(&self, pubkey: &[u8], digest: &[u8; 32], sig: &[u8; 64]) -> Result<(), AegisError> {
+        self.verify_message(pubkey, digest, sig)
+    }
+
+    /// Canonical encoding of a signature (low-S for ECDSA).
+    ///
+    /// Schemes without malleable encodings return the signature unchanged.
+    fn normalize(&self, sig: &[u8; 64]) -> Result<[u8; 64], AegisError> {
+        Ok(*sig)
+    }
+}
+
+/// ECDSA secp256k1 with compressed SEC1 keys and compact (r||s) signatures.
+pub struct Secp256k1Scheme;
+
+/// ECDSA P-256 with compressed SEC1 keys and compact (r||s) signatures.
+pub struct P256Scheme;
+
+/// Ed25519 with 32-byte public keys.
+pub struct Ed25519Scheme;
+
+/// BIP340 Schnorr over secp256k1 with 32-byte x-only public keys.
+pub struct SchnorrScheme;
+
+/// Implements the scheme for a RustCrypto ECDSA curve crate.
+macro_rules! ecdsa_scheme {
+    ($scheme:ty, $curve:ident, $alg:expr) => {
+        impl SignatureScheme for $scheme {
+            fn algorithm(&self) -> SignatureAlgorithm {
+                $alg
+            }
+
+            fn public_key_len(&self) -> usize {
+                33
+            }
+
+            fn public_key(&self, privkey: &[u8; 32]) -> Result<Vec<u8>, AegisError> {
+                let signing_key = $curve::ecdsa::SigningKey::from_bytes(privkey.into())
+                    .map_err(|e| AegisError::Key(format!("Invalid private key: {}", e)))?;
+                let encoded = signing_key.verifying_key().to_encoded_point(true);
+                Ok(encoded.as_bytes().to_vec())
+            }
+
+            #[cfg(feature = "pop")]
+            fn sign_message(
+                &self,
+                privkey: &[u8; 32],
+                message: &[u8],
+            ) -> Result<[u8; 64], AegisError> {
+                use $curve::ecdsa::signature::Signer;
+                let signing_key = $curve::ecdsa::SigningKey::from_bytes(privkey.into())
+                    .map_err(|e| AegisError::Crypto(format!("Invalid signing key: {}", e)))?;
+                let signature: $curve::ecdsa::Signature = signing_key.sign(message);
+                let signature = signature.normalize_s().unwrap_or(signature);
+                Ok(signature.to_bytes().into())
+            }
+
+            fn verify_message(
+                &self,
+                pubkey: &[u8],
+                message: &[u8],
+                sig: &[u8; 64],
+            ) -> Result<(), AegisError> {
+                use $curve::ecdsa::signature::Verifier;
+                if pubkey.len() != 33 {
+                    return Err(AegisError::Crypto(format!(
+                        "Invalid public key: expected 33 bytes, got {}",
+                        pubkey.len()
+                    )));
+                }
+                let verifying_key = $curve::ecdsa::VerifyingKey::from_sec1_bytes(pubkey)
+                    .map_err(|e| AegisError::Crypto(format!("Invalid public key: {}", e)))?;
+                let signature = $curve::ecdsa::Signature::from_bytes(sig.into())
+                    .map_err(|e| AegisError::Crypto(format!("Invalid signature format: {}", e)))?;
+                if signature.normalize_s().is_some() {
+                    return Err(AegisError::Crypto(
+                        "Non-canonical signature: high-S value".to_string(),
+                    ));
+                }
+                verifying_key
+                    .verify(message, &signature)
+                    .map_err(|_| AegisError::Crypto("Signature verification failed".to_string()))
+            }
+
+            fn normalize(&self, sig: &[u8; 64]) -> Result<[u8; 64], AegisError> {
+                let signature = $curve::ecdsa::Signature::from_bytes(sig.into())
+                    .map_err(|e| AegisError::Crypto(format!("Invalid signature format: {}", e)))?;
+                Ok(signature
+                    .normalize_s()
+                    .map_or(*sig, |low| low.to_bytes().into()))
+            }
+        }
+    };
+}
+
+ecdsa_scheme!(Secp256k1Scheme, k256, SignatureAlgorithm::Secp256k1);
+ecdsa_scheme!(P256Scheme, p256, SignatureAlgorithm::P256);
+
+impl SignatureScheme for Ed25519Scheme {
+    fn algorithm(&self) -> SignatureAlgorithm {
+        SignatureAlgorithm::Ed25519
+    }
+
+    fn public_key_len(&self) -> usize {
+        32
+    }
+
+    fn public_key(&self, privkey: &[u8; 32]) -> Result<Vec<u8>, AegisError> {
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(privkey);
+        Ok(signing_key.verifying_key().to_bytes().to_vec())
+    }
+
+    #[cfg(feature = "pop")]
+    fn sign_message(&self, privkey: &[u8; 32], message: &[u8]) -> Result<[u8; 64], AegisError> {
+        use ed25519_dalek::Signer;
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(privkey);
+        Ok(signing_key.sign(message).to_bytes())
+    }
+
+    fn verify_message(
+        &self,
+        pubkey: &[u8],
+        message: &[u8],
+        sig: &[u8; 64],
+    ) -> Result<(), AegisError> {
+        let pubkey: &[u8; 32] = pubkey.try_into().map_err(|_| {
+            AegisError::Crypto(format!(
+                "Invalid public key: expected 32 bytes, got {}",
+                pubkey.len()
+            ))
+        })?;
+        let verifying_key = ed25519_dalek::VerifyingKey::from_bytes(pubkey)
+            .map_err(|e| AegisError::Crypto(format!("Invalid public key: {}", e)))?;
+        let signature = ed25519_dalek::Signature::from_bytes(sig);
+        verifying_key
+            .verify_strict(message, &signature)
+            .map_err(|_| AegisError::Crypto("Signature verification failed".to_string()))
+    }
+}
+
+impl SignatureScheme for SchnorrScheme {
+    fn algorithm(&self) -> SignatureAlgorithm {
+        SignatureAlgorithm::Schnorr
+    }
+
+    fn public_key_len(&self) -> usize {
+        32
+    }
+
+    fn public_key(&self, privkey: &[u8; 32]) -> Result<Vec<u8>, AegisError> {
+        let signing_key = k256::schnorr::SigningKey::from_bytes(privkey)
+            .map_err(|e| AegisError::Key(format!("Invalid private key: {}", e)))?;
+        Ok(signing_key.verifying_key().to_bytes().to_vec())
+    }
+
+    #[cfg(feature = "pop")]
+    fn sign_message(&self, privkey: &[u8; 32], message: &[u8]) -> Result<[u8; 64], AegisError> {
+        let signing_key = k256::schnorr::SigningKey::from_bytes(privkey)
+            .map_err(|e| AegisError::Crypto(format!("Invalid signing key: {}", e)))?;
+        // BIP340 allows all-zero auxiliary randomness; it only adds side-channel hardening
+        #[cfg(not(feature = "std"))]
+        let aux_rand = [0u8; 32];
+        #[cfg(feature = "std")]
+        let aux_rand = {
+            let mut aux_rand = [0u8; 32];
+            fill_random(&mut aux_rand)?;
+            aux_rand
+        };
+        let signature = signing_key
+            .sign_raw(message, &aux_rand)
+            .map_err(|e| AegisError::Crypto(format!("Signing failed: {}", e)))?;
+        Ok(signature.to_bytes())
+    }
+
+    fn verify_message(
+        &self,
+        pubkey: &[u8],
+        message: &[u8],
+        sig: &[u8; 64],
+    ) -> Result<(), AegisError> {
+        if pubkey.len() != 32 {
+            return Err(AegisError::Crypto(format!(
+                "Invalid public key: expected 32 bytes, got {}",
+                pubkey.len()
+            )));
+        }
+        let verifying_key = k256::schnorr::VerifyingKey::from_bytes(pubkey)
+            .map_err(|e| AegisError::Crypto(format!("Invalid public key: {}", e)))?;
+        let signature = k256::schnorr::Signature::try_from(&sig[..])
+            .map_err(|e| AegisError::Crypto(format!("Invalid signature format: {}", e)))?;
+        verifying_key
+            .verify_raw(message, &signature)
+            .map_err(|_| AegisError::Crypto("Signature verification failed".to_string()))
+    }
+}
+
+/// Fill `buf` from the operating system RNG.
+#[cfg(feature = "std")]
+pub(crate) fn fill_random(buf: &mut [u8]) -> Result<(), AegisError> {
+    getrandom::getrandom(buf)
+        .map_err(|e| AegisError::Crypto(format!("Random number generator unavailable: {}", e)))
+}
+
+/// Fill `buf` from a caller-supplied RNG.
+pub(crate) fn fill_from_rng<R: CryptoRngCore + ?Sized>(
+    rng: &mut R,
+    buf: &mut [u8],
+) -> Result<(), AegisError> {
+    rng.try_fill_bytes(buf)
+        .map_err(|e| AegisError::Crypto(format!("Random number generator failed: {}", e)))
+}
+
+/// Verify many BIP340 Schnorr signatures at once.
+///
+/// Each item is `(x-only public key, digest, signature)`. Uses the BIP340
+/// batch equation, so one multi-scalar multiplication replaces a
+/// verification per signature. The weights are derived from a hash of the
+/// whole batch, as BIP340 allows, so no RNG is needed and `no_std` builds
+/// batch too. Succeeds only if every signature is valid; it does not report
+/// which one failed.
+pub fn schnorr_verify_batch(items: &[(&[u8], &[u8; 32], &[u8; 64])]) -> Result<(), AegisError> {
+    use k256::elliptic_curve::ops::{LinearCombinationExt, Reduce};
+    use k256::elliptic_curve::point::DecompactPoint;
+    use k256::elliptic_curve::{Group, PrimeField};
+    use k256::{AffinePoint, ProjectivePoint, Scalar, U256};
+
+    let invalid = || AegisError::Crypto("Batch signature verification failed".to_string());
+    let challenge_tag = sha256(b"BIP0340/challenge");
+
+    // Weights seed: every input is fixed before any weight is known.
+    let mut seed = Sha256::new();
+    seed.update(b"aegis-crm/schnorr-batch");
+    for (pubkey, digest, sig) in items {
+        seed.update(pubkey);
+        seed.update(digest);
+        seed.update(sig);
+    }
+    let seed: [u8; 32] = seed.finalize().into();
+
+    let mut terms = Vec::with_capacity(2 * items.len() + 1);
+    let mut s_sum = Scalar::ZERO;
+    for (i, (pubkey, digest, sig)) in items.iter().enumerate() {
+        let pubkey: &[u8; 32] = (*pubkey).try_into().map_err(|_| invalid())?;
+        let p = Option::<AffinePoint>::from(AffinePoint::decompact(pubkey.into()))
+            .ok_or_else(invalid)?;
+        let (r_bytes, s_bytes) = sig.split_at(32);
+        let r_bytes: [u8; 32] = r_bytes.try_into().map_err(|_| invalid())?;
+        let s_bytes: [u8; 32] = s_bytes.try_into().map_err(|_| invalid())?;
+        let r = Option::<AffinePoint>::from(AffinePoint::decompact(&r_bytes.into()))
+            .ok_or_else(invalid)?;
+        let s = Option::<Scalar>::from(Scalar::from_repr(s_bytes.into())).ok_or_else(invalid)?;
+
+        let mut hasher = Sha256::new();
+        hasher.update(challenge_tag);
+        hasher.update(challenge_tag);
+        hasher.update(r_bytes);
+        hasher.update(pubkey);
+        hasher.update(digest);
+        let e = <Scalar as Reduce<U256>>::reduce_bytes(&hasher.finalize());
+
+        // First weight is 1, the rest pseudorandom (BIP340 batch verification).
+        let a = if i == 0 {
+            Scalar::ONE
+        } else {
+            let mut hasher = Sha256::new();
+            hasher.update(seed);
+            hasher.update((i as u64).to_be_bytes());
+            <Scalar as Reduce<U256>>::reduce_bytes(&hasher.finalize())
+        };
+
+        s_sum += a * s;
+        terms.push((ProjectivePoint::from(r), -a));
+        terms.push((ProjectivePoint::from(p), -(a * e)));
+    }
+    terms.push((ProjectivePoint::GENERATOR, s_sum));
+
+    if bool::from(ProjectivePoint::lincomb_ext(terms.as_slice()).is_identity()) {
+        Ok(())
+    } else {
+        Err(invalid())
+    }
+}
+
 /// Compute SHA-256 hash of input bytes.
 pub fn sha256(bytes: &[u8]) -> [u8; 32] {
     let mut hasher = Sha256::new();
@@ -13,52 +405,31 @@ pub fn sha256(bytes: &[u8]) -> [u8; 32] {
 }
 
 /// Sign a digest using ECDSA secp256k1, returning compact 64-byte signature (r||s).
+#[cfg(feature = "pop")]
 pub fn sign_compact(privkey: &[u8; 32], digest: &[u8; 32]) -> Result<[u8; 64], AegisError> {
-    let signing_key = SigningKey::from_bytes(privkey.into())
-        .map_err(|e| AegisError::Crypto(format!("Invalid signing key: {}", e)))?;
-
-    let signature: Signature = signing_key.sign(digest);
-    let sig_bytes = signature.to_bytes();
-
-    Ok(sig_bytes.into())
+    Secp256k1Scheme.sign(privkey, digest)
 }
 
-/// Verify an ECDSA secp256k1 signature.
+/// Verify an ECDSA secp256k1 signature; high-S signatures are rejected.
 pub fn verify_compact(
     pubkey: &[u8; 33],
     digest: &[u8; 32],
     sig: &[u8; 64],
 ) -> Result<(), AegisError> {
-    let verifying_key = VerifyingKey::from_sec1_bytes(pubkey)
-        .map_err(|e| AegisError::Crypto(format!("Invalid public key: {}", e)))?;
-
-    let signature = Signature::from_bytes(sig.into())
-        .map_err(|e| AegisError::Crypto(format!("Invalid signature format: {}", e)))?;
-
-    verifying_key
-        .verify(digest, &signature)
-        .map_err(|_| AegisError::Crypto("Signature verification failed".to_string()))?;
+    Secp256k1Scheme.verify(pubkey, digest, sig)
+}
 
-    Ok(())
+/// Normalize an ECDSA secp256k1 signature to low-S form.
+pub fn normalize_compact(sig: &[u8; 64]) -> Result<[u8; 64], AegisError> {
+    Secp256k1Scheme.normalize(sig)
 }
 
 /// Derive compressed public key from private key.
 pub fn pubkey_from_privkey_inner(privkey: &[u8; 32]) -> Result<[u8; 33], AegisError> {
-    let signing_key = SigningKey::from_bytes(privkey.into())
-        .map_err(|e| AegisError::Key(format!("Invalid private key: {}", e)))?;
-
-    let verifying_key = signing_key.verifying_key();
-    let encoded = EncodedPoint::from(verifying_key);
-    let compressed = encoded.compress();
-
-    let bytes = compressed.as_bytes();
-    if bytes.len() != 33 {
-        return Err(AegisError::Key("Public key compression failed".to_string()));
-    }
-
-    let mut result = [0u8; 33];
-    result.copy_from_slice(bytes);
-    Ok(result)
+    let bytes = Secp256k1Scheme.public_key(privkey)?;
+    bytes
+        .try_into()
+        .map_err(|_| AegisError::Key("Public key compression failed".to_string()))
 }
 
 #[cfg(test)]
@@ -110,4 +481,116 @@ mod tests {
         assert_eq!(pubkey.len(), 33);
         assert!(pubkey[0] == 0x02 || pubkey[0] == 0x03);
     }
+
+    #[test]
+    fn test_all_schemes_roundtrip() {
+        let privkey = [0x42u8; 32];
+        let digest = sha256(b"test message");
+        for alg in SignatureAlgorithm::ALL {
+            let scheme = alg.scheme();
+            assert_eq!(scheme.algorithm(), alg);
+            let pubkey = scheme.public_key(&privkey).unwrap();
+            assert_eq!(pubkey.len(), scheme.public_key_len());
+            let sig = scheme.sign(&privkey, &digest).unwrap();
+            scheme.verify(&pubkey, &digest, &sig).unwrap();
+
+            let mut bad_sig = sig;
+            bad_sig[10] ^= 0x01;
+            assert!(scheme.verify(&pubkey, &digest, &bad_sig).is_err());
+        }
+    }
+
+    #[test]
+    fn test_cross_scheme_rejected() {
+        let privkey = [0x42u8; 32];
+        let digest = sha256(b"test message");
+        let k1_pub = Secp256k1Scheme.public_key(&privkey).unwrap();
+        let p256_sig = P256Scheme.sign(&privkey, &digest).unwrap();
+        assert!(Secp256k1Scheme.verify(&k1_pub, &digest, &p256_sig).is_err());
+        assert!(P256Scheme.verify(&k1_pub, &digest, &p256_sig).is_err());
+    }
+
+    /// Flip `s` to `n - s`, the malleated twin of an ECDSA signature.
+    fn malleate(sig: &[u8; 64], order: &[u8; 32]) -> [u8; 64] {
+        let mut out = *sig;
+        let mut borrow = 0i16;
+        for i in (0..32).rev() {
+            let d = order[i] as i16 - sig[32 + i] as i16 - borrow;
+            out[32 + i] = d.rem_euclid(256) as u8;
+            borrow = (d < 0) as i16;
+        }
+        out
+    }
+
+    #[test]
+    fn test_high_s_rejected_and_normalized() {
+        const K256_ORDER: &str = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+        const P256_ORDER: &str = "ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551";
+        let privkey = [0x42u8; 32];
+        let digest = sha256(b"test message");
+        for (alg, order) in [
+            (SignatureAlgorithm::Secp256k1, K256_ORDER),
+            (SignatureAlgorithm::P256, P256_ORDER),
+        ] {
+            let order: [u8; 32] = hex::decode(order).unwrap().try_into().unwrap();
+            let scheme = alg.scheme();
+            let pubkey = scheme.public_key(&privkey).unwrap();
+            for i in 0..8u8 {
+                let digest = sha256(&[i]);
+                let sig = scheme.sign(&privkey, &digest).unwrap();
+                assert_eq!(scheme.normalize(&sig).unwrap(), sig, "{} signs low-S", alg);
+            }
+            let sig = scheme.sign(&privkey, &digest).unwrap();
+            let high = malleate(&sig, &order);
+            assert!(scheme.verify(&pubkey, &digest, &high).is_err());
+            assert_eq!(scheme.normalize(&high).unwrap(), sig);
+        }
+        let ed_sig = Ed25519Scheme.sign(&privkey, &digest).unwrap();
+        assert_eq!(Ed25519Scheme.normalize(&ed_sig).unwrap(), ed_sig);
+    }
+
+    #[test]
+    fn test_schnorr_batch_verify() {
+        let keys: Vec<[u8; 32]> = (1..=4u8).map(|i| [i; 32]).collect();
+        let pubkeys: Vec<Vec<u8>> = keys
+            .iter()
+            .map(|k| SchnorrScheme.public_key(k).unwrap())
+            .collect();
+        let digests: Vec<[u8; 32]> = (0..4u8).map(|i| sha256(&[i])).collect();
+        let mut sigs: Vec<[u8; 64]> = keys
+            .iter()
+            .zip(&digests)
+            .map(|(k, d)| SchnorrScheme.sign(k, d).unwrap())
+            .collect();
+
+        let items = |sigs: &[[u8; 64]]| -> Result<(), AegisError> {
+            let batch: Vec<(&[u8], &[u8; 32], &[u8; 64])> = pubkeys
+                .iter()
+                .zip(&digests)
+                .zip(sigs)
+                .map(|((p, d), s)| (p.as_slice(), d, s))
+                .collect();
+            schnorr_verify_batch(&batch)
+        };
+        items(&sigs).unwrap();
+
+        sigs[2][40] ^= 0x01;
+        assert!(items(&sigs).is_err());
+        sigs[2][40] ^= 0x01;
+        sigs.swap(0, 1);
+        assert!(items(&sigs).is_err());
+    }
+
+    #[test]
+    fn test_algorithm_names() {
+        for alg in SignatureAlgorithm::ALL {
+            assert_eq!(alg.name().parse::<SignatureAlgorithm>().unwrap(), alg);
+        }
+        assert_eq!(
+            "P256".parse::<SignatureAlgorithm>().unwrap(),
+            SignatureAlgorithm::P256
+        );
+        assert!("rsa".parse::<SignatureAlgorithm>().is_err());
+        assert!(SignatureAlgorithm::Secp256k1.is_default());
+    }
 }
@@ -1,13 +1,23 @@
 //! Error types for Aegis CRM Standard.
+//!
+//! Every variant maps to a stable string code ([`AegisError::code`], the
+//! SPEC §9 names plus extensions) and a stable number
+//! ([`AegisError::numeric_code`]) for SDKs in other languages. Codes are never
+//! reused or renumbered; new variants get new codes.
 
+use crate::prelude::*;
 use thiserror::Error;
 
 /// Standard error codes for Aegis CRM operations.
 #[derive(Error, Debug)]
 pub enum AegisError {
     /// CBOR parsing failed or invalid certificate structure.
-    #[error("Certificate parsing error: invalid CBOR structure")]
-    CertParse,
+    #[error("Certificate parsing error: {}", parse_detail(.field.as_deref(), .reason))]
+    CertParse {
+        /// Wire name of the offending field (e.g. `lid`), when known.
+        field: Option<String>,
+        reason: String,
+    },
 
     /// Protocol version not supported.
     #[error("Unsupported protocol version")]
@@ -21,15 +31,194 @@ pub enum AegisError {
     #[error("Certificate expired")]
     CertExpired,
 
+    /// Certificate is issued later than the verification time.
+    #[error("Certificate not yet valid (issued at {issued_at})")]
+    CertNotYetValid { issued_at: u64 },
+
     /// Proof-of-Possession signature verification failed.
     #[error("Invalid proof-of-possession signature")]
     PopSignature,
 
+    /// Certificate was issued for a different product.
+    #[error("Product mismatch: expected '{expected}', certificate is for '{found}'")]
+    ProductMismatch { expected: String, found: String },
+
+    /// License ID is on the revocation list.
+    #[error("License has been revoked")]
+    Revoked,
+
+    /// Too long since the last online check for the certificate's offline grace period.
+    #[error("Offline grace period of {days} days exceeded; an online check is required")]
+    OfflineGraceExpired { days: u32 },
+
+    /// Clock is earlier than a previously seen verification time.
+    #[error("Clock rollback detected")]
+    ClockRollback,
+
+    /// Payload is malformed or exceeds format limits.
+    #[error("Invalid payload: {0}")]
+    Payload(String),
+
+    /// Payload violates the issuing tier policy.
+    #[error("Policy violation: {0}")]
+    PolicyViolation(String),
+
     /// Key operation failed (invalid format or derivation error).
     #[error("Key operation error: {0}")]
     Key(String),
 
+    /// ASCII armor is malformed or fails its checksum.
+    #[error("Armor error: {0}")]
+    Armor(String),
+
+    /// License key string is malformed or mistyped.
+    #[error("Invalid license key: {0}")]
+    LicenseKey(String),
+
     /// Cryptographic operation failed.
     #[error("Cryptographic operation error: {0}")]
     Crypto(String),
+
+    /// Hybrid certificate checked without a post-quantum vendor key.
+    #[error("Hybrid certificate requires a post-quantum vendor key")]
+    PqKeyRequired,
+
+    /// Current time unavailable or invalid (no clock configured, system clock error).
+    #[error("Clock error: {0}")]
+    Clock(String),
+}
+
+impl AegisError {
+    /// Parse error not tied to a single field.
+    pub fn parse(reason: impl Into<String>) -> Self {
+        AegisError::CertParse {
+            field: None,
+            reason: reason.into(),
+        }
+    }
+
+    /// Parse error in the field with wire name `field`.
+    pub fn parse_field(field: impl Into<String>, reason: impl Into<String>) -> Self {
+        AegisError::CertParse {
+            field: Some(field.into()),
+            reason: reason.into(),
+        }
+    }
+
+    /// Payload error in the field with wire name `field`.
+    pub fn payload_field(field: &str, reason: impl Into<String>) -> Self {
+        AegisError::Payload(parse_detail(Some(field), &reason.into()))
+    }
+
+    /// Stable string code, e.g. `E_CERT_SIG`.
+    pub fn code(&self) -> &'static str {
+        self.codes().0
+    }
+
+    /// Stable numeric code; 0 is reserved for success.
+    pub fn numeric_code(&self) -> u16 {
+        self.codes().1
+    }
+
+    fn codes(&self) -> (&'static str, u16) {
+        match self {
+            AegisError::CertParse { .. } => ("E_CERT_PARSE", 1),
+            AegisError::UnsupportedVersion => ("E_UNSUPPORTED_VER", 2),
+            AegisError::CertSignature => ("E_CERT_SIG", 3),
+            AegisError::CertExpired => ("E_CERT_EXPIRED", 4),
+            AegisError::PopSignature => ("E_POP_SIG", 5),
+            AegisError::ClockRollback => ("E_CLOCK_ROLLBACK", 6),
+            AegisError::Key(_) => ("E_KEY", 7),
+            AegisError::Crypto(_) => ("E_CRYPTO", 8),
+            AegisError::CertNotYetValid { .. } => ("E_CERT_NOT_YET_VALID", 9),
+            AegisError::ProductMismatch { .. } => ("E_PRODUCT_MISMATCH", 10),
+            AegisError::Revoked => ("E_REVOKED", 11),
+            AegisError::PolicyViolation(_) => ("E_POLICY_VIOLATION", 12),
+            AegisError::Payload(_) => ("E_PAYLOAD", 13),
+            AegisError::Armor(_) => ("E_ARMOR", 14),
+            AegisError::LicenseKey(_) => ("E_LICENSE_KEY", 15),
+            AegisError::OfflineGraceExpired { .. } => ("E_OFFLINE_GRACE", 16),
+            AegisError::PqKeyRequired => ("E_PQ_KEY_REQUIRED", 17),
+            AegisError::Clock(_) => ("E_CLOCK", 18),
+        }
+    }
+}
+
+fn parse_detail(field: Option<&str>, reason: &str) -> String {
+    match field {
+        Some(field) => format!("field '{}': {}", field, reason),
+        None => reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_are_stable() {
+        let cases = [
+            (AegisError::parse("x"), "E_CERT_PARSE", 1),
+            (AegisError::UnsupportedVersion, "E_UNSUPPORTED_VER", 2),
+            (AegisError::CertSignature, "E_CERT_SIG", 3),
+            (AegisError::CertExpired, "E_CERT_EXPIRED", 4),
+            (AegisError::PopSignature, "E_POP_SIG", 5),
+            (AegisError::ClockRollback, "E_CLOCK_ROLLBACK", 6),
+            (AegisError::Key(String::new()), "E_KEY", 7),
+            (AegisError::Crypto(String::new()), "E_CRYPTO", 8),
+            (
+                AegisError::CertNotYetValid { issued_at: 0 },
+                "E_CERT_NOT_YET_VALID",
+                9,
+            ),
+            (
+                AegisError::ProductMismatch {
+                    expected: String::new(),
+                    found: String::new(),
+                },
+                "E_PRODUCT_MISMATCH",
+                10,
+            ),
+            (AegisError::Revoked, "E_REVOKED", 11),
+            (
+                AegisError::PolicyViolation(String::new()),
+                "E_POLICY_VIOLATION",
+                12,
+            ),
+            (AegisError::Payload(String::new()), "E_PAYLOAD", 13),
+            (AegisError::Armor(String::new()), "E_ARMOR", 14),
+            (AegisError::LicenseKey(String::new()), "E_LICENSE_KEY", 15),
+            (
+                AegisError::OfflineGraceExpired { days: 0 },
+                "E_OFFLINE_GRACE",
+                16,
+            ),
+            (AegisError::PqKeyRequired, "E_PQ_KEY_REQUIRED", 17),
+            (AegisError::Clock(String::new()), "E_CLOCK", 18),
+        ];
+        for (err, code, number) in cases {
+            assert_eq!(err.code(), code);
+            assert_eq!(err.numeric_code(), number);
+        }
+    }
+
+    #[test]
+    fn test_payload_error_names_field() {
+        assert_eq!(
+            AegisError::payload_field("feat", "empty window").to_string(),
+            "Invalid payload: field 'feat': empty window"
+        );
+    }
+
+    #[test]
+    fn test_parse_error_names_field() {
+        assert_eq!(
+            AegisError::parse_field("lid", "expected 32 bytes").to_string(),
+            "Certificate parsing error: field 'lid': expected 32 bytes"
+        );
+        assert_eq!(
+            AegisError::parse("not CBOR").to_string(),
+            "Certificate parsing error: not CBOR"
+        );
+    }
 }
@@ -0,0 +1,204 @@
+//! Hierarchical feature names, pattern grants and implication rules.
+//!
+//! Feature names are dot-separated namespaces (`export.pdf`). A grant ending in
+//! `.*` covers every feature below that namespace, and the literal `ALL` covers
+//! everything. Implication rules (`enterprise` implies `sso`, `audit_log`) live in
+//! a [`FeatureCatalog`] embedded in the application and are resolved at verify
+//! time, so certificates only carry the top-level grants.
+
+use crate::cert::FeatureGrant;
+use crate::prelude::*;
+use alloc::collections::{BTreeMap, BTreeSet};
+use serde::{Deserialize, Serialize};
+
+/// Wildcard grant covering every feature.
+pub const ALL_FEATURES: &str = "ALL";
+
+/// Check whether a granted feature pattern covers `feature`.
+pub fn pattern_matches(pattern: &str, feature: &str) -> bool {
+    if pattern == ALL_FEATURES || pattern == feature {
+        return true;
+    }
+    match pattern.strip_suffix(".*") {
+        Some(prefix) => feature
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('.') && rest.len() > 1),
+        None => false,
+    }
+}
+
+/// Check that a feature name or pattern is well-formed.
+///
+/// Segments are non-empty and contain only ASCII alphanumerics, `_` or `-`;
+/// `*` is only allowed as the final segment.
+pub fn is_valid_pattern(pattern: &str) -> bool {
+    if pattern == ALL_FEATURES {
+        return true;
+    }
+    let segments: Vec<&str> = pattern.split('.').collect();
+    let last = segments.len() - 1;
+    segments.iter().enumerate().all(|(i, seg)| {
+        (i == last && i > 0 && *seg == "*")
+            || (!seg.is_empty()
+                && seg
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+    })
+}
+
+/// Vendor-defined implication graph between features.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FeatureCatalog {
+    #[serde(default)]
+    pub implies: BTreeMap<String, Vec<String>>,
+    /// Feature names in license-key bitmask order (bit 0 first, up to 64).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bits: Vec<String>,
+}
+
+impl FeatureCatalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare that holding `feature` also grants each of `implied`.
+    pub fn with_rule<I, S>(mut self, feature: &str, implied: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.implies
+            .entry(feature.to_string())
+            .or_default()
+            .extend(implied.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set the license-key bitmask table (see [`crate::cert::decode_cert_key`]).
+    pub fn with_bits<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.bits = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Expand the grants active at `now_unix` through the implication graph.
+    pub fn resolve(&self, grants: &[FeatureGrant], now_unix: u64) -> FeatureSet {
+        let mut set = FeatureSet {
+            patterns: grants
+                .iter()
+                .filter(|g| g.is_active_at(now_unix))
+                .map(|g| g.name.clone())
+                .collect(),
+        };
+
+        // Iterate to a fixpoint; the set only grows and is bounded by the catalog.
+        loop {
+            let mut added = false;
+            for (feature, implied) in &self.implies {
+                if set.contains(feature) {
+                    for target in implied {
+                        added |= set.patterns.insert(target.clone());
+                    }
+                }
+            }
+            if !added {
+                return set;
+            }
+        }
+    }
+}
+
+/// Effective feature patterns after catalog resolution.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeatureSet {
+    patterns: BTreeSet<String>,
+}
+
+impl FeatureSet {
+    /// Check whether any resolved pattern covers `feature`.
+    pub fn contains(&self, feature: &str) -> bool {
+        self.patterns.iter().any(|p| pattern_matches(p, feature))
+    }
+
+    /// Resolved patterns in sorted order.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.patterns.iter().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_matches() {
+        assert!(pattern_matches("export.pdf", "export.pdf"));
+        assert!(pattern_matches("export.*", "export.pdf"));
+        assert!(pattern_matches("export.*", "export.image.png"));
+        assert!(!pattern_matches("export.*", "export"));
+        assert!(!pattern_matches("export.*", "exporter.pdf"));
+        assert!(!pattern_matches("export.pdf", "export.csv"));
+        assert!(pattern_matches("ALL", "anything.at.all"));
+    }
+
+    #[test]
+    fn test_valid_patterns() {
+        assert!(is_valid_pattern("base_access"));
+        assert!(is_valid_pattern("export.pdf"));
+        assert!(is_valid_pattern("export.*"));
+        assert!(is_valid_pattern("ALL"));
+        assert!(!is_valid_pattern("*"));
+        assert!(!is_valid_pattern("export..pdf"));
+        assert!(!is_valid_pattern("export.*.pdf"));
+        assert!(!is_valid_pattern("export pdf"));
+        assert!(!is_valid_pattern(""));
+    }
+
+    #[test]
+    fn test_resolve_implications() {
+        let catalog = FeatureCatalog::new()
+            .with_rule("enterprise", ["sso", "audit_log", "pro"])
+            .with_rule("pro", ["export.*"]);
+        let set = catalog.resolve(&["enterprise".into()], 0);
+        assert!(set.contains("enterprise"));
+        assert!(set.contains("sso"));
+        assert!(set.contains("audit_log"));
+        assert!(set.contains("export.csv"));
+        assert!(!set.contains("beta"));
+    }
+
+    #[test]
+    fn test_resolve_cycle_terminates() {
+        let catalog = FeatureCatalog::new()
+            .with_rule("a", ["b"])
+            .with_rule("b", ["a", "c"]);
+        let set = catalog.resolve(&["a".into()], 0);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_resolve_skips_inactive_grants() {
+        let catalog = FeatureCatalog::new().with_rule("enterprise", ["sso"]);
+        let grants = vec![FeatureGrant::new("enterprise").with_window(None, Some(100))];
+        assert!(catalog.resolve(&grants, 50).contains("sso"));
+        assert!(!catalog.resolve(&grants, 150).contains("sso"));
+    }
+
+    #[test]
+    fn test_pattern_source_triggers_rule() {
+        let catalog = FeatureCatalog::new().with_rule("export.pdf", ["print"]);
+        let set = catalog.resolve(&["export.*".into()], 0);
+        assert!(set.contains("print"));
+    }
+}
@@ -1,8 +1,118 @@
 //! Key generation and management for Aegis CRM.
 
-use crate::crypto::pubkey_from_privkey_inner;
+#[cfg(feature = "keygen")]
+use crate::crypto::fill_from_rng;
+#[cfg(all(feature = "std", feature = "keygen"))]
+use crate::crypto::fill_random;
+use crate::crypto::{pubkey_from_privkey_inner, SignatureAlgorithm};
 use crate::errors::AegisError;
-use getrandom::getrandom;
+use crate::prelude::*;
+#[cfg(feature = "keygen")]
+use rand_core::CryptoRngCore;
+
+/// Algorithm-tagged private key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrivateKey {
+    pub alg: SignatureAlgorithm,
+    pub bytes: [u8; 32],
+}
+
+/// Algorithm-tagged public key (33 bytes compressed SEC1 for ECDSA curves,
+/// 32 for Ed25519 and x-only Schnorr).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicKey {
+    pub alg: SignatureAlgorithm,
+    pub bytes: Vec<u8>,
+}
+
+/// Algorithm-tagged keypair.
+#[derive(Debug, Clone)]
+pub struct Keypair {
+    pub privkey: PrivateKey,
+    pub pubkey: PublicKey,
+}
+
+impl PrivateKey {
+    pub fn new(alg: SignatureAlgorithm, bytes: [u8; 32]) -> Self {
+        Self { alg, bytes }
+    }
+
+    /// v1.0 secp256k1 private key
+    pub fn secp256k1(bytes: [u8; 32]) -> Self {
+        Self::new(SignatureAlgorithm::Secp256k1, bytes)
+    }
+
+    /// Derive the matching public key.
+    pub fn public_key(&self) -> Result<PublicKey, AegisError> {
+        Ok(PublicKey {
+            alg: self.alg,
+            bytes: self.alg.scheme().public_key(&self.bytes)?,
+        })
+    }
+}
+
+impl PublicKey {
+    /// Build a public key, checking its length for `alg`.
+    pub fn new(alg: SignatureAlgorithm, bytes: &[u8]) -> Result<Self, AegisError> {
+        let expected = alg.scheme().public_key_len();
+        if bytes.len() != expected {
+            return Err(AegisError::Key(format!(
+                "{} public key must be {} bytes, got {}",
+                alg,
+                expected,
+                bytes.len()
+            )));
+        }
+        Ok(Self {
+            alg,
+            bytes: bytes.to_vec(),
+        })
+    }
+
+    /// v1.0 secp256k1 public key
+    pub fn secp256k1(bytes: &[u8; 33]) -> Self {
+        Self {
+            alg: SignatureAlgorithm::Secp256k1,
+            bytes: bytes.to_vec(),
+        }
+    }
+}
+
+/// Generate a new keypair for `alg` using CSPRNG.
+///
+/// Panics if the OS random number generator fails; see [`try_keygen`].
+#[cfg(all(feature = "std", feature = "keygen"))]
+#[deprecated(note = "panics if the OS RNG fails; use `try_keygen`")]
+pub fn keygen(alg: SignatureAlgorithm) -> Keypair {
+    try_keygen(alg).expect("Failed to generate keypair")
+}
+
+/// Generate a new keypair for `alg` from the OS random number generator.
+#[cfg(all(feature = "std", feature = "keygen"))]
+pub fn try_keygen(alg: SignatureAlgorithm) -> Result<Keypair, AegisError> {
+    let mut bytes = [0u8; 32];
+    fill_random(&mut bytes)?;
+    keypair_from_bytes(alg, bytes)
+}
+
+/// Generate a new keypair for `alg` from a caller-supplied CSPRNG, e.g. a
+/// hardware RNG on targets without an operating system.
+#[cfg(feature = "keygen")]
+pub fn keygen_with_rng<R: CryptoRngCore + ?Sized>(
+    alg: SignatureAlgorithm,
+    rng: &mut R,
+) -> Result<Keypair, AegisError> {
+    let mut bytes = [0u8; 32];
+    fill_from_rng(rng, &mut bytes)?;
+    keypair_from_bytes(alg, bytes)
+}
+
+#[cfg(feature = "keygen")]
+fn keypair_from_bytes(alg: SignatureAlgorithm, bytes: [u8; 32]) -> Result<Keypair, AegisError> {
+    let privkey = PrivateKey::new(alg, bytes);
+    let pubkey = privkey.public_key()?;
+    Ok(Keypair { privkey, pubkey })
+}
 
 /// Vendor keypair (private + public key).
 #[derive(Debug, Clone)]
@@ -19,19 +129,42 @@ pub struct UserKeypair {
 }
 
 /// Generate a new vendor keypair using CSPRNG.
+///
+/// Panics if the OS random number generator fails; see [`try_vendor_keygen`].
+#[cfg(all(feature = "std", feature = "keygen"))]
+#[deprecated(note = "panics if the OS RNG fails; use `try_vendor_keygen`")]
 pub fn vendor_keygen() -> VendorKeypair {
-    let mut privkey = [0u8; 32];
-    getrandom(&mut privkey).expect("Failed to generate random bytes");
-    let pubkey = pubkey_from_privkey_inner(&privkey).expect("Failed to derive public key");
-    VendorKeypair { privkey, pubkey }
+    try_vendor_keygen().expect("Failed to generate vendor keypair")
+}
+
+/// Generate a new secp256k1 vendor keypair from the OS random number generator.
+#[cfg(all(feature = "std", feature = "keygen"))]
+pub fn try_vendor_keygen() -> Result<VendorKeypair, AegisError> {
+    let (privkey, pubkey) = secp256k1_keygen()?;
+    Ok(VendorKeypair { privkey, pubkey })
 }
 
 /// Generate a new user keypair using CSPRNG.
+///
+/// Panics if the OS random number generator fails; see [`try_user_keygen`].
+#[cfg(all(feature = "std", feature = "keygen"))]
+#[deprecated(note = "panics if the OS RNG fails; use `try_user_keygen`")]
 pub fn user_keygen() -> UserKeypair {
+    try_user_keygen().expect("Failed to generate user keypair")
+}
+
+/// Generate a new secp256k1 user keypair from the OS random number generator.
+#[cfg(all(feature = "std", feature = "keygen"))]
+pub fn try_user_keygen() -> Result<UserKeypair, AegisError> {
+    let (privkey, pubkey) = secp256k1_keygen()?;
+    Ok(UserKeypair { privkey, pubkey })
+}
+
+#[cfg(all(feature = "std", feature = "keygen"))]
+fn secp256k1_keygen() -> Result<([u8; 32], [u8; 33]), AegisError> {
     let mut privkey = [0u8; 32];
-    getrandom(&mut privkey).expect("Failed to generate random bytes");
-    let pubkey = pubkey_from_privkey_inner(&privkey).expect("Failed to derive public key");
-    UserKeypair { privkey, pubkey }
+    fill_random(&mut privkey)?;
+    Ok((privkey, pubkey_from_privkey_inner(&privkey)?))
 }
 
 /// Derive public key from private key.
@@ -45,7 +178,7 @@ mod tests {
 
     #[test]
     fn test_vendor_keygen() {
-        let vendor = vendor_keygen();
+        let vendor = try_vendor_keygen().unwrap();
         assert_eq!(vendor.privkey.len(), 32);
         assert_eq!(vendor.pubkey.len(), 33);
         assert!(vendor.pubkey[0] == 0x02 || vendor.pubkey[0] == 0x03);
@@ -53,22 +186,87 @@ mod tests {
 
     #[test]
     fn test_user_keygen() {
-        let user = user_keygen();
+        let user = try_user_keygen().unwrap();
         assert_eq!(user.privkey.len(), 32);
         assert_eq!(user.pubkey.len(), 33);
     }
 
     #[test]
     fn test_pubkey_from_privkey() {
-        let user = user_keygen();
+        let user = try_user_keygen().unwrap();
         let derived = pubkey_from_privkey(&user.privkey).unwrap();
         assert_eq!(derived, user.pubkey);
     }
 
     #[test]
     fn test_keygen_randomness() {
-        let v1 = vendor_keygen();
-        let v2 = vendor_keygen();
+        let v1 = try_vendor_keygen().unwrap();
+        let v2 = try_vendor_keygen().unwrap();
         assert_ne!(v1.privkey, v2.privkey);
     }
+
+    #[test]
+    fn test_keygen_all_algorithms() {
+        for alg in SignatureAlgorithm::ALL {
+            let kp = try_keygen(alg).unwrap();
+            assert_eq!(kp.privkey.alg, alg);
+            assert_eq!(kp.pubkey.alg, alg);
+            assert_eq!(kp.pubkey.bytes.len(), alg.scheme().public_key_len());
+            assert_eq!(kp.privkey.public_key().unwrap(), kp.pubkey);
+        }
+    }
+
+    #[test]
+    fn test_public_key_length_checked() {
+        assert!(PublicKey::new(SignatureAlgorithm::Ed25519, &[0u8; 33]).is_err());
+        assert!(PublicKey::new(SignatureAlgorithm::P256, &[0x02; 33]).is_ok());
+    }
+
+    /// Stand-in for a hardware RNG: fixed bytes, or a failure.
+    struct TestRng(Option<u8>);
+
+    impl rand_core::RngCore for TestRng {
+        fn next_u32(&mut self) -> u32 {
+            rand_core::impls::next_u32_via_fill(self)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            rand_core::impls::next_u64_via_fill(self)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            self.try_fill_bytes(dest).unwrap()
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            let byte = self.0.ok_or_else(|| {
+                rand_core::Error::from(
+                    core::num::NonZeroU32::new(rand_core::Error::CUSTOM_START).unwrap(),
+                )
+            })?;
+            dest.fill(byte);
+            Ok(())
+        }
+    }
+
+    impl rand_core::CryptoRng for TestRng {}
+
+    #[test]
+    fn test_keygen_with_rng() {
+        let kp = keygen_with_rng(SignatureAlgorithm::Ed25519, &mut TestRng(Some(7))).unwrap();
+        assert_eq!(kp.privkey.bytes, [7u8; 32]);
+        assert_eq!(kp.privkey.public_key().unwrap(), kp.pubkey);
+        assert_eq!(
+            crate::pop::challenge_with_rng(&mut TestRng(Some(9))).unwrap(),
+            [9u8; 32]
+        );
+
+        // A failing RNG is an error, not a panic
+        assert!(matches!(
+            keygen_with_rng(SignatureAlgorithm::Secp256k1, &mut TestRng(None)),
+            Err(AegisError::Crypto(_))
+        ));
+        assert!(crate::pop::challenge_with_rng(&mut TestRng(None)).is_err());
+        assert!(try_keygen(SignatureAlgorithm::P256).is_ok());
+    }
 }
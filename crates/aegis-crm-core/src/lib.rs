@@ -1,10 +1,40 @@
 //! Aegis CRM Standard v1.0 - Cryptographic Rights Management
+//!
+//! The default `std` feature adds the system clock and OS randomness. Without
+//! it the crate is `no_std` + `alloc`, for verifiers on embedded targets:
+//! certificates decode and verify with a caller-supplied [`clock::Clock`], and
+//! keys and challenges come from a caller-supplied RNG.
+//!
+//! Only verification (`verify`) is on by default. Apps that prove possession
+//! of the user key enable `pop`. Issuers enable `issue` (signing, policies,
+//! COSE/JWS encoding; implies `pop`), `keygen` and `cli-helpers` (armor,
+//! license URIs and license keys).
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod armor;
 pub mod cert;
+pub mod clock;
 pub mod crypto;
 pub mod errors;
+pub mod features;
 pub mod keys;
+#[cfg(feature = "issue")]
+pub mod policy;
 pub mod pop;
+pub mod pq;
+pub mod uri;
+#[cfg(feature = "verify")]
 pub mod verify;
 
 pub use errors::AegisError;
+
+/// `alloc` counterparts of the std prelude, for `no_std` builds.
+mod prelude {
+    pub(crate) use alloc::boxed::Box;
+    pub(crate) use alloc::string::{String, ToString};
+    pub(crate) use alloc::vec::Vec;
+    pub(crate) use alloc::{format, vec};
+}
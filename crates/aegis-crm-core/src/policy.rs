@@ -0,0 +1,311 @@
+//! Tier policies enforced at issuance.
+//!
+//! A [`PolicySet`] maps tier names to a [`TierPolicy`] declaring required
+//! fields, maximum duration, allowed features and default limits. Vendors
+//! usually load it from a TOML file (see `aegis issue --policy`); the built-in
+//! set reproduces the v1.0 rules (`campus` must expire).
+
+use crate::cert::{LicensePayload, Limits};
+use crate::errors::AegisError;
+use crate::features::pattern_matches;
+use crate::prelude::*;
+use alloc::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Rules for a single license tier.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TierPolicy {
+    /// Payload fields that must be present, using the JSON payload names
+    /// (`expires_at`, `limits.seat_max`, `metadata.<key>`, `entitlements.<name>`, ...).
+    #[serde(default)]
+    pub required: Vec<String>,
+    /// Maximum lifetime from issuance; implies the tier cannot be perpetual.
+    #[serde(default)]
+    pub max_duration_days: Option<u64>,
+    /// Feature patterns this tier may grant; `None` allows any feature.
+    #[serde(default)]
+    pub allowed_features: Option<Vec<String>>,
+    /// Limits filled in when the payload leaves them unset.
+    #[serde(default)]
+    pub default_limits: Option<PolicyLimits>,
+}
+
+/// Default limits of a tier, named as in the JSON payload.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyLimits {
+    #[serde(default)]
+    pub seat_max: Option<u32>,
+    #[serde(default)]
+    pub offline_grace_days: Option<u32>,
+}
+
+/// Tier policies keyed by tier name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PolicySet {
+    /// Accept tiers that have no entry in `tiers`.
+    #[serde(default)]
+    pub allow_unknown_tiers: bool,
+    #[serde(default)]
+    pub tiers: BTreeMap<String, TierPolicy>,
+}
+
+impl PolicySet {
+    /// Built-in v1.0 rules: `lifetime_pro` is unrestricted, `campus` requires an expiry.
+    pub fn builtin() -> Self {
+        let mut tiers = BTreeMap::new();
+        tiers.insert("lifetime_pro".to_string(), TierPolicy::default());
+        tiers.insert(
+            "campus".to_string(),
+            TierPolicy {
+                required: vec!["expires_at".to_string()],
+                ..TierPolicy::default()
+            },
+        );
+        Self {
+            allow_unknown_tiers: true,
+            tiers,
+        }
+    }
+
+    /// Fill policy defaults into `payload`, then check it against its tier's rules.
+    pub fn apply(&self, payload: &mut LicensePayload) -> Result<(), AegisError> {
+        let Some(policy) = self.tiers.get(&payload.tier) else {
+            if self.allow_unknown_tiers {
+                return Ok(());
+            }
+            return Err(AegisError::PolicyViolation(format!(
+                "unknown tier '{}'",
+                payload.tier
+            )));
+        };
+        policy.apply_defaults(payload);
+        policy.check(&payload.tier, payload)
+    }
+}
+
+impl TierPolicy {
+    /// Fill unset limit fields from `default_limits`.
+    pub fn apply_defaults(&self, payload: &mut LicensePayload) {
+        let Some(defaults) = &self.default_limits else {
+            return;
+        };
+        let limits = payload.limits.get_or_insert(Limits {
+            seat_max: None,
+            offline_grace_days: None,
+        });
+        limits.seat_max = limits.seat_max.or(defaults.seat_max);
+        limits.offline_grace_days = limits.offline_grace_days.or(defaults.offline_grace_days);
+    }
+
+    /// Check `payload` against this policy without modifying it.
+    pub fn check(&self, tier: &str, payload: &LicensePayload) -> Result<(), AegisError> {
+        let violation =
+            |msg: String| AegisError::PolicyViolation(format!("tier '{}' {}", tier, msg));
+
+        for field in &self.required {
+            if !field_present(payload, field)
+                .ok_or_else(|| violation(format!("requires unknown field '{}'", field)))?
+            {
+                return Err(violation(format!("requires '{}'", field)));
+            }
+        }
+
+        if let Some(days) = self.max_duration_days {
+            let Some(exp) = payload.expiry else {
+                return Err(violation(format!(
+                    "cannot be perpetual (max duration {} days)",
+                    days
+                )));
+            };
+            let duration = exp.saturating_sub(payload.issued_at);
+            if duration > days.saturating_mul(SECONDS_PER_DAY) {
+                return Err(violation(format!(
+                    "allows at most {} days, got {} days",
+                    days,
+                    duration / SECONDS_PER_DAY + u64::from(duration % SECONDS_PER_DAY != 0)
+                )));
+            }
+        }
+
+        if let Some(allowed) = &self.allowed_features {
+            for feature in &payload.features {
+                if !allowed.iter().any(|a| pattern_matches(a, &feature.name)) {
+                    return Err(violation(format!(
+                        "does not allow feature '{}'",
+                        feature.name
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether a named payload field is set, or `None` if the name is unknown.
+fn field_present(payload: &LicensePayload, field: &str) -> Option<bool> {
+    let limits = payload.limits.as_ref();
+    let meta = payload.metadata.as_ref();
+    let present = match field {
+        "expires_at" => payload.expiry.is_some(),
+        "features" => !payload.features.is_empty(),
+        "limits.seat_max" => limits.is_some_and(|l| l.seat_max.is_some()),
+        "limits.offline_grace_days" => limits.is_some_and(|l| l.offline_grace_days.is_some()),
+        _ => {
+            if let Some(key) = field.strip_prefix("metadata.") {
+                meta.is_some_and(|m| m.contains_key(key))
+            } else if let Some(name) = field.strip_prefix("entitlements.") {
+                payload.entitlements.contains_key(name)
+            } else {
+                return None;
+            }
+        }
+    };
+    Some(present)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cert::PROTOCOL_VERSION;
+    use crate::crypto::SignatureAlgorithm;
+
+    fn payload(tier: &str, expiry: Option<u64>) -> LicensePayload {
+        LicensePayload {
+            version: PROTOCOL_VERSION,
+            alg: SignatureAlgorithm::Secp256k1,
+            pq_alg: None,
+            product_id: "test".to_string(),
+            license_id: [0u8; 32],
+            issued_at: 1700000000,
+            expiry,
+            user_pubkey: vec![0x02; 33],
+            user_alg: SignatureAlgorithm::Secp256k1,
+            tier: tier.to_string(),
+            features: vec!["base".into()],
+            limits: None,
+            entitlements: BTreeMap::new(),
+            metadata: None,
+        }
+    }
+
+    fn trial_policy() -> PolicySet {
+        let mut tiers = BTreeMap::new();
+        tiers.insert(
+            "trial".to_string(),
+            TierPolicy {
+                required: vec!["expires_at".to_string()],
+                max_duration_days: Some(30),
+                allowed_features: Some(vec!["base".to_string(), "export.*".to_string()]),
+                default_limits: Some(PolicyLimits {
+                    seat_max: Some(1),
+                    offline_grace_days: Some(3),
+                }),
+            },
+        );
+        PolicySet {
+            allow_unknown_tiers: false,
+            tiers,
+        }
+    }
+
+    #[test]
+    fn test_builtin_campus_requires_expiry() {
+        let policy = PolicySet::builtin();
+        assert!(matches!(
+            policy.apply(&mut payload("campus", None)),
+            Err(AegisError::PolicyViolation(_))
+        ));
+        assert!(policy
+            .apply(&mut payload("campus", Some(1800000000)))
+            .is_ok());
+        assert!(policy.apply(&mut payload("lifetime_pro", None)).is_ok());
+        assert!(policy.apply(&mut payload("custom", None)).is_ok());
+    }
+
+    #[test]
+    fn test_unknown_tier_rejected() {
+        let err = trial_policy()
+            .apply(&mut payload("gold", None))
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown tier 'gold'"));
+    }
+
+    #[test]
+    fn test_max_duration() {
+        let policy = trial_policy();
+        assert!(policy
+            .apply(&mut payload("trial", Some(1700000000 + 30 * 86400)))
+            .is_ok());
+        let err = policy
+            .apply(&mut payload("trial", Some(1700000000 + 31 * 86400)))
+            .unwrap_err();
+        assert!(err.to_string().contains("at most 30 days, got 31 days"));
+        // Partial days round up
+        let err = policy
+            .apply(&mut payload("trial", Some(1700000000 + 30 * 86400 + 1)))
+            .unwrap_err();
+        assert!(err.to_string().contains("got 31 days"));
+    }
+
+    #[test]
+    fn test_allowed_features() {
+        let policy = trial_policy();
+        let mut p = payload("trial", Some(1700086400));
+        p.features = vec!["base".into(), "export.pdf".into()];
+        assert!(policy.apply(&mut p).is_ok());
+        p.features.push("sso".into());
+        let err = policy.apply(&mut p).unwrap_err();
+        assert!(err.to_string().contains("does not allow feature 'sso'"));
+    }
+
+    #[test]
+    fn test_default_limits_fill_unset_fields() {
+        let policy = trial_policy();
+        let mut p = payload("trial", Some(1700086400));
+        p.limits = Some(Limits {
+            seat_max: Some(5),
+            offline_grace_days: None,
+        });
+        policy.apply(&mut p).unwrap();
+        assert_eq!(
+            p.limits,
+            Some(Limits {
+                seat_max: Some(5),
+                offline_grace_days: Some(3),
+            })
+        );
+    }
+
+    #[test]
+    fn test_policy_limits_use_payload_names() {
+        let json = r#"{"seat_max": 5, "offline_grace_days": 7}"#;
+        let limits: PolicyLimits = serde_json::from_str(json).unwrap();
+        assert_eq!(limits.seat_max, Some(5));
+        assert_eq!(limits.offline_grace_days, Some(7));
+        // The signed wire struct only knows its own field names
+        let wire: Limits = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            wire,
+            Limits {
+                seat_max: None,
+                offline_grace_days: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_unknown_required_field() {
+        let policy = TierPolicy {
+            required: vec!["seat_count".to_string()],
+            ..TierPolicy::default()
+        };
+        let err = policy.check("x", &payload("x", None)).unwrap_err();
+        assert!(err.to_string().contains("unknown field 'seat_count'"));
+    }
+}
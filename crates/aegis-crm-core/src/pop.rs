@@ -1,18 +1,44 @@
 //! Proof-of-Possession (PoP) for license ownership.
 
-use crate::crypto::{sha256, sign_compact, verify_compact};
+#[cfg(feature = "std")]
+use crate::crypto::fill_random;
+#[cfg(feature = "pop")]
+use crate::crypto::sign_compact;
+use crate::crypto::{fill_from_rng, sha256, verify_compact};
 use crate::errors::AegisError;
-use getrandom::getrandom;
+#[cfg(feature = "pop")]
+use crate::keys::PrivateKey;
+use crate::keys::PublicKey;
+use rand_core::CryptoRngCore;
 
 pub type Nonce32 = [u8; 32];
 pub type PopSignature = [u8; 64];
 
+/// Random challenge nonce.
+///
+/// Panics if the OS random number generator fails; see [`try_challenge`].
+#[cfg(feature = "std")]
+#[deprecated(note = "panics if the OS RNG fails; use `try_challenge`")]
 pub fn challenge() -> Nonce32 {
+    try_challenge().expect("Failed to generate nonce")
+}
+
+/// Random challenge nonce from the OS random number generator.
+#[cfg(feature = "std")]
+pub fn try_challenge() -> Result<Nonce32, AegisError> {
+    let mut nonce = [0u8; 32];
+    fill_random(&mut nonce)?;
+    Ok(nonce)
+}
+
+/// Random challenge nonce from a caller-supplied CSPRNG.
+pub fn challenge_with_rng<R: CryptoRngCore + ?Sized>(rng: &mut R) -> Result<Nonce32, AegisError> {
     let mut nonce = [0u8; 32];
-    getrandom(&mut nonce).expect("Failed to generate nonce");
-    nonce
+    fill_from_rng(rng, &mut nonce)?;
+    Ok(nonce)
 }
 
+#[cfg(feature = "pop")]
 pub fn prove(user_privkey: &[u8; 32], nonce: &Nonce32) -> Result<PopSignature, AegisError> {
     let digest = sha256(nonce);
     sign_compact(user_privkey, &digest)
@@ -27,31 +53,52 @@ pub fn verify(
     verify_compact(user_pubkey, &digest, sig).map_err(|_| AegisError::PopSignature)
 }
 
+/// Prove possession of an algorithm-tagged user key.
+#[cfg(feature = "pop")]
+pub fn prove_with_key(user_key: &PrivateKey, nonce: &Nonce32) -> Result<PopSignature, AegisError> {
+    let digest = sha256(nonce);
+    user_key.alg.scheme().sign(&user_key.bytes, &digest)
+}
+
+/// Verify a PoP signature against an algorithm-tagged user key.
+pub fn verify_with_key(
+    user_key: &PublicKey,
+    nonce: &Nonce32,
+    sig: &PopSignature,
+) -> Result<(), AegisError> {
+    let digest = sha256(nonce);
+    user_key
+        .alg
+        .scheme()
+        .verify(&user_key.bytes, &digest, sig)
+        .map_err(|_| AegisError::PopSignature)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::keys::user_keygen;
+    use crate::keys::try_user_keygen;
 
     #[test]
     fn test_challenge() {
-        let n1 = challenge();
-        let n2 = challenge();
+        let n1 = try_challenge().unwrap();
+        let n2 = try_challenge().unwrap();
         assert_eq!(n1.len(), 32);
         assert_ne!(n1, n2);
     }
 
     #[test]
     fn test_pop_flow() {
-        let user = user_keygen();
-        let nonce = challenge();
+        let user = try_user_keygen().unwrap();
+        let nonce = try_challenge().unwrap();
         let sig = prove(&user.privkey, &nonce).unwrap();
         verify(&user.pubkey, &nonce, &sig).unwrap();
     }
 
     #[test]
     fn test_pop_invalid() {
-        let user = user_keygen();
-        let nonce = challenge();
+        let user = try_user_keygen().unwrap();
+        let nonce = try_challenge().unwrap();
         let mut sig = prove(&user.privkey, &nonce).unwrap();
         sig[0] ^= 0xFF;
         assert!(matches!(
@@ -62,10 +109,24 @@ mod tests {
 
     #[test]
     fn test_pop_wrong_nonce() {
-        let user = user_keygen();
-        let n1 = challenge();
-        let n2 = challenge();
+        let user = try_user_keygen().unwrap();
+        let n1 = try_challenge().unwrap();
+        let n2 = try_challenge().unwrap();
         let sig = prove(&user.privkey, &n1).unwrap();
         assert!(verify(&user.pubkey, &n2, &sig).is_err());
     }
+
+    #[test]
+    fn test_pop_all_algorithms() {
+        for alg in crate::crypto::SignatureAlgorithm::ALL {
+            let user = crate::keys::try_keygen(alg).unwrap();
+            let nonce = try_challenge().unwrap();
+            let sig = prove_with_key(&user.privkey, &nonce).unwrap();
+            verify_with_key(&user.pubkey, &nonce, &sig).unwrap();
+            assert!(matches!(
+                verify_with_key(&user.pubkey, &try_challenge().unwrap(), &sig),
+                Err(AegisError::PopSignature)
+            ));
+        }
+    }
 }
@@ -0,0 +1,201 @@
+//! Post-quantum vendor signatures for hybrid certificates.
+//!
+//! A hybrid certificate carries an ML-DSA (FIPS 204) signature next to the
+//! classical vendor signature. The payload names the PQ algorithm, so the
+//! extra signature cannot be stripped without invalidating the classical one.
+//! Signing and verification need the `pq` cargo feature; every build can
+//! decode hybrid certificates.
+
+use crate::errors::AegisError;
+use crate::prelude::*;
+use core::fmt;
+use core::str::FromStr;
+use serde::{Deserialize, Serialize};
+
+/// Post-quantum signature algorithm of a hybrid certificate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PqAlgorithm {
+    /// ML-DSA-65 (FIPS 204, NIST category 3)
+    #[serde(rename = "mldsa65")]
+    MlDsa65,
+}
+
+impl PqAlgorithm {
+    pub const ALL: [PqAlgorithm; 1] = [PqAlgorithm::MlDsa65];
+
+    /// Lowercase name used in certificates and key files
+    pub fn name(self) -> &'static str {
+        match self {
+            PqAlgorithm::MlDsa65 => "mldsa65",
+        }
+    }
+}
+
+impl fmt::Display for PqAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for PqAlgorithm {
+    type Err = AegisError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PqAlgorithm::ALL
+            .into_iter()
+            .find(|alg| alg.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| AegisError::Crypto(format!("Unknown PQ signature algorithm: {}", s)))
+    }
+}
+
+#[cfg(feature = "pq")]
+pub use self::mldsa::*;
+
+#[cfg(feature = "pq")]
+mod mldsa {
+    use super::PqAlgorithm;
+    #[cfg(any(feature = "issue", feature = "keygen"))]
+    use crate::crypto::fill_random;
+    use crate::errors::AegisError;
+    use mysten_mldsa_native_rs as mldsa65;
+
+    /// ML-DSA private key seed length (FIPS 204 `xi`).
+    pub const PQ_SEED_LEN: usize = mldsa65::SEED_LENGTH;
+
+    /// FIPS 204 context string binding signatures to Aegis certificates.
+    const CONTEXT: &[u8] = b"aegis-crm/v1/cert";
+
+    /// Algorithm-tagged PQ private key, stored as its 32-byte seed.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct PqPrivateKey {
+        pub alg: PqAlgorithm,
+        pub seed: [u8; PQ_SEED_LEN],
+    }
+
+    /// Algorithm-tagged PQ public key (1952 bytes for ML-DSA-65).
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct PqPublicKey {
+        pub alg: PqAlgorithm,
+        pub bytes: Vec<u8>,
+    }
+
+    /// PQ keypair.
+    #[derive(Debug, Clone)]
+    pub struct PqKeypair {
+        pub privkey: PqPrivateKey,
+        pub pubkey: PqPublicKey,
+    }
+
+    impl PqAlgorithm {
+        /// Encoded public key length
+        pub fn public_key_len(self) -> usize {
+            match self {
+                PqAlgorithm::MlDsa65 => mldsa65::PUBLIC_KEY_LENGTH,
+            }
+        }
+
+        /// Signature length
+        pub fn signature_len(self) -> usize {
+            match self {
+                PqAlgorithm::MlDsa65 => mldsa65::SIGNATURE_LENGTH,
+            }
+        }
+    }
+
+    impl PqPrivateKey {
+        pub fn new(alg: PqAlgorithm, seed: [u8; PQ_SEED_LEN]) -> Self {
+            Self { alg, seed }
+        }
+
+        /// Derive the matching public key.
+        pub fn public_key(&self) -> PqPublicKey {
+            let (_, vk) = mldsa65::SigningKeySeed::from(self.seed).expand();
+            PqPublicKey {
+                alg: self.alg,
+                bytes: vk.as_bytes().to_vec(),
+            }
+        }
+
+        /// Sign `message` with hedged randomness.
+        #[cfg(feature = "issue")]
+        pub fn sign(&self, message: &[u8]) -> Result<Vec<u8>, AegisError> {
+            let (sk, _) = mldsa65::SigningKeySeed::from(self.seed).expand();
+            let mut rnd = [0u8; mldsa65::RND_LENGTH];
+            fill_random(&mut rnd)?;
+            let sig = sk
+                .sign(message, CONTEXT, &rnd)
+                .map_err(|e| AegisError::Crypto(e.to_string()))?;
+            Ok(sig.as_bytes().to_vec())
+        }
+    }
+
+    impl PqPublicKey {
+        /// Build a public key, checking its length for `alg`.
+        pub fn new(alg: PqAlgorithm, bytes: &[u8]) -> Result<Self, AegisError> {
+            let expected = alg.public_key_len();
+            if bytes.len() != expected {
+                return Err(AegisError::Key(format!(
+                    "{} public key must be {} bytes, got {}",
+                    alg,
+                    expected,
+                    bytes.len()
+                )));
+            }
+            Ok(Self {
+                alg,
+                bytes: bytes.to_vec(),
+            })
+        }
+
+        /// Verify a signature over `message`.
+        pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), AegisError> {
+            let invalid = |_| AegisError::Crypto("Invalid ML-DSA signature".to_string());
+            let vk = mldsa65::VerifyingKey::from_bytes(&self.bytes).map_err(invalid)?;
+            let sig = mldsa65::Signature::from_bytes(signature).map_err(invalid)?;
+            vk.verify(message, CONTEXT, &sig).map_err(invalid)
+        }
+    }
+
+    /// Generate a new PQ keypair using CSPRNG.
+    ///
+    /// Fails with [`AegisError::Crypto`] if the OS random number generator is unavailable.
+    #[cfg(feature = "keygen")]
+    pub fn pq_keygen(alg: PqAlgorithm) -> Result<PqKeypair, AegisError> {
+        let mut seed = [0u8; PQ_SEED_LEN];
+        fill_random(&mut seed)?;
+        let privkey = PqPrivateKey::new(alg, seed);
+        let pubkey = privkey.public_key();
+        Ok(PqKeypair { privkey, pubkey })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_algorithm_names() {
+        assert_eq!(
+            "mldsa65".parse::<PqAlgorithm>().unwrap(),
+            PqAlgorithm::MlDsa65
+        );
+        assert!("mldsa44".parse::<PqAlgorithm>().is_err());
+    }
+
+    #[cfg(feature = "pq")]
+    #[test]
+    fn test_sign_verify() {
+        let kp = pq_keygen(PqAlgorithm::MlDsa65).unwrap();
+        assert_eq!(kp.pubkey.bytes.len(), 1952);
+        let sig = kp.privkey.sign(b"payload").unwrap();
+        assert_eq!(sig.len(), PqAlgorithm::MlDsa65.signature_len());
+        kp.pubkey.verify(b"payload", &sig).unwrap();
+        assert!(kp.pubkey.verify(b"tampered", &sig).is_err());
+    }
+
+    #[cfg(feature = "pq")]
+    #[test]
+    fn test_public_key_length_checked() {
+        assert!(PqPublicKey::new(PqAlgorithm::MlDsa65, &[0u8; 33]).is_err());
+    }
+}
@@ -0,0 +1,214 @@
+//! `aegis:` URIs for QR codes and deep links.
+//!
+//! - `aegis:license?c=<base64url canonical certificate CBOR>`
+//! - `aegis:request?alg=<alg>&upk=<hex user public key>[&pid=<product id>]`
+//!
+//! A request URI is what a device shows so the vendor can issue a license for
+//! its key; a license URI is what the vendor hands back.
+
+#[cfg(feature = "cli-helpers")]
+use crate::cert::encode_cert;
+use crate::cert::{decode_cert, LicenseCert};
+use crate::crypto::SignatureAlgorithm;
+use crate::errors::AegisError;
+use crate::keys::PublicKey;
+use crate::prelude::*;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+/// URI scheme, without the colon.
+pub const URI_SCHEME: &str = "aegis";
+
+/// Parsed `aegis:` URI.
+#[derive(Debug, Clone)]
+pub enum AegisUri {
+    /// Certificate to install
+    License(Box<LicenseCert>),
+    /// Activation request from a device
+    Request(ActivationRequest),
+}
+
+/// Activation request carrying the user's public key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivationRequest {
+    pub user_key: PublicKey,
+    /// Product the device wants a license for
+    pub product_id: Option<String>,
+}
+
+impl ActivationRequest {
+    pub fn new(user_key: PublicKey, product_id: Option<String>) -> Self {
+        Self {
+            user_key,
+            product_id,
+        }
+    }
+
+    /// Encode as an `aegis:request` URI.
+    pub fn to_uri(&self) -> String {
+        let mut uri = format!(
+            "{}:request?alg={}&upk={}",
+            URI_SCHEME,
+            self.user_key.alg,
+            hex::encode(&self.user_key.bytes)
+        );
+        if let Some(pid) = &self.product_id {
+            uri.push_str("&pid=");
+            uri.push_str(&percent_encode(pid));
+        }
+        uri
+    }
+}
+
+/// Encode a certificate as an `aegis:license` URI.
+#[cfg(feature = "cli-helpers")]
+pub fn license_uri(cert: &LicenseCert) -> Result<String, AegisError> {
+    Ok(format!(
+        "{}:license?c={}",
+        URI_SCHEME,
+        URL_SAFE_NO_PAD.encode(encode_cert(cert)?)
+    ))
+}
+
+/// Whether `text` starts with the `aegis:` scheme.
+pub fn is_aegis_uri(text: &str) -> bool {
+    text.trim()
+        .split_once(':')
+        .is_some_and(|(scheme, _)| scheme.eq_ignore_ascii_case(URI_SCHEME))
+}
+
+/// Parse an `aegis:` URI. License certificates are decoded but not verified.
+pub fn parse_uri(uri: &str) -> Result<AegisUri, AegisError> {
+    let invalid = |reason: &str| AegisError::Payload(format!("invalid aegis URI: {}", reason));
+    let uri = uri.trim();
+    let rest = uri
+        .split_once(':')
+        .filter(|(scheme, _)| scheme.eq_ignore_ascii_case(URI_SCHEME))
+        .map(|(_, rest)| rest)
+        .ok_or_else(|| invalid("expected the aegis: scheme"))?;
+    let (kind, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+    let mut params = Vec::new();
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = percent_decode(value).ok_or_else(|| invalid("bad percent-encoding"))?;
+        params.push((key, value));
+    }
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    match kind {
+        "license" => {
+            let cert = param("c").ok_or_else(|| invalid("missing c parameter"))?;
+            let bytes = URL_SAFE_NO_PAD
+                .decode(cert)
+                .map_err(|_| AegisError::parse_field("c", "invalid base64url"))?;
+            decode_cert(&bytes).map(|cert| AegisUri::License(Box::new(cert)))
+        }
+        "request" => {
+            // Like key files, a missing algorithm means secp256k1
+            let alg: SignatureAlgorithm = param("alg")
+                .map(str::parse)
+                .transpose()?
+                .unwrap_or_default();
+            let upk = param("upk").ok_or_else(|| invalid("missing upk parameter"))?;
+            let bytes = hex::decode(upk)
+                .map_err(|_| AegisError::Key("invalid hex public key".to_string()))?;
+            Ok(AegisUri::Request(ActivationRequest {
+                user_key: PublicKey::new(alg, &bytes)?,
+                product_id: param("pid").map(str::to_string),
+            }))
+        }
+        other => Err(invalid(&format!("unknown kind '{}'", other))),
+    }
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            out.push(char::from(byte));
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
+
+fn percent_decode(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut iter = value.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(core::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cert::{issue_cert, LicensePayload, PROTOCOL_VERSION};
+    use crate::keys::try_keygen;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_license_uri_roundtrip() {
+        let vendor = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let user = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let payload = LicensePayload {
+            version: PROTOCOL_VERSION,
+            alg: SignatureAlgorithm::Secp256k1,
+            pq_alg: None,
+            product_id: "kiosk".to_string(),
+            license_id: [4u8; 32],
+            issued_at: 1700000000,
+            expiry: None,
+            user_pubkey: user.pubkey.bytes,
+            user_alg: SignatureAlgorithm::Secp256k1,
+            tier: "lifetime_pro".to_string(),
+            features: vec!["base".into()],
+            limits: None,
+            entitlements: BTreeMap::new(),
+            metadata: None,
+        };
+        let cert = issue_cert(&vendor.privkey.bytes, payload).unwrap();
+        let uri = license_uri(&cert).unwrap();
+        assert!(uri.starts_with("aegis:license?c="));
+        assert!(is_aegis_uri(&uri));
+        match parse_uri(&uri).unwrap() {
+            AegisUri::License(decoded) => assert_eq!(decoded.vendor_sig, cert.vendor_sig),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_request_uri_roundtrip() {
+        let user = try_keygen(SignatureAlgorithm::Ed25519).unwrap();
+        let request = ActivationRequest::new(user.pubkey, Some("my app/β".to_string()));
+        let uri = request.to_uri();
+        assert!(uri.contains("&pid=my%20app%2F%CE%B2"));
+        match parse_uri(&uri).unwrap() {
+            AegisUri::Request(parsed) => assert_eq!(parsed, request),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_uris_rejected() {
+        assert!(!is_aegis_uri("https://example.com"));
+        assert!(parse_uri("https://example.com").is_err());
+        assert!(parse_uri("aegis:unknown?x=1").is_err());
+        assert!(parse_uri("aegis:request?alg=secp256k1").is_err());
+        assert!(parse_uri("aegis:request?alg=secp256k1&upk=zz").is_err());
+        assert!(parse_uri("aegis:license?c=AAAA").is_err());
+        assert!(parse_uri("aegis:request?alg=secp256k1&upk=02&pid=%G1").is_err());
+    }
+}
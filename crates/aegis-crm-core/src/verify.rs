@@ -1,8 +1,32 @@
 //! Unified license verification (Certificate + PoP).
+//!
+//! [`Verifier`] bundles the trusted vendor keys, clock, expected product,
+//! revocation list and rollback state, and hands out a [`VerifiedLicense`]
+//! only when every check passes.
 
-use crate::cert::{verify_cert, LicenseCert};
+use crate::cert::{
+    check_expiry, decode_cert, verify_cert_signature, verify_cert_with_key, Entitlement,
+    LicenseCert, LicensePayload,
+};
+#[cfg(feature = "pq")]
+use crate::cert::{verify_classical_signature, verify_pq_signature};
+#[cfg(feature = "std")]
+use crate::clock::SystemClock;
+use crate::clock::{Clock, FixedClock};
 use crate::errors::AegisError;
-use crate::pop::{verify as verify_pop, Nonce32, PopSignature};
+use crate::features::{FeatureCatalog, FeatureSet};
+use crate::keys::PublicKey;
+use crate::pop::{verify_with_key as verify_pop, Nonce32, PopSignature};
+#[cfg(feature = "pq")]
+use crate::pq::PqPublicKey;
+use crate::prelude::*;
+use alloc::collections::BTreeSet;
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::{AtomicU64, Ordering};
+
+mod report;
+
+pub use report::{Check, CheckResult, CheckStatus, VerifyReport};
 
 pub fn verify_license(
     vendor_pubkey: &[u8; 33],
@@ -11,68 +35,819 @@ pub fn verify_license(
     nonce: &Nonce32,
     pop_sig: &PopSignature,
 ) -> Result<(), AegisError> {
-    verify_cert(vendor_pubkey, cert, now_unix)?;
-    verify_pop(&cert.payload.user_pubkey, nonce, pop_sig)?;
+    verify_license_with_key(
+        &PublicKey::secp256k1(vendor_pubkey),
+        cert,
+        now_unix,
+        nonce,
+        pop_sig,
+    )
+}
+
+/// Verify certificate and PoP against an algorithm-tagged vendor key.
+pub fn verify_license_with_key(
+    vendor_key: &PublicKey,
+    cert: &LicenseCert,
+    now_unix: u64,
+    nonce: &Nonce32,
+    pop_sig: &PopSignature,
+) -> Result<(), AegisError> {
+    verify_cert_with_key(vendor_key, cert, now_unix)?;
+    let user_key = cert
+        .payload
+        .user_key()
+        .map_err(|_| AegisError::PopSignature)?;
+    verify_pop(&user_key, nonce, pop_sig)?;
     Ok(())
 }
 
+/// Vendor keys a verifier accepts certificates from.
+///
+/// Several keys allow rotation: a certificate is accepted if any key of its
+/// algorithm verifies it. With PQ keys present, only hybrid certificates whose
+/// ML-DSA signature also verifies are accepted; without them, hybrid
+/// certificates fail with [`AegisError::PqKeyRequired`].
+#[derive(Debug, Clone, Default)]
+pub struct TrustStore {
+    keys: Vec<PublicKey>,
+    #[cfg(feature = "pq")]
+    pq_keys: Vec<PqPublicKey>,
+}
+
+impl TrustStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trust a vendor public key.
+    pub fn with_key(mut self, key: PublicKey) -> Self {
+        self.keys.push(key);
+        self
+    }
+
+    /// Trust a vendor ML-DSA public key, requiring hybrid certificates.
+    #[cfg(feature = "pq")]
+    pub fn with_pq_key(mut self, key: PqPublicKey) -> Self {
+        self.pq_keys.push(key);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Verify version and signature against the trusted keys, ignoring expiry.
+    fn verify_signature(&self, cert: &LicenseCert) -> Result<(), AegisError> {
+        let mut result = Err(AegisError::CertSignature);
+        for key in self.keys.iter().filter(|key| key.alg == cert.payload.alg) {
+            result = self.verify_with(key, cert);
+            if !matches!(result, Err(AegisError::CertSignature)) {
+                break;
+            }
+        }
+        result
+    }
+
+    #[cfg(not(feature = "pq"))]
+    fn verify_with(&self, key: &PublicKey, cert: &LicenseCert) -> Result<(), AegisError> {
+        verify_cert_signature(key, cert)
+    }
+
+    #[cfg(feature = "pq")]
+    fn verify_with(&self, key: &PublicKey, cert: &LicenseCert) -> Result<(), AegisError> {
+        if self.pq_keys.is_empty() {
+            return verify_cert_signature(key, cert);
+        }
+        verify_classical_signature(key, cert)?;
+        let mut result = Err(AegisError::CertSignature);
+        for pq_key in &self.pq_keys {
+            result = verify_pq_signature(pq_key, cert);
+            if result.is_ok() {
+                break;
+            }
+        }
+        result
+    }
+}
+
+/// Revoked license IDs, as distributed by the vendor.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RevocationList {
+    revoked: BTreeSet<[u8; 32]>,
+}
+
+impl RevocationList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a revoked license ID.
+    pub fn revoke(&mut self, license_id: [u8; 32]) {
+        self.revoked.insert(license_id);
+    }
+
+    pub fn with(mut self, license_id: [u8; 32]) -> Self {
+        self.revoke(license_id);
+        self
+    }
+
+    pub fn is_revoked(&self, license_id: &[u8; 32]) -> bool {
+        self.revoked.contains(license_id)
+    }
+}
+
+/// Persisted latest verification time, for clock rollback detection (SPEC §8).
+///
+/// Implementations typically write to a file or the platform keystore.
+pub trait StateStore {
+    /// Latest recorded verification time, if any.
+    fn last_seen(&self) -> Option<u64>;
+
+    /// Record a verification time not earlier than [`Self::last_seen`].
+    fn record(&self, now_unix: u64);
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T: StateStore + ?Sized> StateStore for alloc::sync::Arc<T> {
+    fn last_seen(&self) -> Option<u64> {
+        (**self).last_seen()
+    }
+
+    fn record(&self, now_unix: u64) {
+        (**self).record(now_unix)
+    }
+}
+
+/// In-memory [`StateStore`], for tests and short-lived processes.
+#[cfg(target_has_atomic = "64")]
+#[derive(Debug, Default)]
+pub struct MemoryStateStore {
+    // 0 means nothing recorded yet
+    last_seen: AtomicU64,
+}
+
+#[cfg(target_has_atomic = "64")]
+impl MemoryStateStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl StateStore for MemoryStateStore {
+    fn last_seen(&self) -> Option<u64> {
+        Some(self.last_seen.load(Ordering::SeqCst)).filter(|&t| t > 0)
+    }
+
+    fn record(&self, now_unix: u64) {
+        self.last_seen.fetch_max(now_unix, Ordering::SeqCst);
+    }
+}
+
+/// Tolerated difference between the issuer's and the verifier's clocks.
+pub const MAX_CLOCK_SKEW_SECS: u64 = 300;
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Builder for [`Verifier`].
+#[derive(Default)]
+pub struct VerifierBuilder {
+    trust: TrustStore,
+    clock: Option<Box<dyn Clock>>,
+    product_id: Option<String>,
+    revocations: Option<RevocationList>,
+    state: Option<Box<dyn StateStore>>,
+    last_online: Option<u64>,
+    catalog: Option<FeatureCatalog>,
+}
+
+impl VerifierBuilder {
+    /// Trust a vendor public key (may be called repeatedly).
+    pub fn trust(mut self, key: PublicKey) -> Self {
+        self.trust = self.trust.with_key(key);
+        self
+    }
+
+    /// Trust a vendor ML-DSA public key, requiring hybrid certificates.
+    #[cfg(feature = "pq")]
+    pub fn trust_pq(mut self, key: PqPublicKey) -> Self {
+        self.trust = self.trust.with_pq_key(key);
+        self
+    }
+
+    /// Replace the trusted keys.
+    pub fn trust_store(mut self, trust: TrustStore) -> Self {
+        self.trust = trust;
+        self
+    }
+
+    /// Read the time from `clock` instead of the system clock.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Box::new(clock));
+        self
+    }
+
+    /// Verify at a fixed time; shorthand for a [`FixedClock`].
+    pub fn now(self, now_unix: u64) -> Self {
+        self.clock(FixedClock::new(now_unix))
+    }
+
+    /// Only accept certificates for this product.
+    pub fn product(mut self, product_id: impl Into<String>) -> Self {
+        self.product_id = Some(product_id.into());
+        self
+    }
+
+    /// Reject license IDs on this list.
+    pub fn revocations(mut self, revocations: RevocationList) -> Self {
+        self.revocations = Some(revocations);
+        self
+    }
+
+    /// Detect clock rollback against, and record verification times in, `state`.
+    pub fn state_store(mut self, state: impl StateStore + 'static) -> Self {
+        self.state = Some(Box::new(state));
+        self
+    }
+
+    /// Time of the last successful online check, enforcing the certificate's
+    /// offline grace period (`limits.offline_grace_days`).
+    pub fn last_online(mut self, last_online_unix: u64) -> Self {
+        self.last_online = Some(last_online_unix);
+        self
+    }
+
+    /// Resolve feature implications through `catalog` in [`VerifiedLicense::has_feature`].
+    pub fn catalog(mut self, catalog: FeatureCatalog) -> Self {
+        self.catalog = Some(catalog);
+        self
+    }
+
+    /// Without the `std` feature there is no system clock, so a clock must be set.
+    pub fn build(self) -> Result<Verifier, AegisError> {
+        if self.trust.is_empty() {
+            return Err(AegisError::Key("no trusted vendor keys".to_string()));
+        }
+        let clock = match self.clock {
+            Some(clock) => clock,
+            #[cfg(feature = "std")]
+            None => Box::new(SystemClock),
+            #[cfg(not(feature = "std"))]
+            None => return Err(AegisError::Clock("no clock set".to_string())),
+        };
+        Ok(Verifier {
+            trust: self.trust,
+            clock,
+            product_id: self.product_id,
+            revocations: self.revocations,
+            state: self.state,
+            last_online: self.last_online,
+            catalog: self.catalog,
+        })
+    }
+}
+
+/// Configured license verifier, created with [`Verifier::builder`].
+pub struct Verifier {
+    trust: TrustStore,
+    clock: Box<dyn Clock>,
+    product_id: Option<String>,
+    revocations: Option<RevocationList>,
+    state: Option<Box<dyn StateStore>>,
+    last_online: Option<u64>,
+    catalog: Option<FeatureCatalog>,
+}
+
+impl Verifier {
+    pub fn builder() -> VerifierBuilder {
+        VerifierBuilder::default()
+    }
+
+    /// Decode and verify a CBOR certificate.
+    pub fn verify(&self, cert_bytes: &[u8]) -> Result<VerifiedLicense, AegisError> {
+        self.verify_cert(decode_cert(cert_bytes)?)
+    }
+
+    /// Decode and verify a CBOR certificate and the holder's PoP signature.
+    pub fn verify_with_pop(
+        &self,
+        cert_bytes: &[u8],
+        nonce: &Nonce32,
+        pop_sig: &PopSignature,
+    ) -> Result<VerifiedLicense, AegisError> {
+        let license = self
+            .check(decode_cert(cert_bytes)?)?
+            .prove_possession(nonce, pop_sig)?;
+        self.record(license.verified_at);
+        Ok(license)
+    }
+
+    /// Verify an already decoded certificate.
+    ///
+    /// Checks, in order: clock rollback, version and signature, validity
+    /// period (issue time up to [`MAX_CLOCK_SKEW_SECS`] in the future, then
+    /// expiry), offline grace, product, then revocation. The time is recorded
+    /// in the state store only once every check has passed, so a forged or
+    /// rejected certificate cannot push the stored time forward.
+    pub fn verify_cert(&self, cert: LicenseCert) -> Result<VerifiedLicense, AegisError> {
+        let license = self.check(cert)?;
+        self.record(license.verified_at);
+        Ok(license)
+    }
+
+    /// Run every [`Self::verify_cert`] check without recording the time.
+    fn check(&self, cert: LicenseCert) -> Result<VerifiedLicense, AegisError> {
+        let now_unix = self.clock.now_unix()?;
+        if let Some(state) = &self.state {
+            if state.last_seen().is_some_and(|last| now_unix < last) {
+                return Err(AegisError::ClockRollback);
+            }
+        }
+
+        self.trust.verify_signature(&cert)?;
+        check_validity(&cert.payload, now_unix)?;
+        self.check_grace(&cert.payload, now_unix)?;
+        self.check_product(&cert.payload)?;
+        self.check_revocation(&cert.payload)?;
+
+        Ok(VerifiedLicense {
+            cert,
+            verified_at: now_unix,
+            pop_verified: false,
+            catalog: self.catalog.clone(),
+        })
+    }
+
+    fn record(&self, now_unix: u64) {
+        if let Some(state) = &self.state {
+            state.record(now_unix);
+        }
+    }
+
+    fn check_grace(&self, payload: &LicensePayload, now_unix: u64) -> Result<(), AegisError> {
+        let grace_days = payload.limits.as_ref().and_then(|l| l.offline_grace_days);
+        match (grace_days, self.last_online) {
+            (Some(days), Some(last_online))
+                if now_unix.saturating_sub(last_online) > u64::from(days) * SECONDS_PER_DAY =>
+            {
+                Err(AegisError::OfflineGraceExpired { days })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_product(&self, payload: &LicensePayload) -> Result<(), AegisError> {
+        match &self.product_id {
+            Some(expected) if payload.product_id != *expected => Err(AegisError::ProductMismatch {
+                expected: expected.clone(),
+                found: payload.product_id.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_revocation(&self, payload: &LicensePayload) -> Result<(), AegisError> {
+        match &self.revocations {
+            Some(revocations) if revocations.is_revoked(&payload.license_id) => {
+                Err(AegisError::Revoked)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Issued no later than the tolerated clock skew, and not expired.
+fn check_validity(payload: &LicensePayload, now_unix: u64) -> Result<(), AegisError> {
+    if payload.issued_at > now_unix.saturating_add(MAX_CLOCK_SKEW_SECS) {
+        return Err(AegisError::CertNotYetValid {
+            issued_at: payload.issued_at,
+        });
+    }
+    check_expiry(payload, now_unix)
+}
+
+/// A certificate that passed [`Verifier`] checks.
+///
+/// Only a verifier can construct one, so gating features on it cannot use an
+/// unverified certificate by mistake. Feature windows are evaluated at the
+/// verification time, and implications through the verifier's catalog.
+#[derive(Debug, Clone)]
+pub struct VerifiedLicense {
+    cert: LicenseCert,
+    verified_at: u64,
+    pop_verified: bool,
+    catalog: Option<FeatureCatalog>,
+}
+
+impl VerifiedLicense {
+    /// Additionally check the holder's PoP signature over `nonce`.
+    pub fn prove_possession(
+        mut self,
+        nonce: &Nonce32,
+        pop_sig: &PopSignature,
+    ) -> Result<Self, AegisError> {
+        let user_key = self
+            .cert
+            .payload
+            .user_key()
+            .map_err(|_| AegisError::PopSignature)?;
+        verify_pop(&user_key, nonce, pop_sig)?;
+        self.pop_verified = true;
+        Ok(self)
+    }
+
+    /// Whether the holder proved possession of the user key.
+    pub fn pop_verified(&self) -> bool {
+        self.pop_verified
+    }
+
+    /// Time the license was verified at.
+    pub fn verified_at(&self) -> u64 {
+        self.verified_at
+    }
+
+    pub fn payload(&self) -> &LicensePayload {
+        &self.cert.payload
+    }
+
+    pub fn cert(&self) -> &LicenseCert {
+        &self.cert
+    }
+
+    pub fn into_cert(self) -> LicenseCert {
+        self.cert
+    }
+
+    pub fn product_id(&self) -> &str {
+        &self.cert.payload.product_id
+    }
+
+    pub fn license_id(&self) -> &[u8; 32] {
+        &self.cert.payload.license_id
+    }
+
+    pub fn tier(&self) -> &str {
+        &self.cert.payload.tier
+    }
+
+    pub fn expiry(&self) -> Option<u64> {
+        self.cert.payload.expiry
+    }
+
+    /// Whether `feature` is granted, directly or through the verifier's
+    /// catalog, and active at the verification time.
+    pub fn has_feature(&self, feature: &str) -> bool {
+        match &self.catalog {
+            Some(catalog) => self.features(catalog).contains(feature),
+            None => self.cert.payload.has_feature_at(feature, self.verified_at),
+        }
+    }
+
+    /// Features active at the verification time, expanded through `catalog`.
+    pub fn features(&self, catalog: &FeatureCatalog) -> FeatureSet {
+        self.cert
+            .payload
+            .resolve_features(catalog, self.verified_at)
+    }
+
+    pub fn entitlement(&self, name: &str) -> Option<&Entitlement> {
+        self.cert.payload.entitlement(name)
+    }
+
+    pub fn get_u64(&self, name: &str) -> Option<u64> {
+        self.cert.payload.get_u64(name)
+    }
+
+    pub fn get_i64(&self, name: &str) -> Option<i64> {
+        self.cert.payload.get_i64(name)
+    }
+
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        self.cert.payload.get_bool(name)
+    }
+
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        self.cert.payload.get_str(name)
+    }
+
+    pub fn get_list(&self, name: &str) -> Option<&[String]> {
+        self.cert.payload.get_list(name)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::cert::{issue_cert, LicensePayload, PROTOCOL_VERSION};
-    use crate::keys::{user_keygen, vendor_keygen};
-    use crate::pop::{challenge, prove};
+    use crate::cert::{
+        encode_cert, issue_cert, issue_cert_with_policy, FeatureGrant, PROTOCOL_VERSION,
+    };
+    use crate::clock::OffsetClock;
+    use crate::crypto::SignatureAlgorithm;
+    use crate::keys::{try_keygen, try_user_keygen, try_vendor_keygen};
+    use crate::policy::PolicySet;
+    use crate::pop::{prove, prove_with_key, try_challenge};
     use getrandom::getrandom;
+    use std::collections::BTreeMap;
 
     #[test]
     fn test_verify_license_valid() {
-        let vendor = vendor_keygen();
-        let user = user_keygen();
+        let vendor = try_vendor_keygen().unwrap();
+        let user = try_user_keygen().unwrap();
         let mut license_id = [0u8; 32];
         getrandom(&mut license_id).unwrap();
         let payload = LicensePayload {
             version: PROTOCOL_VERSION,
+            alg: SignatureAlgorithm::Secp256k1,
+            pq_alg: None,
             product_id: "test".to_string(),
             license_id,
             issued_at: 1700000000,
             expiry: Some(2000000000),
-            user_pubkey: user.pubkey,
+            user_pubkey: user.pubkey.to_vec(),
+            user_alg: SignatureAlgorithm::Secp256k1,
             tier: "lifetime_pro".to_string(),
-            features: vec!["base".to_string(), "premium".to_string()],
+            features: vec!["base".into(), "premium".into()],
             limits: None,
+            entitlements: BTreeMap::new(),
             metadata: None,
         };
         let cert = issue_cert(&vendor.privkey, payload).unwrap();
-        let nonce = challenge();
+        let nonce = try_challenge().unwrap();
         let pop_sig = prove(&user.privkey, &nonce).unwrap();
         verify_license(&vendor.pubkey, &cert, 1800000000, &nonce, &pop_sig).unwrap();
     }
 
     #[test]
     fn test_verify_license_invalid_pop() {
-        let vendor = vendor_keygen();
-        let user = user_keygen();
-        let wrong = user_keygen();
+        let vendor = try_vendor_keygen().unwrap();
+        let user = try_user_keygen().unwrap();
+        let wrong = try_user_keygen().unwrap();
         let mut license_id = [0u8; 32];
         getrandom(&mut license_id).unwrap();
         let payload = LicensePayload {
             version: PROTOCOL_VERSION,
+            alg: SignatureAlgorithm::Secp256k1,
+            pq_alg: None,
             product_id: "test".to_string(),
             license_id,
             issued_at: 1700000000,
             expiry: None,
-            user_pubkey: user.pubkey,
+            user_pubkey: user.pubkey.to_vec(),
+            user_alg: SignatureAlgorithm::Secp256k1,
             tier: "lifetime_pro".to_string(),
-            features: vec!["base".to_string()],
+            features: vec!["base".into()],
             limits: None,
+            entitlements: BTreeMap::new(),
             metadata: None,
         };
         let cert = issue_cert(&vendor.privkey, payload).unwrap();
-        let nonce = challenge();
+        let nonce = try_challenge().unwrap();
         let pop_sig = prove(&wrong.privkey, &nonce).unwrap();
         assert!(matches!(
             verify_license(&vendor.pubkey, &cert, 1800000000, &nonce, &pop_sig),
             Err(AegisError::PopSignature)
         ));
     }
+
+    fn verifier_cert(vendor: &crate::keys::Keypair, user: &crate::keys::Keypair) -> LicenseCert {
+        let payload = LicensePayload {
+            version: PROTOCOL_VERSION,
+            alg: vendor.privkey.alg,
+            pq_alg: None,
+            product_id: "my_app".to_string(),
+            license_id: [7u8; 32],
+            issued_at: 1700000000,
+            expiry: Some(2000000000),
+            user_pubkey: user.pubkey.bytes.clone(),
+            user_alg: user.pubkey.alg,
+            tier: "lifetime_pro".to_string(),
+            features: vec![
+                "base".into(),
+                FeatureGrant::new("beta").with_window(None, Some(1750000000)),
+            ],
+            limits: None,
+            entitlements: BTreeMap::from([("max_projects".to_string(), Entitlement::Int(10))]),
+            metadata: None,
+        };
+        issue_cert_with_policy(&vendor.privkey, payload, &PolicySet::builtin()).unwrap()
+    }
+
+    #[test]
+    fn test_verifier_returns_verified_license() {
+        let vendor = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let user = try_keygen(SignatureAlgorithm::Ed25519).unwrap();
+        let cert = encode_cert(&verifier_cert(&vendor, &user)).unwrap();
+        let verifier = Verifier::builder()
+            .trust(vendor.pubkey)
+            .product("my_app")
+            .now(1800000000)
+            .build()
+            .unwrap();
+
+        let license = verifier.verify(&cert).unwrap();
+        assert!(!license.pop_verified());
+        assert_eq!(license.verified_at(), 1800000000);
+        assert!(license.has_feature("base"));
+        // Window closed before the verification time
+        assert!(!license.has_feature("beta"));
+        assert_eq!(license.get_u64("max_projects"), Some(10));
+
+        let nonce = try_challenge().unwrap();
+        let pop_sig = prove_with_key(&user.privkey, &nonce).unwrap();
+        let license = verifier.verify_with_pop(&cert, &nonce, &pop_sig).unwrap();
+        assert!(license.pop_verified());
+        assert!(matches!(
+            verifier.verify_with_pop(&cert, &try_challenge().unwrap(), &pop_sig),
+            Err(AegisError::PopSignature)
+        ));
+    }
+
+    #[test]
+    fn test_verifier_rejections() {
+        let vendor = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let user = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let cert = encode_cert(&verifier_cert(&vendor, &user)).unwrap();
+        let builder = || {
+            Verifier::builder()
+                .trust(vendor.pubkey.clone())
+                .now(1800000000)
+        };
+
+        assert!(matches!(
+            builder()
+                .product("other_app")
+                .build()
+                .unwrap()
+                .verify(&cert),
+            Err(AegisError::ProductMismatch { .. })
+        ));
+        let revoked = RevocationList::new().with([7u8; 32]);
+        assert!(matches!(
+            builder()
+                .revocations(revoked)
+                .build()
+                .unwrap()
+                .verify(&cert),
+            Err(AegisError::Revoked)
+        ));
+        assert!(matches!(
+            builder().now(2000000001).build().unwrap().verify(&cert),
+            Err(AegisError::CertExpired)
+        ));
+        let later = OffsetClock::new(FixedClock::new(1800000000), 200_000_001);
+        assert!(matches!(
+            builder().clock(later).build().unwrap().verify(&cert),
+            Err(AegisError::CertExpired)
+        ));
+        assert!(builder()
+            .now(1700000000 - MAX_CLOCK_SKEW_SECS)
+            .build()
+            .unwrap()
+            .verify(&cert)
+            .is_ok());
+        assert!(matches!(
+            builder().now(1699999000).build().unwrap().verify(&cert),
+            Err(AegisError::CertNotYetValid {
+                issued_at: 1700000000
+            })
+        ));
+        assert!(matches!(
+            builder().build().unwrap().verify(b"garbage"),
+            Err(AegisError::CertParse { .. })
+        ));
+        let stranger = try_keygen(SignatureAlgorithm::Secp256k1).unwrap().pubkey;
+        assert!(matches!(
+            Verifier::builder()
+                .trust(stranger)
+                .build()
+                .unwrap()
+                .verify(&cert),
+            Err(AegisError::CertSignature)
+        ));
+        assert!(matches!(
+            Verifier::builder().build(),
+            Err(AegisError::Key(_))
+        ));
+    }
+
+    #[test]
+    fn test_verifier_resolves_implied_features() {
+        let vendor = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let user = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let cert = verifier_cert(&vendor, &user);
+        let catalog = FeatureCatalog::new()
+            .with_rule("base", ["export"])
+            .with_rule("beta", ["reports"]);
+        let builder = || {
+            Verifier::builder()
+                .trust(vendor.pubkey.clone())
+                .now(1800000000)
+        };
+
+        let license = builder()
+            .build()
+            .unwrap()
+            .verify_cert(cert.clone())
+            .unwrap();
+        assert!(!license.has_feature("export"));
+
+        let license = builder()
+            .catalog(catalog)
+            .build()
+            .unwrap()
+            .verify_cert(cert)
+            .unwrap();
+        assert!(license.has_feature("base"));
+        assert!(license.has_feature("export"));
+        // Implied only by a grant whose window has closed
+        assert!(!license.has_feature("beta"));
+        assert!(!license.has_feature("reports"));
+    }
+
+    #[test]
+    fn test_verifier_accepts_any_trusted_key() {
+        let old = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let new = try_keygen(SignatureAlgorithm::P256).unwrap();
+        let user = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let verifier = Verifier::builder()
+            .trust_store(
+                TrustStore::new()
+                    .with_key(old.pubkey.clone())
+                    .with_key(new.pubkey.clone()),
+            )
+            .now(1800000000)
+            .build()
+            .unwrap();
+        for vendor in [&old, &new] {
+            let cert = verifier_cert(vendor, &user);
+            assert!(verifier.verify_cert(cert).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_verifier_detects_clock_rollback() {
+        let vendor = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let user = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let cert = encode_cert(&verifier_cert(&vendor, &user)).unwrap();
+        let state = std::sync::Arc::new(MemoryStateStore::new());
+        let at = |now: u64| {
+            Verifier::builder()
+                .trust(vendor.pubkey.clone())
+                .now(now)
+                .state_store(state.clone())
+                .build()
+                .unwrap()
+                .verify(&cert)
+        };
+
+        assert!(at(1800000000).is_ok());
+        assert_eq!(state.last_seen(), Some(1800000000));
+        assert!(matches!(at(1700000000), Err(AegisError::ClockRollback)));
+        assert!(at(1800000100).is_ok());
+        assert_eq!(state.last_seen(), Some(1800000100));
+    }
+
+    #[test]
+    fn test_verifier_records_state_only_after_success() {
+        let vendor = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let user = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let state = std::sync::Arc::new(MemoryStateStore::new());
+        let verifier = |now: u64| {
+            Verifier::builder()
+                .trust(vendor.pubkey.clone())
+                .now(now)
+                .state_store(state.clone())
+                .build()
+                .unwrap()
+        };
+        assert!(verifier(1800000000)
+            .verify_cert(verifier_cert(&vendor, &user))
+            .is_ok());
+
+        // A forged certificate presented with the clock far ahead is rejected
+        // without moving the recorded time, so honest checks keep working.
+        let forger = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        assert!(matches!(
+            verifier(4000000000).verify_cert(verifier_cert(&forger, &user)),
+            Err(AegisError::CertSignature)
+        ));
+        assert_eq!(state.last_seen(), Some(1800000000));
+        assert!(verifier(1800000100)
+            .verify_cert(verifier_cert(&vendor, &user))
+            .is_ok());
+
+        // Likewise a valid certificate with a bad PoP signature
+        let cert = encode_cert(&verifier_cert(&vendor, &user)).unwrap();
+        let nonce = try_challenge().unwrap();
+        let pop_sig = prove_with_key(&forger.privkey, &nonce).unwrap();
+        assert!(matches!(
+            verifier(1800000500).verify_with_pop(&cert, &nonce, &pop_sig),
+            Err(AegisError::PopSignature)
+        ));
+        assert_eq!(state.last_seen(), Some(1800000100));
+        let pop_sig = prove_with_key(&user.privkey, &nonce).unwrap();
+        assert!(verifier(1800000200)
+            .verify_with_pop(&cert, &nonce, &pop_sig)
+            .is_ok());
+        assert_eq!(state.last_seen(), Some(1800000200));
+    }
 }
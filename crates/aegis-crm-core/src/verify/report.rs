@@ -0,0 +1,468 @@
+//! Step-by-step verification report.
+//!
+//! [`Verifier::verify_report`] runs every check instead of stopping at the
+//! first failure, so tools can explain why a certificate is rejected.
+
+use super::{check_validity, Verifier};
+use crate::cert::{decode_cert, encode_cert, normalize_cert, LicenseCert, PROTOCOL_VERSION};
+use crate::errors::AegisError;
+use crate::pop::{verify_with_key as verify_pop, Nonce32, PopSignature};
+use crate::prelude::*;
+use core::fmt;
+use serde::Serialize;
+
+/// One step of certificate verification, in the order it is reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Check {
+    Decode,
+    Version,
+    /// Canonical CBOR and low-S signature; informational, see [`VerifyReport::is_valid`].
+    Canonical,
+    Signature,
+    /// Validity period: not issued in the future, not expired.
+    Expiry,
+    /// Offline grace period since the last online check.
+    Grace,
+    Product,
+    Pop,
+    Revocation,
+}
+
+impl Check {
+    pub fn name(self) -> &'static str {
+        match self {
+            Check::Decode => "decode",
+            Check::Version => "version",
+            Check::Canonical => "canonical",
+            Check::Signature => "signature",
+            Check::Expiry => "expiry",
+            Check::Grace => "grace",
+            Check::Product => "product",
+            Check::Pop => "pop",
+            Check::Revocation => "revocation",
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    /// Not applicable, not configured, or blocked by an earlier failure.
+    Skip,
+}
+
+/// Outcome of one [`Check`] with a human-readable reason.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckResult {
+    pub check: Check,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// Stable error code ([`AegisError::code`]) of a failed check.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<&'static str>,
+}
+
+impl CheckResult {
+    fn pass(check: Check, detail: impl Into<String>) -> Self {
+        Self {
+            check,
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            code: None,
+        }
+    }
+
+    fn fail(check: Check, err: &AegisError) -> Self {
+        Self {
+            check,
+            status: CheckStatus::Fail,
+            detail: err.to_string(),
+            code: Some(err.code()),
+        }
+    }
+
+    /// Failure that does not reject the certificate on its own.
+    fn advisory(check: Check, detail: impl Into<String>) -> Self {
+        Self {
+            check,
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            code: None,
+        }
+    }
+
+    fn skip(check: Check, detail: impl Into<String>) -> Self {
+        Self {
+            check,
+            status: CheckStatus::Skip,
+            detail: detail.into(),
+            code: None,
+        }
+    }
+
+    fn from_result(check: Check, result: Result<String, AegisError>) -> Self {
+        match result {
+            Ok(detail) => Self::pass(check, detail),
+            Err(err) => Self::fail(check, &err),
+        }
+    }
+
+    /// Run a time-dependent check, failing it if the clock cannot be read.
+    fn at(
+        check: Check,
+        now: &Result<u64, AegisError>,
+        run: impl FnOnce(u64) -> Result<String, AegisError>,
+    ) -> Self {
+        match now {
+            Ok(now) => Self::from_result(check, run(*now)),
+            Err(err) => Self::fail(check, err),
+        }
+    }
+}
+
+/// Results of every verification check, in [`Check`] order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VerifyReport {
+    pub checks: Vec<CheckResult>,
+}
+
+impl VerifyReport {
+    /// Whether [`Verifier::verify`] (with the same PoP, if any) would accept
+    /// the certificate.
+    ///
+    /// A non-canonical encoding alone does not invalidate a certificate, since
+    /// the signature covers the re-encoded payload.
+    pub fn is_valid(&self) -> bool {
+        self.first_failure().is_none()
+    }
+
+    /// The first failed check that rejects the certificate.
+    pub fn first_failure(&self) -> Option<&CheckResult> {
+        self.checks
+            .iter()
+            .find(|c| c.status == CheckStatus::Fail && c.check != Check::Canonical)
+    }
+
+    pub fn get(&self, check: Check) -> Option<&CheckResult> {
+        self.checks.iter().find(|c| c.check == check)
+    }
+}
+
+impl Verifier {
+    /// Run every check on a CBOR certificate and report each outcome.
+    ///
+    /// Unlike [`Verifier::verify`], later checks still run after a failure,
+    /// except those that cannot: nothing runs without a decoded certificate,
+    /// and the signature is not checked for an unsupported version. The state
+    /// store is neither consulted nor updated.
+    pub fn verify_report(
+        &self,
+        cert_bytes: &[u8],
+        pop: Option<(&Nonce32, &PopSignature)>,
+    ) -> VerifyReport {
+        let cert = match decode_cert(cert_bytes) {
+            Ok(cert) => cert,
+            Err(err) => {
+                let mut checks = vec![CheckResult::fail(Check::Decode, &err)];
+                checks.extend(
+                    [
+                        Check::Version,
+                        Check::Canonical,
+                        Check::Signature,
+                        Check::Expiry,
+                        Check::Grace,
+                        Check::Product,
+                        Check::Pop,
+                        Check::Revocation,
+                    ]
+                    .map(|check| CheckResult::skip(check, "certificate not decoded")),
+                );
+                return VerifyReport { checks };
+            }
+        };
+        let payload = &cert.payload;
+        let now = self.clock.now_unix();
+
+        let version_ok = payload.version == PROTOCOL_VERSION;
+        let checks = vec![
+            CheckResult::pass(
+                Check::Decode,
+                format!("CBOR certificate, {} bytes", cert_bytes.len()),
+            ),
+            if version_ok {
+                CheckResult::pass(
+                    Check::Version,
+                    format!("protocol version {}", payload.version),
+                )
+            } else {
+                CheckResult::fail(Check::Version, &AegisError::UnsupportedVersion)
+            },
+            canonical_check(&cert, cert_bytes),
+            if version_ok {
+                CheckResult::from_result(
+                    Check::Signature,
+                    self.trust
+                        .verify_signature(&cert)
+                        .map(|()| format!("{} signature by a trusted vendor key", payload.alg)),
+                )
+            } else {
+                CheckResult::skip(Check::Signature, "unsupported protocol version")
+            },
+            CheckResult::at(Check::Expiry, &now, |now| {
+                check_validity(payload, now)?;
+                Ok(match payload.expiry {
+                    Some(exp) => format!("valid until {} (now {})", exp, now),
+                    None => format!("no expiry (now {})", now),
+                })
+            }),
+            self.grace_check(&cert, &now),
+            match &self.product_id {
+                Some(_) => CheckResult::from_result(
+                    Check::Product,
+                    self.check_product(payload)
+                        .map(|()| format!("product '{}'", payload.product_id)),
+                ),
+                None => CheckResult::skip(Check::Product, "no expected product configured"),
+            },
+            match pop {
+                Some((nonce, pop_sig)) => CheckResult::from_result(
+                    Check::Pop,
+                    payload
+                        .user_key()
+                        .map_err(|_| AegisError::PopSignature)
+                        .and_then(|user_key| verify_pop(&user_key, nonce, pop_sig))
+                        .map(|()| "holder signed the challenge with the user key".to_string()),
+                ),
+                None => CheckResult::skip(Check::Pop, "no challenge and PoP signature given"),
+            },
+            match &self.revocations {
+                Some(_) => CheckResult::from_result(
+                    Check::Revocation,
+                    self.check_revocation(payload)
+                        .map(|()| "license ID not on the revocation list".to_string()),
+                ),
+                None => CheckResult::skip(Check::Revocation, "no revocation list configured"),
+            },
+        ];
+        VerifyReport { checks }
+    }
+
+    fn grace_check(&self, cert: &LicenseCert, now: &Result<u64, AegisError>) -> CheckResult {
+        let grace_days = cert
+            .payload
+            .limits
+            .as_ref()
+            .and_then(|l| l.offline_grace_days);
+        let (Some(days), Some(last_online)) = (grace_days, self.last_online) else {
+            let reason = match grace_days {
+                None => "no offline grace period in certificate",
+                Some(_) => "time of last online check not given",
+            };
+            return CheckResult::skip(Check::Grace, reason);
+        };
+        CheckResult::at(Check::Grace, now, |now| {
+            self.check_grace(&cert.payload, now)?;
+            Ok(format!(
+                "{} s offline, {} days allowed",
+                now.saturating_sub(last_online),
+                days
+            ))
+        })
+    }
+}
+
+/// Whether the input is exactly the canonical encoding with a low-S signature.
+fn canonical_check(cert: &LicenseCert, cert_bytes: &[u8]) -> CheckResult {
+    let mut normalized = cert.clone();
+    match normalize_cert(&mut normalized) {
+        Ok(false) => {}
+        Ok(true) => return CheckResult::advisory(Check::Canonical, "signature is not low-S"),
+        Err(err) => return CheckResult::fail(Check::Canonical, &err),
+    }
+    match encode_cert(cert) {
+        Ok(encoded) if encoded == cert_bytes => {
+            CheckResult::pass(Check::Canonical, "canonical CBOR, low-S signature")
+        }
+        Ok(_) => CheckResult::advisory(
+            Check::Canonical,
+            "encoding differs from canonical CBOR re-encoding",
+        ),
+        Err(err) => CheckResult::fail(Check::Canonical, &err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cert::{issue_cert_with_policy, LicensePayload, Limits};
+    use crate::crypto::SignatureAlgorithm;
+    use crate::keys::{try_keygen, Keypair};
+    use crate::policy::PolicySet;
+    use crate::pop::{prove_with_key, try_challenge};
+    use crate::verify::RevocationList;
+    use std::collections::BTreeMap;
+
+    fn report_cert(vendor: &Keypair, user: &Keypair) -> LicenseCert {
+        let payload = LicensePayload {
+            version: PROTOCOL_VERSION,
+            alg: vendor.privkey.alg,
+            pq_alg: None,
+            product_id: "my_app".to_string(),
+            license_id: [9u8; 32],
+            issued_at: 1700000000,
+            expiry: Some(2000000000),
+            user_pubkey: user.pubkey.bytes.clone(),
+            user_alg: user.pubkey.alg,
+            tier: "subscription".to_string(),
+            features: vec!["base".into()],
+            limits: Some(Limits {
+                seat_max: None,
+                offline_grace_days: Some(7),
+            }),
+            entitlements: BTreeMap::new(),
+            metadata: None,
+        };
+        issue_cert_with_policy(&vendor.privkey, payload, &PolicySet::builtin()).unwrap()
+    }
+
+    fn statuses(report: &VerifyReport) -> Vec<(Check, CheckStatus)> {
+        report.checks.iter().map(|c| (c.check, c.status)).collect()
+    }
+
+    #[test]
+    fn test_report_all_checks_pass() {
+        let vendor = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let user = try_keygen(SignatureAlgorithm::Ed25519).unwrap();
+        let cert = encode_cert(&report_cert(&vendor, &user)).unwrap();
+        let verifier = Verifier::builder()
+            .trust(vendor.pubkey.clone())
+            .now(1800000000)
+            .product("my_app")
+            .revocations(RevocationList::new().with([1u8; 32]))
+            .last_online(1800000000 - 86_400)
+            .build()
+            .unwrap();
+        let nonce = try_challenge().unwrap();
+        let pop_sig = prove_with_key(&user.privkey, &nonce).unwrap();
+
+        let report = verifier.verify_report(&cert, Some((&nonce, &pop_sig)));
+        assert!(report.is_valid());
+        assert!(report
+            .checks
+            .iter()
+            .all(|c| c.status == CheckStatus::Pass && c.code.is_none()));
+        assert_eq!(report.checks.len(), 9);
+
+        // Unconfigured checks are skipped, not passed
+        let bare = Verifier::builder()
+            .trust(vendor.pubkey)
+            .now(1800000000)
+            .build()
+            .unwrap()
+            .verify_report(&cert, None);
+        assert!(bare.is_valid());
+        for check in [Check::Grace, Check::Product, Check::Pop, Check::Revocation] {
+            assert_eq!(bare.get(check).unwrap().status, CheckStatus::Skip);
+        }
+    }
+
+    #[test]
+    fn test_report_lists_every_failure() {
+        let vendor = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let user = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let cert = encode_cert(&report_cert(&vendor, &user)).unwrap();
+        let stranger = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let report = Verifier::builder()
+            .trust(stranger.pubkey)
+            .now(2000000001)
+            .product("other_app")
+            .revocations(RevocationList::new().with([9u8; 32]))
+            .last_online(1800000000)
+            .build()
+            .unwrap()
+            .verify_report(&cert, Some((&try_challenge().unwrap(), &[0u8; 64])));
+
+        assert!(!report.is_valid());
+        assert_eq!(report.first_failure().unwrap().check, Check::Signature);
+        let codes: Vec<_> = report.checks.iter().filter_map(|c| c.code).collect();
+        assert_eq!(
+            codes,
+            [
+                "E_CERT_SIG",
+                "E_CERT_EXPIRED",
+                "E_OFFLINE_GRACE",
+                "E_PRODUCT_MISMATCH",
+                "E_POP_SIG",
+                "E_REVOKED"
+            ]
+        );
+        assert_eq!(
+            report.get(Check::Canonical).unwrap().status,
+            CheckStatus::Pass
+        );
+    }
+
+    #[test]
+    fn test_report_skips_after_decode_failure() {
+        let vendor = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let verifier = Verifier::builder()
+            .trust(vendor.pubkey.clone())
+            .build()
+            .unwrap();
+        let report = verifier.verify_report(b"garbage", None);
+        assert_eq!(report.checks[0].code, Some("E_CERT_PARSE"));
+        assert!(report.checks[1..]
+            .iter()
+            .all(|c| c.status == CheckStatus::Skip));
+        assert_eq!(report.checks.len(), 9);
+
+        let user = try_keygen(SignatureAlgorithm::Secp256k1).unwrap();
+        let mut cert = report_cert(&vendor, &user);
+        cert.payload.version = 2;
+        let report = verifier.verify_report(&encode_cert(&cert).unwrap(), None);
+        assert_eq!(
+            statuses(&report)[1..4],
+            [
+                (Check::Version, CheckStatus::Fail),
+                (Check::Canonical, CheckStatus::Pass),
+                (Check::Signature, CheckStatus::Skip),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_report_canonical_is_advisory() {
+        let vendor = try_keygen(SignatureAlgorithm::Ed25519).unwrap();
+        let user = try_keygen(SignatureAlgorithm::Ed25519).unwrap();
+        let cert = report_cert(&vendor, &user);
+        let verifier = Verifier::builder()
+            .trust(vendor.pubkey)
+            .now(1800000000)
+            .build()
+            .unwrap();
+
+        // Same certificate as a definite-length map with the fields reordered
+        let value: ciborium::Value =
+            ciborium::from_reader(encode_cert(&cert).unwrap().as_slice()).unwrap();
+        let mut entries = value.into_map().unwrap();
+        entries.reverse();
+        let mut reordered = Vec::new();
+        ciborium::into_writer(&ciborium::Value::Map(entries), &mut reordered).unwrap();
+
+        let report = verifier.verify_report(&reordered, None);
+        let canonical = report.get(Check::Canonical).unwrap();
+        assert_eq!(canonical.status, CheckStatus::Fail);
+        assert_eq!(canonical.code, None);
+        assert!(report.is_valid());
+        assert!(verifier.verify(&reordered).is_ok());
+    }
+}
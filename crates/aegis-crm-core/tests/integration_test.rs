@@ -1,35 +1,40 @@
 //! Integration tests for Aegis CRM Standard
 
 use aegis_crm_core::{
-    cert::{decode_cert, encode_cert, issue_cert, verify_cert, LicensePayload, PROTOCOL_VERSION},
-    keys::{pubkey_from_privkey, user_keygen, vendor_keygen},
-    pop::{challenge, prove, verify as verify_pop},
-    verify::verify_license,
+    cert::{
+        decode_cert, encode_cert, issue_cert, verify_cert, verify_cert_with_key, LicensePayload,
+        PROTOCOL_VERSION,
+    },
+    crypto::{sha256, SignatureAlgorithm},
+    keys::{pubkey_from_privkey, try_user_keygen, try_vendor_keygen, PrivateKey, PublicKey},
+    pop::{prove, prove_with_key, try_challenge, verify as verify_pop},
+    verify::{verify_license, verify_license_with_key},
     AegisError,
 };
 use getrandom::getrandom;
+use std::collections::BTreeMap;
 
 #[test]
 fn test_complete_license_flow() {
-    let vendor = vendor_keygen();
-    let user = user_keygen();
+    let vendor = try_vendor_keygen().unwrap();
+    let user = try_user_keygen().unwrap();
     let mut license_id = [0u8; 32];
     getrandom(&mut license_id).unwrap();
 
     let payload = LicensePayload {
         version: PROTOCOL_VERSION,
+        alg: SignatureAlgorithm::Secp256k1,
+        pq_alg: None,
         product_id: "integration_test_app".to_string(),
         license_id,
         issued_at: 1700000000,
         expiry: Some(2000000000),
-        user_pubkey: user.pubkey,
+        user_pubkey: user.pubkey.to_vec(),
+        user_alg: SignatureAlgorithm::Secp256k1,
         tier: "lifetime_pro".to_string(),
-        features: vec![
-            "base".to_string(),
-            "premium".to_string(),
-            "advanced".to_string(),
-        ],
+        features: vec!["base".into(), "premium".into(), "advanced".into()],
         limits: None,
+        entitlements: BTreeMap::new(),
         metadata: None,
     };
 
@@ -40,7 +45,7 @@ fn test_complete_license_flow() {
     assert_eq!(decoded.payload.product_id, cert.payload.product_id);
     verify_cert(&vendor.pubkey, &decoded, 1800000000).expect("Cert verification failed");
 
-    let nonce = challenge();
+    let nonce = try_challenge().unwrap();
     let pop_sig = prove(&user.privkey, &nonce).expect("Failed to prove");
     verify_pop(&user.pubkey, &nonce, &pop_sig).expect("PoP verification failed");
 
@@ -50,21 +55,25 @@ fn test_complete_license_flow() {
 
 #[test]
 fn test_tampered_certificate_fails() {
-    let vendor = vendor_keygen();
-    let user = user_keygen();
+    let vendor = try_vendor_keygen().unwrap();
+    let user = try_user_keygen().unwrap();
     let mut license_id = [0u8; 32];
     getrandom(&mut license_id).unwrap();
 
     let payload = LicensePayload {
         version: PROTOCOL_VERSION,
+        alg: SignatureAlgorithm::Secp256k1,
+        pq_alg: None,
         product_id: "test_app".to_string(),
         license_id,
         issued_at: 1700000000,
         expiry: None,
-        user_pubkey: user.pubkey,
+        user_pubkey: user.pubkey.to_vec(),
+        user_alg: SignatureAlgorithm::Secp256k1,
         tier: "lifetime_pro".to_string(),
-        features: vec!["base".to_string()],
+        features: vec!["base".into()],
         limits: None,
+        entitlements: BTreeMap::new(),
         metadata: None,
     };
 
@@ -77,21 +86,25 @@ fn test_tampered_certificate_fails() {
 
 #[test]
 fn test_expired_certificate_fails() {
-    let vendor = vendor_keygen();
-    let user = user_keygen();
+    let vendor = try_vendor_keygen().unwrap();
+    let user = try_user_keygen().unwrap();
     let mut license_id = [0u8; 32];
     getrandom(&mut license_id).unwrap();
 
     let payload = LicensePayload {
         version: PROTOCOL_VERSION,
+        alg: SignatureAlgorithm::Secp256k1,
+        pq_alg: None,
         product_id: "test_app".to_string(),
         license_id,
         issued_at: 1700000000,
         expiry: Some(1900000000),
-        user_pubkey: user.pubkey,
+        user_pubkey: user.pubkey.to_vec(),
+        user_alg: SignatureAlgorithm::Secp256k1,
         tier: "campus".to_string(),
-        features: vec!["education".to_string()],
+        features: vec!["education".into()],
         limits: None,
+        entitlements: BTreeMap::new(),
         metadata: None,
     };
 
@@ -102,27 +115,31 @@ fn test_expired_certificate_fails() {
 
 #[test]
 fn test_invalid_pop_fails() {
-    let vendor = vendor_keygen();
-    let user = user_keygen();
-    let wrong_user = user_keygen();
+    let vendor = try_vendor_keygen().unwrap();
+    let user = try_user_keygen().unwrap();
+    let wrong_user = try_user_keygen().unwrap();
     let mut license_id = [0u8; 32];
     getrandom(&mut license_id).unwrap();
 
     let payload = LicensePayload {
         version: PROTOCOL_VERSION,
+        alg: SignatureAlgorithm::Secp256k1,
+        pq_alg: None,
         product_id: "test_app".to_string(),
         license_id,
         issued_at: 1700000000,
         expiry: None,
-        user_pubkey: user.pubkey,
+        user_pubkey: user.pubkey.to_vec(),
+        user_alg: SignatureAlgorithm::Secp256k1,
         tier: "lifetime_pro".to_string(),
-        features: vec!["base".to_string()],
+        features: vec!["base".into()],
         limits: None,
+        entitlements: BTreeMap::new(),
         metadata: None,
     };
 
     let cert = issue_cert(&vendor.privkey, payload).expect("Failed to issue cert");
-    let nonce = challenge();
+    let nonce = try_challenge().unwrap();
     let pop_sig = prove(&wrong_user.privkey, &nonce).expect("Failed to prove");
 
     let result = verify_license(&vendor.pubkey, &cert, 1800000000, &nonce, &pop_sig);
@@ -131,21 +148,25 @@ fn test_invalid_pop_fails() {
 
 #[test]
 fn test_cbor_encode_decode_stability() {
-    let vendor = vendor_keygen();
-    let user = user_keygen();
+    let vendor = try_vendor_keygen().unwrap();
+    let user = try_user_keygen().unwrap();
     let mut license_id = [0u8; 32];
     getrandom(&mut license_id).unwrap();
 
     let payload = LicensePayload {
         version: PROTOCOL_VERSION,
+        alg: SignatureAlgorithm::Secp256k1,
+        pq_alg: None,
         product_id: "stability_test".to_string(),
         license_id,
         issued_at: 1700000000,
         expiry: None,
-        user_pubkey: user.pubkey,
+        user_pubkey: user.pubkey.to_vec(),
+        user_alg: SignatureAlgorithm::Secp256k1,
         tier: "lifetime_pro".to_string(),
-        features: vec!["ALL".to_string()],
+        features: vec!["ALL".into()],
         limits: None,
+        entitlements: BTreeMap::new(),
         metadata: None,
     };
 
@@ -161,7 +182,85 @@ fn test_cbor_encode_decode_stability() {
 
 #[test]
 fn test_pubkey_derivation_consistency() {
-    let user = user_keygen();
+    let user = try_user_keygen().unwrap();
     let derived = pubkey_from_privkey(&user.privkey).expect("Failed to derive pubkey");
     assert_eq!(derived, user.pubkey);
 }
+
+fn hex_field(value: &serde_json::Value, field: &str) -> Vec<u8> {
+    hex::decode(value[field].as_str().expect(field)).expect(field)
+}
+
+fn hex_array<const N: usize>(value: &serde_json::Value, field: &str) -> [u8; N] {
+    hex_field(value, field).try_into().expect(field)
+}
+
+#[test]
+fn test_committed_vectors_match() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../../fixtures/vectors.json");
+    let vectors: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(path).expect("Missing vectors.json"))
+            .expect("Invalid vectors.json");
+
+    let mut keys = BTreeMap::new();
+    for key in vectors["keys"].as_array().unwrap() {
+        let alg: SignatureAlgorithm = key["alg"].as_str().unwrap().parse().unwrap();
+        let privkey = PrivateKey::new(alg, hex_array(key, "private"));
+        let pubkey = privkey.public_key().unwrap();
+        assert_eq!(pubkey.bytes, hex_field(key, "public"), "{}", key["name"]);
+        keys.insert(key["name"].as_str().unwrap().to_string(), privkey);
+    }
+
+    for vector in vectors["certificates"].as_array().unwrap() {
+        let name = &vector["name"];
+        let vendor = &keys[vector["vendor_key"].as_str().unwrap()];
+        let cert_cbor = hex_field(vector, "cert_cbor");
+        let cert = decode_cert(&cert_cbor).expect("Vector does not decode");
+        assert_eq!(encode_cert(&cert).unwrap(), cert_cbor, "{}", name);
+
+        let mut payload_cbor = Vec::new();
+        ciborium::into_writer(&cert.payload, &mut payload_cbor).unwrap();
+        assert_eq!(payload_cbor, hex_field(vector, "payload_cbor"), "{}", name);
+        let digest = sha256(&payload_cbor);
+        assert_eq!(digest.to_vec(), hex_field(vector, "digest"), "{}", name);
+
+        let signature = vendor.alg.scheme().sign(&vendor.bytes, &digest).unwrap();
+        assert_eq!(
+            signature.to_vec(),
+            hex_field(vector, "signature"),
+            "{}",
+            name
+        );
+        assert_eq!(cert.vendor_sig, signature, "{}", name);
+        verify_cert_with_key(&vendor.public_key().unwrap(), &cert, cert.payload.issued_at).unwrap();
+    }
+
+    for vector in vectors["pop"].as_array().unwrap() {
+        let user = &keys[vector["user_key"].as_str().unwrap()];
+        let nonce = hex_array(vector, "nonce");
+        assert_eq!(sha256(&nonce).to_vec(), hex_field(vector, "digest"));
+        let signature = prove_with_key(user, &nonce).unwrap();
+        assert_eq!(signature.to_vec(), hex_field(vector, "signature"));
+    }
+
+    for case in vectors["verification"].as_array().unwrap() {
+        let key = &case["vendor_key"];
+        let result = PublicKey::new(
+            key["alg"].as_str().unwrap().parse().unwrap(),
+            &hex_field(key, "public"),
+        )
+        .and_then(|vendor_key| {
+            let cert = decode_cert(&hex_field(case, "cert_cbor"))?;
+            let now = case["now"].as_u64().unwrap();
+            if case.get("pop_sig").is_some() {
+                let nonce = hex_array(case, "nonce");
+                let pop_sig = hex_array(case, "pop_sig");
+                verify_license_with_key(&vendor_key, &cert, now, &nonce, &pop_sig)
+            } else {
+                verify_cert_with_key(&vendor_key, &cert, now)
+            }
+        });
+        let code = result.as_ref().map_or_else(AegisError::code, |_| "OK");
+        assert_eq!(code, case["result"], "{}", case["name"]);
+    }
+}
@@ -0,0 +1,19 @@
+//! Regenerate `include/aegis.h` from the `extern "C"` API
+//!
+//! Run after changing the C API and commit the result; CI fails if the
+//! committed header is out of date.
+
+use std::path::Path;
+
+fn main() {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .expect("Failed to read cbindgen.toml");
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(crate_dir.join("src/lib.rs"))
+        .generate()
+        .expect("Failed to generate C header")
+        .write_to_file(crate_dir.join("include/aegis.h"));
+    println!("Wrote include/aegis.h");
+}
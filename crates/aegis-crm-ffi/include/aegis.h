@@ -0,0 +1,314 @@
+/* Aegis CRM C API. Generated by cbindgen from crates/aegis-crm-ffi; do not edit. */
+
+#ifndef AEGIS_H
+#define AEGIS_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Length of a PoP challenge nonce.
+ */
+#define AEGIS_NONCE_LEN 32
+
+/**
+ * Length of a PoP signature.
+ */
+#define AEGIS_POP_SIG_LEN 64
+
+/**
+ * Length of a license ID.
+ */
+#define AEGIS_LICENSE_ID_LEN 32
+
+/**
+ * Length of a private key.
+ */
+#define AEGIS_PRIVATE_KEY_LEN 32
+
+/**
+ * Longest encoded public key (compressed SEC1).
+ */
+#define AEGIS_PUBLIC_KEY_MAX_LEN 33
+
+/**
+ * Result of a fallible call: `AEGIS_STATUS_OK` or an error code.
+ *
+ * Values 1-18 are the core `AegisError::numeric_code` values (1-8 being the
+ * SPEC §9 set); names follow the string codes without the `E_` prefix.
+ */
+typedef enum AegisStatus {
+  AEGIS_STATUS_OK = 0,
+  AEGIS_STATUS_CERT_PARSE = 1,
+  AEGIS_STATUS_UNSUPPORTED_VER = 2,
+  AEGIS_STATUS_CERT_SIG = 3,
+  AEGIS_STATUS_CERT_EXPIRED = 4,
+  AEGIS_STATUS_POP_SIG = 5,
+  AEGIS_STATUS_CLOCK_ROLLBACK = 6,
+  AEGIS_STATUS_KEY = 7,
+  AEGIS_STATUS_CRYPTO = 8,
+  AEGIS_STATUS_CERT_NOT_YET_VALID = 9,
+  AEGIS_STATUS_PRODUCT_MISMATCH = 10,
+  AEGIS_STATUS_REVOKED = 11,
+  AEGIS_STATUS_POLICY_VIOLATION = 12,
+  AEGIS_STATUS_PAYLOAD = 13,
+  AEGIS_STATUS_ARMOR = 14,
+  AEGIS_STATUS_LICENSE_KEY = 15,
+  AEGIS_STATUS_OFFLINE_GRACE = 16,
+  AEGIS_STATUS_PQ_KEY_REQUIRED = 17,
+  AEGIS_STATUS_CLOCK = 18,
+  /**
+   * Null pointer or malformed argument (C API only).
+   */
+  AEGIS_STATUS_INVALID_ARGUMENT = 100,
+} AegisStatus;
+
+/**
+ * Decoded, not yet verified certificate.
+ */
+typedef struct AegisCert AegisCert;
+
+/**
+ * License that passed verification; feature queries are only offered here.
+ */
+typedef struct AegisLicense AegisLicense;
+
+/**
+ * Algorithm-tagged private key.
+ */
+typedef struct AegisPrivateKey AegisPrivateKey;
+
+/**
+ * Algorithm-tagged public key.
+ */
+typedef struct AegisPublicKey AegisPublicKey;
+
+/**
+ * Configured verifier.
+ */
+typedef struct AegisVerifier AegisVerifier;
+
+/**
+ * Verifier configuration, turned into an `AegisVerifier` by `aegis_verifier_build`.
+ */
+typedef struct AegisVerifierBuilder AegisVerifierBuilder;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Message of the last failed call on this thread, or null.
+ *
+ * Valid until the next failing call on the same thread.
+ */
+const char *aegis_last_error(void);
+
+/**
+ * Decode a certificate given as raw CBOR, base64 text or ASCII armor.
+ */
+enum AegisStatus aegis_cert_decode(const uint8_t *data, size_t data_len, struct AegisCert **out);
+
+/**
+ * Check the vendor signature and expiry at `now_unix`.
+ *
+ * Use an `AegisVerifier` to also check product, revocation and clock rollback.
+ */
+enum AegisStatus aegis_cert_verify(const struct AegisCert *cert,
+                                   const struct AegisPublicKey *vendor_key,
+                                   uint64_t now_unix);
+
+/**
+ * Product ID, valid while `cert` lives; null if `cert` is null.
+ */
+const char *aegis_cert_product_id(const struct AegisCert *cert);
+
+/**
+ * Tier name, valid while `cert` lives; null if `cert` is null.
+ */
+const char *aegis_cert_tier(const struct AegisCert *cert);
+
+/**
+ * Expiry in Unix seconds; `false` (leaving `out` untouched) for a perpetual license.
+ */
+bool aegis_cert_expiry(const struct AegisCert *cert, uint64_t *out);
+
+void aegis_cert_free(struct AegisCert *cert);
+
+/**
+ * Public key from its encoded bytes (33 bytes compressed SEC1, or 32 for
+ * `ed25519` and `schnorr`).
+ */
+enum AegisStatus aegis_public_key_new(const char *alg,
+                                      const uint8_t *key,
+                                      size_t key_len,
+                                      struct AegisPublicKey **out);
+
+/**
+ * Copy the encoded key to `out` (`AEGIS_PUBLIC_KEY_MAX_LEN` bytes) and its length to `out_len`.
+ */
+enum AegisStatus aegis_public_key_bytes(const struct AegisPublicKey *key,
+                                        uint8_t *out,
+                                        size_t *out_len);
+
+void aegis_public_key_free(struct AegisPublicKey *key);
+
+/**
+ * Private key from its `AEGIS_PRIVATE_KEY_LEN` bytes.
+ */
+enum AegisStatus aegis_private_key_new(const char *alg,
+                                       const uint8_t *key,
+                                       struct AegisPrivateKey **out);
+
+/**
+ * Generate a private key from the OS random number generator.
+ */
+enum AegisStatus aegis_private_key_generate(const char *alg, struct AegisPrivateKey **out);
+
+/**
+ * Copy the `AEGIS_PRIVATE_KEY_LEN` key bytes to `out`, e.g. to store them.
+ */
+enum AegisStatus aegis_private_key_bytes(const struct AegisPrivateKey *key, uint8_t *out);
+
+/**
+ * Derive the matching public key.
+ */
+enum AegisStatus aegis_private_key_public_key(const struct AegisPrivateKey *key,
+                                              struct AegisPublicKey **out);
+
+void aegis_private_key_free(struct AegisPrivateKey *key);
+
+/**
+ * Write a random `AEGIS_NONCE_LEN`-byte challenge to `out`.
+ */
+enum AegisStatus aegis_pop_challenge(uint8_t *out);
+
+/**
+ * Sign `nonce` with the user key, writing `AEGIS_POP_SIG_LEN` bytes to `out`.
+ */
+enum AegisStatus aegis_pop_prove(const struct AegisPrivateKey *user_key,
+                                 const uint8_t *nonce,
+                                 uint8_t *out);
+
+/**
+ * Check a PoP signature; `AEGIS_STATUS_POP_SIG` if it is invalid.
+ */
+enum AegisStatus aegis_pop_verify(const struct AegisPublicKey *user_key,
+                                  const uint8_t *nonce,
+                                  const uint8_t *pop_sig);
+
+/**
+ * New verifier configuration using the system clock.
+ */
+struct AegisVerifierBuilder *aegis_verifier_builder_new(void);
+
+/**
+ * Trust a vendor public key (may be called repeatedly to accept rotated keys).
+ */
+enum AegisStatus aegis_verifier_builder_trust(struct AegisVerifierBuilder *builder,
+                                              const struct AegisPublicKey *vendor_key);
+
+/**
+ * Only accept certificates for `product_id`.
+ */
+enum AegisStatus aegis_verifier_builder_product(struct AegisVerifierBuilder *builder,
+                                                const char *product_id);
+
+/**
+ * Reject the `AEGIS_LICENSE_ID_LEN`-byte license ID (may be called repeatedly).
+ */
+enum AegisStatus aegis_verifier_builder_revoke(struct AegisVerifierBuilder *builder,
+                                               const uint8_t *license_id);
+
+/**
+ * Verify at a fixed time instead of the system clock.
+ */
+enum AegisStatus aegis_verifier_builder_now(struct AegisVerifierBuilder *builder,
+                                            uint64_t now_unix);
+
+/**
+ * Time of the last successful online check, enforcing the offline grace period.
+ */
+enum AegisStatus aegis_verifier_builder_last_online(struct AegisVerifierBuilder *builder,
+                                                    uint64_t last_online_unix);
+
+/**
+ * Reject verification times earlier than one this verifier has already seen.
+ */
+enum AegisStatus aegis_verifier_builder_detect_rollback(struct AegisVerifierBuilder *builder);
+
+void aegis_verifier_builder_free(struct AegisVerifierBuilder *builder);
+
+/**
+ * Build the verifier. Always consumes `builder`, even on failure.
+ */
+enum AegisStatus aegis_verifier_build(struct AegisVerifierBuilder *builder,
+                                      struct AegisVerifier **out);
+
+/**
+ * Verify a decoded certificate; on success `out` receives the license.
+ */
+enum AegisStatus aegis_verifier_verify(const struct AegisVerifier *verifier,
+                                       const struct AegisCert *cert,
+                                       struct AegisLicense **out);
+
+void aegis_verifier_free(struct AegisVerifier *verifier);
+
+/**
+ * Check the holder's PoP signature (`AEGIS_POP_SIG_LEN` bytes) over `nonce`
+ * (`AEGIS_NONCE_LEN` bytes).
+ */
+enum AegisStatus aegis_license_prove_possession(struct AegisLicense *license,
+                                                const uint8_t *nonce,
+                                                const uint8_t *pop_sig);
+
+/**
+ * Whether `aegis_license_prove_possession` succeeded.
+ */
+bool aegis_license_pop_verified(const struct AegisLicense *license);
+
+/**
+ * Whether the license grants `feature` (supports `ALL` and `prefix.*` grants).
+ */
+bool aegis_license_has_feature(const struct AegisLicense *license, const char *feature);
+
+/**
+ * Integer entitlement `name`; `false` if absent or not a non-negative integer.
+ */
+bool aegis_license_get_u64(const struct AegisLicense *license, const char *name, uint64_t *out);
+
+/**
+ * Integer entitlement `name`; `false` if absent or not an integer.
+ */
+bool aegis_license_get_i64(const struct AegisLicense *license, const char *name, int64_t *out);
+
+/**
+ * Boolean entitlement `name`; `false` if absent or not a boolean.
+ */
+bool aegis_license_get_bool(const struct AegisLicense *license, const char *name, bool *out);
+
+/**
+ * Product ID, valid while `license` lives; null if `license` is null.
+ */
+const char *aegis_license_product_id(const struct AegisLicense *license);
+
+/**
+ * Tier name, valid while `license` lives; null if `license` is null.
+ */
+const char *aegis_license_tier(const struct AegisLicense *license);
+
+/**
+ * Expiry in Unix seconds; `false` (leaving `out` untouched) for a perpetual license.
+ */
+bool aegis_license_expiry(const struct AegisLicense *license, uint64_t *out);
+
+void aegis_license_free(struct AegisLicense *license);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* AEGIS_H */
@@ -0,0 +1,79 @@
+//! Decoded certificate handles.
+
+use crate::keys::AegisPublicKey;
+use crate::{bytes, free, handle, handle_mut, put, run, AegisStatus, PayloadStrings};
+use aegis_crm_core::armor::decode_cert_auto;
+use aegis_crm_core::cert::{verify_cert_with_key, LicenseCert};
+use std::ffi::c_char;
+use std::ptr;
+
+/// Decoded, not yet verified certificate.
+pub struct AegisCert {
+    pub(crate) cert: LicenseCert,
+    strings: PayloadStrings,
+}
+
+/// Decode a certificate given as raw CBOR, base64 text or ASCII armor.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_cert_decode(
+    data: *const u8,
+    data_len: usize,
+    out: *mut *mut AegisCert,
+) -> AegisStatus {
+    run(|| {
+        let cert = decode_cert_auto(bytes(data, data_len, "data")?)?;
+        let strings = PayloadStrings::new(&cert.payload)?;
+        put(out, AegisCert { cert, strings })
+    })
+}
+
+/// Check the vendor signature and expiry at `now_unix`.
+///
+/// Use an `AegisVerifier` to also check product, revocation and clock rollback.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_cert_verify(
+    cert: *const AegisCert,
+    vendor_key: *const AegisPublicKey,
+    now_unix: u64,
+) -> AegisStatus {
+    run(|| {
+        let cert = &handle(cert, "cert")?.cert;
+        Ok(verify_cert_with_key(
+            &handle(vendor_key, "vendor_key")?.0,
+            cert,
+            now_unix,
+        )?)
+    })
+}
+
+/// Product ID, valid while `cert` lives; null if `cert` is null.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_cert_product_id(cert: *const AegisCert) -> *const c_char {
+    cert.as_ref()
+        .map_or(ptr::null(), |cert| cert.strings.product_id.as_ptr())
+}
+
+/// Tier name, valid while `cert` lives; null if `cert` is null.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_cert_tier(cert: *const AegisCert) -> *const c_char {
+    cert.as_ref()
+        .map_or(ptr::null(), |cert| cert.strings.tier.as_ptr())
+}
+
+/// Expiry in Unix seconds; `false` (leaving `out` untouched) for a perpetual license.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_cert_expiry(cert: *const AegisCert, out: *mut u64) -> bool {
+    let (Ok(cert), Ok(out)) = (handle(cert, "cert"), handle_mut(out, "out")) else {
+        return false;
+    };
+    cert.cert
+        .payload
+        .expiry
+        .map(|expiry| *out = expiry)
+        .is_some()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn aegis_cert_free(cert: *mut AegisCert) {
+    free(cert)
+}
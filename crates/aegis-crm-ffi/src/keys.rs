@@ -0,0 +1,107 @@
+//! Public and private key handles.
+
+use crate::{array, bytes, free, handle, handle_mut, put, run, string, write, AegisStatus, Error};
+use crate::{AEGIS_PRIVATE_KEY_LEN, AEGIS_PUBLIC_KEY_MAX_LEN};
+use aegis_crm_core::crypto::SignatureAlgorithm;
+use aegis_crm_core::keys::{try_keygen, PrivateKey, PublicKey};
+use std::ffi::c_char;
+
+/// Algorithm-tagged public key.
+pub struct AegisPublicKey(pub(crate) PublicKey);
+
+/// Algorithm-tagged private key.
+pub struct AegisPrivateKey(pub(crate) PrivateKey);
+
+/// Parse an algorithm name (`secp256k1`, `p256`, `ed25519`, `schnorr`); null means secp256k1.
+unsafe fn algorithm(alg: *const c_char) -> Result<SignatureAlgorithm, Error> {
+    if alg.is_null() {
+        return Ok(SignatureAlgorithm::Secp256k1);
+    }
+    Ok(string(alg, "alg")?.parse()?)
+}
+
+/// Public key from its encoded bytes (33 bytes compressed SEC1, or 32 for
+/// `ed25519` and `schnorr`).
+#[no_mangle]
+pub unsafe extern "C" fn aegis_public_key_new(
+    alg: *const c_char,
+    key: *const u8,
+    key_len: usize,
+    out: *mut *mut AegisPublicKey,
+) -> AegisStatus {
+    run(|| {
+        let key = PublicKey::new(algorithm(alg)?, bytes(key, key_len, "key")?)?;
+        put(out, AegisPublicKey(key))
+    })
+}
+
+/// Copy the encoded key to `out` (`AEGIS_PUBLIC_KEY_MAX_LEN` bytes) and its length to `out_len`.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_public_key_bytes(
+    key: *const AegisPublicKey,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> AegisStatus {
+    run(|| {
+        let key = &handle(key, "key")?.0.bytes;
+        let mut buf = [0u8; AEGIS_PUBLIC_KEY_MAX_LEN];
+        buf[..key.len()].copy_from_slice(key);
+        write(out, &buf, "out")?;
+        *handle_mut(out_len, "out_len")? = key.len();
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn aegis_public_key_free(key: *mut AegisPublicKey) {
+    free(key)
+}
+
+/// Private key from its `AEGIS_PRIVATE_KEY_LEN` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_private_key_new(
+    alg: *const c_char,
+    key: *const u8,
+    out: *mut *mut AegisPrivateKey,
+) -> AegisStatus {
+    run(|| {
+        let key = PrivateKey::new(algorithm(alg)?, array(key, "key")?);
+        key.public_key()?;
+        put(out, AegisPrivateKey(key))
+    })
+}
+
+/// Generate a private key from the OS random number generator.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_private_key_generate(
+    alg: *const c_char,
+    out: *mut *mut AegisPrivateKey,
+) -> AegisStatus {
+    run(|| put(out, AegisPrivateKey(try_keygen(algorithm(alg)?)?.privkey)))
+}
+
+/// Copy the `AEGIS_PRIVATE_KEY_LEN` key bytes to `out`, e.g. to store them.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_private_key_bytes(
+    key: *const AegisPrivateKey,
+    out: *mut u8,
+) -> AegisStatus {
+    run(|| {
+        let key: &[u8; AEGIS_PRIVATE_KEY_LEN] = &handle(key, "key")?.0.bytes;
+        write(out, key, "out")
+    })
+}
+
+/// Derive the matching public key.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_private_key_public_key(
+    key: *const AegisPrivateKey,
+    out: *mut *mut AegisPublicKey,
+) -> AegisStatus {
+    run(|| put(out, AegisPublicKey(handle(key, "key")?.0.public_key()?)))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn aegis_private_key_free(key: *mut AegisPrivateKey) {
+    free(key)
+}
@@ -0,0 +1,285 @@
+//! C ABI for license verification and proof-of-possession.
+//!
+//! Host applications (C, C++, Unity) include the cbindgen-generated
+//! `include/aegis.h` and link `aegis_crm_ffi`. Certificates, verifiers,
+//! verified licenses and keys are opaque handles released with the matching
+//! `aegis_*_free`. Fallible functions return an [`AegisStatus`] whose values
+//! are the stable numeric error codes of the core crate; `aegis_last_error`
+//! describes the last failure on the calling thread.
+//!
+//! # Safety
+//!
+//! Every function taking a pointer is `unsafe`; the contract is the same for
+//! all of them, so it is stated once here. Pointers must be null or valid:
+//! byte buffers for the documented length, strings NUL-terminated UTF-8,
+//! handles obtained from this library and not yet freed. Output pointers must
+//! be writable. Null arguments are reported as
+//! `AEGIS_STATUS_INVALID_ARGUMENT`. A handle must not be used from two
+//! threads at once.
+//!
+//! This is the only crate that uses `unsafe`; it is confined to the pointer
+//! helpers at the bottom of this file and the `extern "C"` signatures.
+
+// The shared safety contract is documented at the crate level.
+#![allow(clippy::missing_safety_doc)]
+
+mod cert;
+mod keys;
+mod pop;
+mod verify;
+
+pub use cert::*;
+pub use keys::*;
+pub use pop::*;
+pub use verify::*;
+
+use aegis_crm_core::cert::LicensePayload;
+use aegis_crm_core::AegisError;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::fmt;
+use std::ptr;
+
+/// Length of a PoP challenge nonce.
+pub const AEGIS_NONCE_LEN: usize = 32;
+/// Length of a PoP signature.
+pub const AEGIS_POP_SIG_LEN: usize = 64;
+/// Length of a license ID.
+pub const AEGIS_LICENSE_ID_LEN: usize = 32;
+/// Length of a private key.
+pub const AEGIS_PRIVATE_KEY_LEN: usize = 32;
+/// Longest encoded public key (compressed SEC1).
+pub const AEGIS_PUBLIC_KEY_MAX_LEN: usize = 33;
+
+/// Result of a fallible call: `AEGIS_STATUS_OK` or an error code.
+///
+/// Values 1-18 are the core `AegisError::numeric_code` values (1-8 being the
+/// SPEC §9 set); names follow the string codes without the `E_` prefix.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AegisStatus {
+    Ok = 0,
+    CertParse = 1,
+    UnsupportedVer = 2,
+    CertSig = 3,
+    CertExpired = 4,
+    PopSig = 5,
+    ClockRollback = 6,
+    Key = 7,
+    Crypto = 8,
+    CertNotYetValid = 9,
+    ProductMismatch = 10,
+    Revoked = 11,
+    PolicyViolation = 12,
+    Payload = 13,
+    Armor = 14,
+    LicenseKey = 15,
+    OfflineGrace = 16,
+    PqKeyRequired = 17,
+    Clock = 18,
+    /// Null pointer or malformed argument (C API only).
+    InvalidArgument = 100,
+}
+
+impl From<&AegisError> for AegisStatus {
+    fn from(err: &AegisError) -> Self {
+        match err {
+            AegisError::CertParse { .. } => AegisStatus::CertParse,
+            AegisError::UnsupportedVersion => AegisStatus::UnsupportedVer,
+            AegisError::CertSignature => AegisStatus::CertSig,
+            AegisError::CertExpired => AegisStatus::CertExpired,
+            AegisError::CertNotYetValid { .. } => AegisStatus::CertNotYetValid,
+            AegisError::PopSignature => AegisStatus::PopSig,
+            AegisError::ProductMismatch { .. } => AegisStatus::ProductMismatch,
+            AegisError::Revoked => AegisStatus::Revoked,
+            AegisError::OfflineGraceExpired { .. } => AegisStatus::OfflineGrace,
+            AegisError::ClockRollback => AegisStatus::ClockRollback,
+            AegisError::Payload(_) => AegisStatus::Payload,
+            AegisError::PolicyViolation(_) => AegisStatus::PolicyViolation,
+            AegisError::Key(_) => AegisStatus::Key,
+            AegisError::Armor(_) => AegisStatus::Armor,
+            AegisError::LicenseKey(_) => AegisStatus::LicenseKey,
+            AegisError::Crypto(_) => AegisStatus::Crypto,
+            AegisError::PqKeyRequired => AegisStatus::PqKeyRequired,
+            AegisError::Clock(_) => AegisStatus::Clock,
+        }
+    }
+}
+
+/// Message of the last failed call on this thread, or null.
+///
+/// Valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn aegis_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Failure of an API call.
+enum Error {
+    Core(AegisError),
+    Argument(String),
+}
+
+impl From<AegisError> for Error {
+    fn from(err: AegisError) -> Self {
+        Error::Core(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Core(err) => write!(f, "{}: {}", err.code(), err),
+            Error::Argument(message) => write!(f, "E_INVALID_ARGUMENT: {}", message),
+        }
+    }
+}
+
+/// Run an API call, recording the error message on failure.
+fn run(call: impl FnOnce() -> Result<(), Error>) -> AegisStatus {
+    let err = match call() {
+        Ok(()) => return AegisStatus::Ok,
+        Err(err) => err,
+    };
+    let message = CString::new(err.to_string().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+    match err {
+        Error::Core(err) => AegisStatus::from(&err),
+        Error::Argument(_) => AegisStatus::InvalidArgument,
+    }
+}
+
+/// Product ID and tier of a payload as C strings, for borrowed accessors.
+struct PayloadStrings {
+    product_id: CString,
+    tier: CString,
+}
+
+impl PayloadStrings {
+    fn new(payload: &LicensePayload) -> Result<Self, AegisError> {
+        let c_string = |field: &str, value: &str| {
+            CString::new(value).map_err(|_| AegisError::parse_field(field, "contains a NUL byte"))
+        };
+        Ok(Self {
+            product_id: c_string("pid", &payload.product_id)?,
+            tier: c_string("tier", &payload.tier)?,
+        })
+    }
+}
+
+fn null(name: &str) -> Error {
+    Error::Argument(format!("{} is null", name))
+}
+
+unsafe fn handle<'a, T>(ptr: *const T, name: &str) -> Result<&'a T, Error> {
+    ptr.as_ref().ok_or_else(|| null(name))
+}
+
+unsafe fn handle_mut<'a, T>(ptr: *mut T, name: &str) -> Result<&'a mut T, Error> {
+    ptr.as_mut().ok_or_else(|| null(name))
+}
+
+unsafe fn bytes<'a>(ptr: *const u8, len: usize, name: &str) -> Result<&'a [u8], Error> {
+    if ptr.is_null() {
+        return Err(null(name));
+    }
+    Ok(std::slice::from_raw_parts(ptr, len))
+}
+
+unsafe fn array<const N: usize>(ptr: *const u8, name: &str) -> Result<[u8; N], Error> {
+    Ok(*ptr.cast::<[u8; N]>().as_ref().ok_or_else(|| null(name))?)
+}
+
+unsafe fn string<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, Error> {
+    if ptr.is_null() {
+        return Err(null(name));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| Error::Argument(format!("{} is not UTF-8", name)))
+}
+
+/// Copy `value` to the `N`-byte buffer at `out`.
+unsafe fn write<const N: usize>(out: *mut u8, value: &[u8; N], name: &str) -> Result<(), Error> {
+    if out.is_null() {
+        return Err(null(name));
+    }
+    ptr::copy_nonoverlapping(value.as_ptr(), out, N);
+    Ok(())
+}
+
+/// Move `value` into a new handle stored at `out`.
+unsafe fn put<T>(out: *mut *mut T, value: T) -> Result<(), Error> {
+    if out.is_null() {
+        return Err(null("out"));
+    }
+    *out = Box::into_raw(Box::new(value));
+    Ok(())
+}
+
+unsafe fn free<T>(ptr: *mut T) {
+    if !ptr.is_null() {
+        drop(Box::from_raw(ptr));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_matches_numeric_codes() {
+        let errors = [
+            AegisError::parse("x"),
+            AegisError::UnsupportedVersion,
+            AegisError::CertSignature,
+            AegisError::CertExpired,
+            AegisError::PopSignature,
+            AegisError::ClockRollback,
+            AegisError::Key(String::new()),
+            AegisError::Crypto(String::new()),
+            AegisError::CertNotYetValid { issued_at: 0 },
+            AegisError::ProductMismatch {
+                expected: String::new(),
+                found: String::new(),
+            },
+            AegisError::Revoked,
+            AegisError::PolicyViolation(String::new()),
+            AegisError::Payload(String::new()),
+            AegisError::Armor(String::new()),
+            AegisError::LicenseKey(String::new()),
+            AegisError::OfflineGraceExpired { days: 0 },
+            AegisError::PqKeyRequired,
+            AegisError::Clock(String::new()),
+        ];
+        for err in &errors {
+            assert_eq!(AegisStatus::from(err) as u16, err.numeric_code());
+        }
+    }
+
+    #[test]
+    fn test_last_error() {
+        let status = run(|| Err(AegisError::Revoked.into()));
+        assert_eq!(status, AegisStatus::Revoked);
+        let message = unsafe { CStr::from_ptr(aegis_last_error()) };
+        assert_eq!(
+            message.to_str().unwrap(),
+            "E_REVOKED: License has been revoked"
+        );
+
+        assert_eq!(run(|| Err(null("cert"))), AegisStatus::InvalidArgument);
+        let message = unsafe { CStr::from_ptr(aegis_last_error()) };
+        assert_eq!(
+            message.to_str().unwrap(),
+            "E_INVALID_ARGUMENT: cert is null"
+        );
+    }
+}
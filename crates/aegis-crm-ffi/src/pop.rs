@@ -0,0 +1,40 @@
+//! Proof-of-possession challenge, proof and check.
+
+use crate::keys::{AegisPrivateKey, AegisPublicKey};
+use crate::{array, handle, run, write, AegisStatus};
+use aegis_crm_core::pop::{prove_with_key, try_challenge, verify_with_key};
+
+/// Write a random `AEGIS_NONCE_LEN`-byte challenge to `out`.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_pop_challenge(out: *mut u8) -> AegisStatus {
+    run(|| write(out, &try_challenge()?, "out"))
+}
+
+/// Sign `nonce` with the user key, writing `AEGIS_POP_SIG_LEN` bytes to `out`.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_pop_prove(
+    user_key: *const AegisPrivateKey,
+    nonce: *const u8,
+    out: *mut u8,
+) -> AegisStatus {
+    run(|| {
+        let sig = prove_with_key(&handle(user_key, "user_key")?.0, &array(nonce, "nonce")?)?;
+        write(out, &sig, "out")
+    })
+}
+
+/// Check a PoP signature; `AEGIS_STATUS_POP_SIG` if it is invalid.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_pop_verify(
+    user_key: *const AegisPublicKey,
+    nonce: *const u8,
+    pop_sig: *const u8,
+) -> AegisStatus {
+    run(|| {
+        Ok(verify_with_key(
+            &handle(user_key, "user_key")?.0,
+            &array(nonce, "nonce")?,
+            &array(pop_sig, "pop_sig")?,
+        )?)
+    })
+}
@@ -0,0 +1,277 @@
+//! Verifier and verified license handles.
+
+use crate::cert::AegisCert;
+use crate::keys::AegisPublicKey;
+use crate::{array, free, handle, handle_mut, null, put, run, string, AegisStatus, PayloadStrings};
+use aegis_crm_core::verify::{MemoryStateStore, Verifier};
+use aegis_crm_core::verify::{RevocationList, VerifiedLicense, VerifierBuilder};
+use std::ffi::c_char;
+use std::ptr;
+
+/// Verifier configuration, turned into an `AegisVerifier` by `aegis_verifier_build`.
+#[derive(Default)]
+pub struct AegisVerifierBuilder {
+    builder: VerifierBuilder,
+    revocations: RevocationList,
+}
+
+/// Configured verifier.
+pub struct AegisVerifier(Verifier);
+
+/// License that passed verification; feature queries are only offered here.
+pub struct AegisLicense {
+    license: VerifiedLicense,
+    strings: PayloadStrings,
+}
+
+impl AegisVerifierBuilder {
+    fn update(&mut self, f: impl FnOnce(VerifierBuilder) -> VerifierBuilder) {
+        self.builder = f(std::mem::take(&mut self.builder));
+    }
+}
+
+/// New verifier configuration using the system clock.
+#[no_mangle]
+pub extern "C" fn aegis_verifier_builder_new() -> *mut AegisVerifierBuilder {
+    Box::into_raw(Box::default())
+}
+
+/// Trust a vendor public key (may be called repeatedly to accept rotated keys).
+#[no_mangle]
+pub unsafe extern "C" fn aegis_verifier_builder_trust(
+    builder: *mut AegisVerifierBuilder,
+    vendor_key: *const AegisPublicKey,
+) -> AegisStatus {
+    run(|| {
+        let key = handle(vendor_key, "vendor_key")?.0.clone();
+        handle_mut(builder, "builder")?.update(|b| b.trust(key));
+        Ok(())
+    })
+}
+
+/// Only accept certificates for `product_id`.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_verifier_builder_product(
+    builder: *mut AegisVerifierBuilder,
+    product_id: *const c_char,
+) -> AegisStatus {
+    run(|| {
+        let product_id = string(product_id, "product_id")?;
+        handle_mut(builder, "builder")?.update(|b| b.product(product_id));
+        Ok(())
+    })
+}
+
+/// Reject the `AEGIS_LICENSE_ID_LEN`-byte license ID (may be called repeatedly).
+#[no_mangle]
+pub unsafe extern "C" fn aegis_verifier_builder_revoke(
+    builder: *mut AegisVerifierBuilder,
+    license_id: *const u8,
+) -> AegisStatus {
+    run(|| {
+        let license_id = array(license_id, "license_id")?;
+        handle_mut(builder, "builder")?
+            .revocations
+            .revoke(license_id);
+        Ok(())
+    })
+}
+
+/// Verify at a fixed time instead of the system clock.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_verifier_builder_now(
+    builder: *mut AegisVerifierBuilder,
+    now_unix: u64,
+) -> AegisStatus {
+    run(|| {
+        handle_mut(builder, "builder")?.update(|b| b.now(now_unix));
+        Ok(())
+    })
+}
+
+/// Time of the last successful online check, enforcing the offline grace period.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_verifier_builder_last_online(
+    builder: *mut AegisVerifierBuilder,
+    last_online_unix: u64,
+) -> AegisStatus {
+    run(|| {
+        handle_mut(builder, "builder")?.update(|b| b.last_online(last_online_unix));
+        Ok(())
+    })
+}
+
+/// Reject verification times earlier than one this verifier has already seen.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_verifier_builder_detect_rollback(
+    builder: *mut AegisVerifierBuilder,
+) -> AegisStatus {
+    run(|| {
+        handle_mut(builder, "builder")?.update(|b| b.state_store(MemoryStateStore::new()));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn aegis_verifier_builder_free(builder: *mut AegisVerifierBuilder) {
+    free(builder)
+}
+
+/// Build the verifier. Always consumes `builder`, even on failure.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_verifier_build(
+    builder: *mut AegisVerifierBuilder,
+    out: *mut *mut AegisVerifier,
+) -> AegisStatus {
+    run(|| {
+        if builder.is_null() {
+            return Err(null("builder"));
+        }
+        let AegisVerifierBuilder {
+            builder,
+            revocations,
+        } = *Box::from_raw(builder);
+        put(
+            out,
+            AegisVerifier(builder.revocations(revocations).build()?),
+        )
+    })
+}
+
+/// Verify a decoded certificate; on success `out` receives the license.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_verifier_verify(
+    verifier: *const AegisVerifier,
+    cert: *const AegisCert,
+    out: *mut *mut AegisLicense,
+) -> AegisStatus {
+    run(|| {
+        let cert = handle(cert, "cert")?.cert.clone();
+        let license = handle(verifier, "verifier")?.0.verify_cert(cert)?;
+        let strings = PayloadStrings::new(license.payload())?;
+        put(out, AegisLicense { license, strings })
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn aegis_verifier_free(verifier: *mut AegisVerifier) {
+    free(verifier)
+}
+
+/// Check the holder's PoP signature (`AEGIS_POP_SIG_LEN` bytes) over `nonce`
+/// (`AEGIS_NONCE_LEN` bytes).
+#[no_mangle]
+pub unsafe extern "C" fn aegis_license_prove_possession(
+    license: *mut AegisLicense,
+    nonce: *const u8,
+    pop_sig: *const u8,
+) -> AegisStatus {
+    run(|| {
+        let (nonce, pop_sig) = (array(nonce, "nonce")?, array(pop_sig, "pop_sig")?);
+        let license = &mut handle_mut(license, "license")?.license;
+        *license = license.clone().prove_possession(&nonce, &pop_sig)?;
+        Ok(())
+    })
+}
+
+/// Whether `aegis_license_prove_possession` succeeded.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_license_pop_verified(license: *const AegisLicense) -> bool {
+    license
+        .as_ref()
+        .is_some_and(|license| license.license.pop_verified())
+}
+
+/// Whether the license grants `feature` (supports `ALL` and `prefix.*` grants).
+#[no_mangle]
+pub unsafe extern "C" fn aegis_license_has_feature(
+    license: *const AegisLicense,
+    feature: *const c_char,
+) -> bool {
+    match (handle(license, "license"), string(feature, "feature")) {
+        (Ok(license), Ok(feature)) => license.license.has_feature(feature),
+        _ => false,
+    }
+}
+
+/// Integer entitlement `name`; `false` if absent or not a non-negative integer.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_license_get_u64(
+    license: *const AegisLicense,
+    name: *const c_char,
+    out: *mut u64,
+) -> bool {
+    entitlement(license, name, out, VerifiedLicense::get_u64)
+}
+
+/// Integer entitlement `name`; `false` if absent or not an integer.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_license_get_i64(
+    license: *const AegisLicense,
+    name: *const c_char,
+    out: *mut i64,
+) -> bool {
+    entitlement(license, name, out, VerifiedLicense::get_i64)
+}
+
+/// Boolean entitlement `name`; `false` if absent or not a boolean.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_license_get_bool(
+    license: *const AegisLicense,
+    name: *const c_char,
+    out: *mut bool,
+) -> bool {
+    entitlement(license, name, out, VerifiedLicense::get_bool)
+}
+
+unsafe fn entitlement<T>(
+    license: *const AegisLicense,
+    name: *const c_char,
+    out: *mut T,
+    get: impl FnOnce(&VerifiedLicense, &str) -> Option<T>,
+) -> bool {
+    let (Ok(license), Ok(name), Ok(out)) = (
+        handle(license, "license"),
+        string(name, "name"),
+        handle_mut(out, "out"),
+    ) else {
+        return false;
+    };
+    get(&license.license, name)
+        .map(|value| *out = value)
+        .is_some()
+}
+
+/// Product ID, valid while `license` lives; null if `license` is null.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_license_product_id(license: *const AegisLicense) -> *const c_char {
+    license
+        .as_ref()
+        .map_or(ptr::null(), |license| license.strings.product_id.as_ptr())
+}
+
+/// Tier name, valid while `license` lives; null if `license` is null.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_license_tier(license: *const AegisLicense) -> *const c_char {
+    license
+        .as_ref()
+        .map_or(ptr::null(), |license| license.strings.tier.as_ptr())
+}
+
+/// Expiry in Unix seconds; `false` (leaving `out` untouched) for a perpetual license.
+#[no_mangle]
+pub unsafe extern "C" fn aegis_license_expiry(license: *const AegisLicense, out: *mut u64) -> bool {
+    let (Ok(license), Ok(out)) = (handle(license, "license"), handle_mut(out, "out")) else {
+        return false;
+    };
+    license
+        .license
+        .expiry()
+        .map(|expiry| *out = expiry)
+        .is_some()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn aegis_license_free(license: *mut AegisLicense) {
+    free(license)
+}
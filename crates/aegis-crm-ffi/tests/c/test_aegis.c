@@ -0,0 +1,178 @@
+/*
+ * Exercises the C API against the fixture certificate.
+ *
+ * Usage: test_aegis <vendor public key hex> <certificate base64>
+ * Built and run by tests/c_api.rs.
+ */
+
+#include "aegis.h"
+
+#include <stdio.h>
+#include <string.h>
+
+static int failures = 0;
+
+#define CHECK(cond)                                                   \
+    do {                                                              \
+        if (!(cond)) {                                                \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,    \
+                    __LINE__, #cond);                                 \
+            failures++;                                               \
+        }                                                             \
+    } while (0)
+
+#define CHECK_STATUS(call, expected)                                  \
+    do {                                                              \
+        AegisStatus status_ = (call);                                 \
+        if (status_ != (expected)) {                                  \
+            const char *error_ = aegis_last_error();                  \
+            fprintf(stderr, "%s:%d: %s returned %d (%s), expected %s\n", \
+                    __FILE__, __LINE__, #call, (int)status_,          \
+                    error_ ? error_ : "no message", #expected);       \
+            failures++;                                               \
+        }                                                             \
+    } while (0)
+
+static size_t from_hex(const char *hex, uint8_t *out, size_t cap) {
+    size_t len = strlen(hex) / 2;
+    for (size_t i = 0; i < len && i < cap; i++) {
+        unsigned int byte;
+        sscanf(hex + 2 * i, "%2x", &byte);
+        out[i] = (uint8_t)byte;
+    }
+    return len;
+}
+
+/* Fixture certificate: product example_product, perpetual, two features. */
+static void test_verify(const AegisPublicKey *vendor, const char *cert_b64) {
+    AegisCert *cert = NULL;
+    CHECK_STATUS(aegis_cert_decode((const uint8_t *)cert_b64, strlen(cert_b64), &cert),
+                 AEGIS_STATUS_OK);
+    if (cert == NULL) {
+        return;
+    }
+    CHECK(strcmp(aegis_cert_product_id(cert), "example_product") == 0);
+    CHECK(strcmp(aegis_cert_tier(cert), "lifetime_pro") == 0);
+    uint64_t expiry = 0;
+    CHECK(!aegis_cert_expiry(cert, &expiry));
+    CHECK_STATUS(aegis_cert_verify(cert, vendor, 1800000000), AEGIS_STATUS_OK);
+
+    AegisVerifierBuilder *builder = aegis_verifier_builder_new();
+    CHECK_STATUS(aegis_verifier_builder_trust(builder, vendor), AEGIS_STATUS_OK);
+    CHECK_STATUS(aegis_verifier_builder_product(builder, "example_product"), AEGIS_STATUS_OK);
+    CHECK_STATUS(aegis_verifier_builder_now(builder, 1800000000), AEGIS_STATUS_OK);
+    AegisVerifier *verifier = NULL;
+    CHECK_STATUS(aegis_verifier_build(builder, &verifier), AEGIS_STATUS_OK);
+
+    AegisLicense *license = NULL;
+    CHECK_STATUS(aegis_verifier_verify(verifier, cert, &license), AEGIS_STATUS_OK);
+    if (license == NULL) {
+        aegis_verifier_free(verifier);
+        aegis_cert_free(cert);
+        return;
+    }
+    CHECK(strcmp(aegis_license_product_id(license), "example_product") == 0);
+    CHECK(aegis_license_has_feature(license, "base_access"));
+    CHECK(aegis_license_has_feature(license, "premium_features"));
+    CHECK(!aegis_license_has_feature(license, "export"));
+    uint64_t value = 0;
+    CHECK(!aegis_license_get_u64(license, "max_projects", &value));
+    CHECK(!aegis_license_pop_verified(license));
+
+    /* The certificate is bound to another user's key. */
+    uint8_t nonce[AEGIS_NONCE_LEN] = {0};
+    uint8_t sig[AEGIS_POP_SIG_LEN] = {0};
+    CHECK_STATUS(aegis_license_prove_possession(license, nonce, sig), AEGIS_STATUS_POP_SIG);
+    aegis_license_free(license);
+    aegis_verifier_free(verifier);
+
+    builder = aegis_verifier_builder_new();
+    aegis_verifier_builder_trust(builder, vendor);
+    aegis_verifier_builder_product(builder, "other_product");
+    CHECK_STATUS(aegis_verifier_build(builder, &verifier), AEGIS_STATUS_OK);
+    license = NULL;
+    CHECK_STATUS(aegis_verifier_verify(verifier, cert, &license),
+                 AEGIS_STATUS_PRODUCT_MISMATCH);
+    CHECK(license == NULL);
+    CHECK(strncmp(aegis_last_error(), "E_PRODUCT_MISMATCH", 18) == 0);
+    aegis_verifier_free(verifier);
+
+    aegis_cert_free(cert);
+}
+
+static void test_errors(const AegisPublicKey *vendor) {
+    AegisCert *cert = NULL;
+    const char *garbage = "not a certificate";
+    CHECK_STATUS(aegis_cert_decode((const uint8_t *)garbage, strlen(garbage), &cert),
+                 AEGIS_STATUS_CERT_PARSE);
+    CHECK(cert == NULL);
+    CHECK_STATUS(aegis_cert_decode(NULL, 0, &cert), AEGIS_STATUS_INVALID_ARGUMENT);
+    CHECK_STATUS(aegis_cert_verify(NULL, vendor, 0), AEGIS_STATUS_INVALID_ARGUMENT);
+
+    AegisPublicKey *key = NULL;
+    uint8_t short_key[3] = {2, 0, 0};
+    CHECK_STATUS(aegis_public_key_new(NULL, short_key, sizeof short_key, &key), AEGIS_STATUS_KEY);
+    CHECK_STATUS(aegis_public_key_new("rsa", short_key, sizeof short_key, &key),
+                 AEGIS_STATUS_CRYPTO);
+
+    /* A verifier needs at least one trusted key. */
+    AegisVerifier *verifier = NULL;
+    CHECK_STATUS(aegis_verifier_build(aegis_verifier_builder_new(), &verifier), AEGIS_STATUS_KEY);
+}
+
+static void test_pop(void) {
+    AegisPrivateKey *user = NULL;
+    AegisPublicKey *user_pub = NULL;
+    CHECK_STATUS(aegis_private_key_generate("ed25519", &user), AEGIS_STATUS_OK);
+    CHECK_STATUS(aegis_private_key_public_key(user, &user_pub), AEGIS_STATUS_OK);
+
+    uint8_t pubkey[AEGIS_PUBLIC_KEY_MAX_LEN];
+    size_t pubkey_len = 0;
+    CHECK_STATUS(aegis_public_key_bytes(user_pub, pubkey, &pubkey_len), AEGIS_STATUS_OK);
+    CHECK(pubkey_len == 32);
+
+    uint8_t nonce[AEGIS_NONCE_LEN];
+    uint8_t sig[AEGIS_POP_SIG_LEN];
+    CHECK_STATUS(aegis_pop_challenge(nonce), AEGIS_STATUS_OK);
+    CHECK_STATUS(aegis_pop_prove(user, nonce, sig), AEGIS_STATUS_OK);
+    CHECK_STATUS(aegis_pop_verify(user_pub, nonce, sig), AEGIS_STATUS_OK);
+    nonce[0] ^= 1;
+    CHECK_STATUS(aegis_pop_verify(user_pub, nonce, sig), AEGIS_STATUS_POP_SIG);
+
+    /* Round-trip the stored key bytes. */
+    uint8_t secret[AEGIS_PRIVATE_KEY_LEN];
+    AegisPrivateKey *restored = NULL;
+    CHECK_STATUS(aegis_private_key_bytes(user, secret), AEGIS_STATUS_OK);
+    CHECK_STATUS(aegis_private_key_new("ed25519", secret, &restored), AEGIS_STATUS_OK);
+    CHECK_STATUS(aegis_pop_prove(restored, nonce, sig), AEGIS_STATUS_OK);
+    CHECK_STATUS(aegis_pop_verify(user_pub, nonce, sig), AEGIS_STATUS_OK);
+
+    aegis_private_key_free(restored);
+    aegis_public_key_free(user_pub);
+    aegis_private_key_free(user);
+}
+
+int main(int argc, char **argv) {
+    if (argc != 3) {
+        fprintf(stderr, "usage: %s <vendor public key hex> <certificate base64>\n", argv[0]);
+        return 2;
+    }
+
+    uint8_t vendor_bytes[AEGIS_PUBLIC_KEY_MAX_LEN];
+    size_t vendor_len = from_hex(argv[1], vendor_bytes, sizeof vendor_bytes);
+    AegisPublicKey *vendor = NULL;
+    CHECK_STATUS(aegis_public_key_new("secp256k1", vendor_bytes, vendor_len, &vendor),
+                 AEGIS_STATUS_OK);
+
+    test_verify(vendor, argv[2]);
+    test_errors(vendor);
+    test_pop();
+    aegis_public_key_free(vendor);
+
+    if (failures > 0) {
+        fprintf(stderr, "%d check(s) failed\n", failures);
+        return 1;
+    }
+    printf("ok\n");
+    return 0;
+}
@@ -0,0 +1,51 @@
+//! Compiles `tests/c/test_aegis.c` against the generated header and the
+//! shared library, then runs it on the fixture certificate.
+
+#![cfg(unix)]
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn test_c_program() {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // Integration tests and the library both live in `target/<profile>/deps`
+    let exe = env::current_exe().unwrap();
+    let lib_dir = exe.parent().unwrap();
+    let program = Path::new(env!("CARGO_TARGET_TMPDIR")).join("test_aegis");
+
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(&cc)
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg(crate_dir.join("tests/c/test_aegis.c"))
+        .arg("-I")
+        .arg(crate_dir.join("include"))
+        .arg("-L")
+        .arg(lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-laegis_crm_ffi")
+        .arg("-o")
+        .arg(&program)
+        .status()
+        .unwrap_or_else(|e| panic!("failed to run {}: {}", cc, e));
+    assert!(status.success(), "C test program failed to compile");
+
+    let fixtures = crate_dir.join("../../fixtures");
+    let vendor_pub = fs::read_to_string(fixtures.join("vendor_pub.hex")).unwrap();
+    let cert = fs::read_to_string(fixtures.join("license.cert.base64")).unwrap();
+    let output = Command::new(&program)
+        .arg(vendor_pub.trim())
+        .arg(cert.trim())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "C test program failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+}
@@ -0,0 +1,148 @@
+//! Python exceptions for [`aegis_crm_core::AegisError`].
+//!
+//! Every exception derives from `AegisError` and carries the stable `code`
+//! (e.g. `E_CERT_SIG`) and `numeric_code` attributes.
+
+use aegis_crm_core::AegisError as CoreError;
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+
+create_exception!(
+    aegis_crm,
+    AegisError,
+    PyException,
+    "Base class of all Aegis errors; `code` and `numeric_code` give the stable error code."
+);
+create_exception!(aegis_crm, CertParseError, AegisError, "E_CERT_PARSE");
+create_exception!(
+    aegis_crm,
+    UnsupportedVersionError,
+    AegisError,
+    "E_UNSUPPORTED_VER"
+);
+create_exception!(aegis_crm, CertSignatureError, AegisError, "E_CERT_SIG");
+create_exception!(aegis_crm, CertExpiredError, AegisError, "E_CERT_EXPIRED");
+create_exception!(aegis_crm, PopSignatureError, AegisError, "E_POP_SIG");
+create_exception!(
+    aegis_crm,
+    ClockRollbackError,
+    AegisError,
+    "E_CLOCK_ROLLBACK"
+);
+create_exception!(aegis_crm, InvalidKeyError, AegisError, "E_KEY");
+create_exception!(aegis_crm, CryptoError, AegisError, "E_CRYPTO");
+create_exception!(
+    aegis_crm,
+    CertNotYetValidError,
+    AegisError,
+    "E_CERT_NOT_YET_VALID"
+);
+create_exception!(
+    aegis_crm,
+    ProductMismatchError,
+    AegisError,
+    "E_PRODUCT_MISMATCH"
+);
+create_exception!(aegis_crm, RevokedError, AegisError, "E_REVOKED");
+create_exception!(
+    aegis_crm,
+    PolicyViolationError,
+    AegisError,
+    "E_POLICY_VIOLATION"
+);
+create_exception!(aegis_crm, PayloadError, AegisError, "E_PAYLOAD");
+create_exception!(aegis_crm, ArmorError, AegisError, "E_ARMOR");
+create_exception!(aegis_crm, LicenseKeyError, AegisError, "E_LICENSE_KEY");
+create_exception!(aegis_crm, OfflineGraceError, AegisError, "E_OFFLINE_GRACE");
+create_exception!(
+    aegis_crm,
+    PqKeyRequiredError,
+    AegisError,
+    "E_PQ_KEY_REQUIRED"
+);
+create_exception!(aegis_crm, ClockError, AegisError, "E_CLOCK");
+
+/// Core error on its way to Python.
+pub struct Error(pub CoreError);
+
+impl From<CoreError> for Error {
+    fn from(err: CoreError) -> Self {
+        Error(err)
+    }
+}
+
+impl From<Error> for PyErr {
+    fn from(Error(err): Error) -> Self {
+        let (code, numeric_code, message) = (err.code(), err.numeric_code(), err.to_string());
+        let mut field = None;
+        let py_err = match err {
+            CoreError::CertParse { field: f, .. } => {
+                field = f;
+                CertParseError::new_err(message)
+            }
+            CoreError::UnsupportedVersion => UnsupportedVersionError::new_err(message),
+            CoreError::CertSignature => CertSignatureError::new_err(message),
+            CoreError::CertExpired => CertExpiredError::new_err(message),
+            CoreError::CertNotYetValid { .. } => CertNotYetValidError::new_err(message),
+            CoreError::PopSignature => PopSignatureError::new_err(message),
+            CoreError::ProductMismatch { .. } => ProductMismatchError::new_err(message),
+            CoreError::Revoked => RevokedError::new_err(message),
+            CoreError::OfflineGraceExpired { .. } => OfflineGraceError::new_err(message),
+            CoreError::ClockRollback => ClockRollbackError::new_err(message),
+            CoreError::Payload(_) => PayloadError::new_err(message),
+            CoreError::PolicyViolation(_) => PolicyViolationError::new_err(message),
+            CoreError::Key(_) => InvalidKeyError::new_err(message),
+            CoreError::Armor(_) => ArmorError::new_err(message),
+            CoreError::LicenseKey(_) => LicenseKeyError::new_err(message),
+            CoreError::Crypto(_) => CryptoError::new_err(message),
+            CoreError::PqKeyRequired => PqKeyRequiredError::new_err(message),
+            CoreError::Clock(_) => ClockError::new_err(message),
+        };
+        Python::attach(|py| {
+            let value = py_err.value(py);
+            let attrs = value
+                .setattr("code", code)
+                .and_then(|()| value.setattr("numeric_code", numeric_code))
+                .and_then(|()| value.setattr("field", field));
+            attrs.map(|()| py_err.clone_ref(py)).unwrap_or_else(|e| e)
+        })
+    }
+}
+
+/// Register the exception classes on the module.
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("AegisError", py.get_type::<AegisError>())?;
+    m.add("CertParseError", py.get_type::<CertParseError>())?;
+    m.add(
+        "UnsupportedVersionError",
+        py.get_type::<UnsupportedVersionError>(),
+    )?;
+    m.add("CertSignatureError", py.get_type::<CertSignatureError>())?;
+    m.add("CertExpiredError", py.get_type::<CertExpiredError>())?;
+    m.add("PopSignatureError", py.get_type::<PopSignatureError>())?;
+    m.add("ClockRollbackError", py.get_type::<ClockRollbackError>())?;
+    m.add("InvalidKeyError", py.get_type::<InvalidKeyError>())?;
+    m.add("CryptoError", py.get_type::<CryptoError>())?;
+    m.add(
+        "CertNotYetValidError",
+        py.get_type::<CertNotYetValidError>(),
+    )?;
+    m.add(
+        "ProductMismatchError",
+        py.get_type::<ProductMismatchError>(),
+    )?;
+    m.add("RevokedError", py.get_type::<RevokedError>())?;
+    m.add(
+        "PolicyViolationError",
+        py.get_type::<PolicyViolationError>(),
+    )?;
+    m.add("PayloadError", py.get_type::<PayloadError>())?;
+    m.add("ArmorError", py.get_type::<ArmorError>())?;
+    m.add("LicenseKeyError", py.get_type::<LicenseKeyError>())?;
+    m.add("OfflineGraceError", py.get_type::<OfflineGraceError>())?;
+    m.add("PqKeyRequiredError", py.get_type::<PqKeyRequiredError>())?;
+    m.add("ClockError", py.get_type::<ClockError>())?;
+    Ok(())
+}
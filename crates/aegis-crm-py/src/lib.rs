@@ -0,0 +1,314 @@
+//! Python bindings for vendor-side issuance scripting.
+//!
+//! The `aegis_crm` module covers key generation, issuance from a payload dict,
+//! certificate encoding, verification and proof-of-possession. Keys, nonces
+//! and signatures are `bytes`; algorithms are named as in the CLI
+//! (`secp256k1` by default). Failures raise a subclass of `AegisError` whose
+//! `code` and `numeric_code` are the stable error codes. Times are Unix
+//! seconds and default to the system clock.
+
+mod errors;
+mod payload;
+
+use aegis_crm_core::armor::{decode_cert_auto, encode_cert_armored};
+use aegis_crm_core::cert::{self, LicenseCert};
+use aegis_crm_core::clock::{Clock, SystemClock};
+use aegis_crm_core::crypto::SignatureAlgorithm;
+use aegis_crm_core::keys::{try_keygen, PrivateKey, PublicKey};
+use aegis_crm_core::policy::PolicySet;
+use aegis_crm_core::{pop, AegisError};
+use errors::Error;
+use payload::PayloadDict;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyString};
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Decoded license certificate, as returned by `issue_cert` and `decode_cert`.
+#[pyclass(name = "LicenseCert", module = "aegis_crm", frozen)]
+pub struct PyLicenseCert(LicenseCert);
+
+#[pymethods]
+impl PyLicenseCert {
+    #[getter]
+    fn product_id(&self) -> &str {
+        &self.0.payload.product_id
+    }
+
+    #[getter]
+    fn license_id<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.0.payload.license_id)
+    }
+
+    #[getter]
+    fn tier(&self) -> &str {
+        &self.0.payload.tier
+    }
+
+    /// Vendor signature algorithm
+    #[getter]
+    fn alg(&self) -> String {
+        self.0.payload.alg.to_string()
+    }
+
+    #[getter]
+    fn issued_at(&self) -> u64 {
+        self.0.payload.issued_at
+    }
+
+    /// Expiry, or `None` for a perpetual license
+    #[getter]
+    fn expiry(&self) -> Option<u64> {
+        self.0.payload.expiry
+    }
+
+    /// Granted feature names
+    #[getter]
+    fn features(&self) -> Vec<String> {
+        let features = &self.0.payload.features;
+        features.iter().map(|f| f.name.clone()).collect()
+    }
+
+    #[getter]
+    fn user_pubkey<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.0.payload.user_pubkey)
+    }
+
+    /// User key algorithm for PoP
+    #[getter]
+    fn user_alg(&self) -> String {
+        self.0.payload.user_alg.to_string()
+    }
+
+    /// Whether `feature` is granted at `now` (supports `ALL` and `prefix.*`
+    /// grants and honours per-feature windows)
+    #[pyo3(signature = (feature, *, now = None))]
+    fn has_feature(&self, feature: &str, now: Option<u64>) -> Result<bool> {
+        Ok(self.0.payload.has_feature_at(feature, now_or_clock(now)?))
+    }
+
+    /// All payload fields as a dict, in the certificate's wire form
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let json = serde_json::to_string(&self.0.payload)
+            .map_err(|e| Error(AegisError::parse(e.to_string())))?;
+        py.import("json")?.call_method1("loads", (json,))
+    }
+
+    fn __repr__(&self) -> String {
+        let payload = &self.0.payload;
+        format!(
+            "LicenseCert(product_id={:?}, tier={:?}, license_id='{}')",
+            payload.product_id,
+            payload.tier,
+            hex::encode(payload.license_id)
+        )
+    }
+}
+
+/// Generate a keypair, returning `(private_key, public_key)`.
+#[pyfunction]
+#[pyo3(signature = (alg = "secp256k1"))]
+fn keygen<'py>(py: Python<'py>, alg: &str) -> Result<(Bound<'py, PyBytes>, Bound<'py, PyBytes>)> {
+    let keypair = try_keygen(alg.parse()?)?;
+    Ok((
+        PyBytes::new(py, &keypair.privkey.bytes),
+        PyBytes::new(py, &keypair.pubkey.bytes),
+    ))
+}
+
+/// Derive the public key of a private key.
+#[pyfunction]
+#[pyo3(signature = (private_key, alg = "secp256k1"))]
+fn public_key<'py>(py: Python<'py>, private_key: &[u8], alg: &str) -> Result<Bound<'py, PyBytes>> {
+    let key = private(alg, private_key, "private key")?.public_key()?;
+    Ok(PyBytes::new(py, &key.bytes))
+}
+
+/// Issue a certificate for `user_pubkey` from a payload dict.
+///
+/// The payload has the shape of an `aegis issue --payload` JSON file. `policy`
+/// is a tier policy dict (the TOML policy file's structure), defaulting to the
+/// built-in policy. The license ID is random unless given.
+#[pyfunction]
+#[pyo3(signature = (
+    vendor_privkey,
+    user_pubkey,
+    payload,
+    *,
+    alg = "secp256k1",
+    user_alg = "secp256k1",
+    policy = None,
+    license_id = None,
+    now = None,
+))]
+#[allow(clippy::too_many_arguments)]
+fn issue_cert(
+    vendor_privkey: &[u8],
+    user_pubkey: &[u8],
+    payload: &Bound<'_, PyDict>,
+    alg: &str,
+    user_alg: &str,
+    policy: Option<&Bound<'_, PyDict>>,
+    license_id: Option<&[u8]>,
+    now: Option<u64>,
+) -> PyResult<PyLicenseCert> {
+    let vendor_key = private(alg, vendor_privkey, "vendor private key")?;
+    let user_key = PublicKey::new(parse_alg(user_alg)?, user_pubkey).map_err(Error)?;
+    let payload = PayloadDict::from_json(&to_json(payload)?).map_err(Error)?;
+    let policy = match policy {
+        Some(policy) => serde_json::from_str(&to_json(policy)?).map_err(|e| {
+            Error(AegisError::PolicyViolation(format!(
+                "invalid policy: {}",
+                e
+            )))
+        })?,
+        None => PolicySet::builtin(),
+    };
+    let license_id = match license_id {
+        Some(id) => id.try_into().map_err(|_| {
+            Error(AegisError::Payload(format!(
+                "license_id must be 32 bytes, got {}",
+                id.len()
+            )))
+        })?,
+        None => random_license_id()?,
+    };
+
+    let payload = payload
+        .into_payload(&user_key, license_id, now_or_clock(now)?)
+        .map_err(Error)?;
+    let cert = cert::issue_cert_with_policy(&vendor_key, payload, &policy).map_err(Error)?;
+    Ok(PyLicenseCert(cert))
+}
+
+/// Encode a certificate as CBOR `bytes`, or as an ASCII-armored `str`.
+#[pyfunction]
+#[pyo3(signature = (cert, *, armored = false))]
+fn encode_cert<'py>(
+    py: Python<'py>,
+    cert: &PyLicenseCert,
+    armored: bool,
+) -> Result<Bound<'py, PyAny>> {
+    Ok(if armored {
+        PyString::new(py, &encode_cert_armored(&cert.0)?).into_any()
+    } else {
+        PyBytes::new(py, &cert::encode_cert(&cert.0)?).into_any()
+    })
+}
+
+/// Decode a certificate given as CBOR, base64 or ASCII armor (`bytes` or `str`).
+#[pyfunction]
+fn decode_cert(data: &Bound<'_, PyAny>) -> PyResult<PyLicenseCert> {
+    let cert = match data.cast::<PyString>() {
+        Ok(text) => decode_cert_auto(text.to_cow()?.as_bytes()),
+        Err(_) => decode_cert_auto(data.extract::<&[u8]>()?),
+    };
+    Ok(PyLicenseCert(cert.map_err(Error)?))
+}
+
+/// Verify the vendor signature and expiry of a certificate.
+#[pyfunction]
+#[pyo3(signature = (vendor_pubkey, cert, *, alg = "secp256k1", now = None))]
+fn verify_cert(
+    vendor_pubkey: &[u8],
+    cert: &PyLicenseCert,
+    alg: &str,
+    now: Option<u64>,
+) -> Result<()> {
+    let vendor_key = PublicKey::new(alg.parse()?, vendor_pubkey)?;
+    Ok(cert::verify_cert_with_key(
+        &vendor_key,
+        &cert.0,
+        now_or_clock(now)?,
+    )?)
+}
+
+/// Random 32-byte challenge nonce.
+#[pyfunction]
+fn challenge(py: Python<'_>) -> Result<Bound<'_, PyBytes>> {
+    Ok(PyBytes::new(py, &pop::try_challenge()?))
+}
+
+/// Sign `nonce` with the user's private key.
+#[pyfunction]
+#[pyo3(signature = (user_privkey, nonce, *, alg = "secp256k1"))]
+fn pop_prove<'py>(
+    py: Python<'py>,
+    user_privkey: &[u8],
+    nonce: &[u8],
+    alg: &str,
+) -> Result<Bound<'py, PyBytes>> {
+    let user_key = private(alg, user_privkey, "user private key")?;
+    let sig = pop::prove_with_key(&user_key, &array(nonce, "nonce")?)?;
+    Ok(PyBytes::new(py, &sig))
+}
+
+/// Check a PoP signature, raising `PopSignatureError` if it is invalid.
+#[pyfunction]
+#[pyo3(signature = (user_pubkey, nonce, pop_sig, *, alg = "secp256k1"))]
+fn pop_verify(user_pubkey: &[u8], nonce: &[u8], pop_sig: &[u8], alg: &str) -> Result<()> {
+    let user_key = PublicKey::new(alg.parse()?, user_pubkey)?;
+    let pop_sig = pop_sig.try_into().map_err(|_| AegisError::PopSignature)?;
+    Ok(pop::verify_with_key(
+        &user_key,
+        &array(nonce, "nonce")?,
+        &pop_sig,
+    )?)
+}
+
+fn parse_alg(alg: &str) -> Result<SignatureAlgorithm> {
+    Ok(alg.parse()?)
+}
+
+fn private(alg: &str, bytes: &[u8], what: &str) -> Result<PrivateKey> {
+    let key = PrivateKey::new(parse_alg(alg)?, array(bytes, what)?);
+    key.public_key()?;
+    Ok(key)
+}
+
+fn array<const N: usize>(bytes: &[u8], what: &str) -> Result<[u8; N]> {
+    bytes.try_into().map_err(|_| {
+        Error(AegisError::Key(format!(
+            "{} must be {} bytes, got {}",
+            what,
+            N,
+            bytes.len()
+        )))
+    })
+}
+
+/// Issuance or verification time: `now` if given, else the system clock.
+fn now_or_clock(now: Option<u64>) -> Result<u64> {
+    match now {
+        Some(now) => Ok(now),
+        None => Ok(SystemClock.now_unix()?),
+    }
+}
+
+fn random_license_id() -> Result<[u8; 32]> {
+    let mut license_id = [0u8; 32];
+    getrandom::getrandom(&mut license_id)
+        .map_err(|e| AegisError::Crypto(format!("Failed to generate random license ID: {}", e)))?;
+    Ok(license_id)
+}
+
+/// JSON text of a Python dict, via the standard `json` module.
+fn to_json(dict: &Bound<'_, PyDict>) -> PyResult<String> {
+    let json = dict.py().import("json")?;
+    json.call_method1("dumps", (dict,))?.extract()
+}
+
+#[pymodule]
+fn aegis_crm(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyLicenseCert>()?;
+    m.add_function(wrap_pyfunction!(keygen, m)?)?;
+    m.add_function(wrap_pyfunction!(public_key, m)?)?;
+    m.add_function(wrap_pyfunction!(issue_cert, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_cert, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_cert, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_cert, m)?)?;
+    m.add_function(wrap_pyfunction!(challenge, m)?)?;
+    m.add_function(wrap_pyfunction!(pop_prove, m)?)?;
+    m.add_function(wrap_pyfunction!(pop_verify, m)?)?;
+    errors::register(m)
+}
@@ -0,0 +1,128 @@
+//! License payload dicts.
+//!
+//! A payload dict has the same shape as the `aegis issue --payload` JSON file,
+//! plus an optional `product_id` (defaulting to the tier, as in the CLI).
+
+use aegis_crm_core::cert::{
+    Entitlement, FeatureGrant, LicensePayload, Limits, MetaValue, Metadata, PROTOCOL_VERSION,
+};
+use aegis_crm_core::features::is_valid_pattern;
+use aegis_crm_core::keys::PublicKey;
+use aegis_crm_core::AegisError;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Feature entry: a bare name, or a dict with its own validity window
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum FeatureDict {
+    Name(String),
+    Windowed {
+        name: String,
+        not_before: Option<u64>,
+        expires_at: Option<u64>,
+    },
+}
+
+/// License limits
+#[derive(Debug, Deserialize)]
+struct LimitsDict {
+    seat_max: Option<u32>,
+    offline_grace_days: Option<u32>,
+}
+
+/// Metadata value: integer, string, or `{"hex": "..."}` for raw bytes
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum MetadataValueDict {
+    Int(i64),
+    Str(String),
+    Bytes {
+        #[serde(with = "hex::serde")]
+        hex: Vec<u8>,
+    },
+}
+
+/// License payload dict
+#[derive(Debug, Deserialize)]
+pub struct PayloadDict {
+    product_id: Option<String>,
+    tier: String,
+    expires_at: Option<u64>,
+    #[serde(default)]
+    features: Vec<FeatureDict>,
+    limits: Option<LimitsDict>,
+    #[serde(default)]
+    entitlements: BTreeMap<String, Entitlement>,
+    metadata: Option<BTreeMap<String, MetadataValueDict>>,
+}
+
+impl PayloadDict {
+    /// Parse the JSON form of a payload dict
+    pub fn from_json(json: &str) -> Result<Self, AegisError> {
+        serde_json::from_str(json).map_err(|e| AegisError::Payload(e.to_string()))
+    }
+
+    /// Build the core library payload
+    pub fn into_payload(
+        self,
+        user_key: &PublicKey,
+        license_id: [u8; 32],
+        issued_at: u64,
+    ) -> Result<LicensePayload, AegisError> {
+        if self.tier.trim().is_empty() {
+            return Err(AegisError::Payload("tier must not be empty".to_string()));
+        }
+        let features = self
+            .features
+            .into_iter()
+            .map(|feature| match feature {
+                FeatureDict::Name(name) => FeatureGrant::new(name),
+                FeatureDict::Windowed {
+                    name,
+                    not_before,
+                    expires_at,
+                } => FeatureGrant::new(name).with_window(not_before, expires_at),
+            })
+            .collect::<Vec<_>>();
+        if let Some(feature) = features.iter().find(|f| !is_valid_pattern(&f.name)) {
+            return Err(AegisError::Payload(format!(
+                "invalid feature name '{}'",
+                feature.name
+            )));
+        }
+        let metadata = self.metadata.map(|metadata| {
+            metadata
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        MetadataValueDict::Int(i) => MetaValue::Int(i),
+                        MetadataValueDict::Str(s) => MetaValue::Str(s),
+                        MetadataValueDict::Bytes { hex } => MetaValue::Bytes(hex),
+                    };
+                    (key, value)
+                })
+                .collect::<Metadata>()
+        });
+
+        Ok(LicensePayload {
+            version: PROTOCOL_VERSION,
+            alg: Default::default(),
+            pq_alg: None,
+            product_id: self.product_id.unwrap_or_else(|| self.tier.clone()),
+            license_id,
+            issued_at,
+            expiry: self.expires_at,
+            user_pubkey: user_key.bytes.clone(),
+            user_alg: user_key.alg,
+            tier: self.tier,
+            features,
+            limits: self.limits.map(|l| Limits {
+                seat_max: l.seat_max,
+                offline_grace_days: l.offline_grace_days,
+            }),
+            entitlements: self.entitlements,
+            metadata,
+        })
+    }
+}
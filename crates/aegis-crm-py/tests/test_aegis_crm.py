@@ -0,0 +1,172 @@
+"""Tests for the aegis_crm Python module (run against an installed wheel)."""
+
+import base64
+from pathlib import Path
+
+import pytest
+
+import aegis_crm
+
+FIXTURES = Path(__file__).resolve().parents[3] / "fixtures"
+NOW = 1_800_000_000
+
+PAYLOAD = {
+    "product_id": "my_app",
+    "tier": "pro",
+    "expires_at": NOW + 365 * 86400,
+    "features": ["export", {"name": "beta.reports", "expires_at": NOW + 30 * 86400}],
+    "limits": {"seat_max": 5},
+    "entitlements": {"max_projects": 10, "formats": ["pdf", "csv"]},
+    "metadata": {"customer": "acme", "order": 42, "blob": {"hex": "00ff"}},
+}
+
+
+@pytest.fixture
+def vendor():
+    return aegis_crm.keygen()
+
+
+@pytest.fixture
+def user():
+    return aegis_crm.keygen()
+
+
+@pytest.fixture
+def cert(vendor, user):
+    return aegis_crm.issue_cert(vendor[0], user[1], PAYLOAD, now=NOW)
+
+
+def test_keygen():
+    priv, pub = aegis_crm.keygen()
+    assert isinstance(priv, bytes) and len(priv) == 32
+    assert len(pub) == 33
+    assert aegis_crm.public_key(priv) == pub
+
+    priv, pub = aegis_crm.keygen("ed25519")
+    assert len(pub) == 32
+    assert aegis_crm.public_key(priv, "ed25519") == pub
+
+
+def test_issue_from_dict(cert, user):
+    assert cert.product_id == "my_app"
+    assert cert.tier == "pro"
+    assert cert.issued_at == NOW
+    assert cert.expiry == NOW + 365 * 86400
+    assert cert.features == ["export", "beta.reports"]
+    assert cert.has_feature("beta.reports", now=NOW)
+    assert not cert.has_feature("beta.reports", now=NOW + 31 * 86400)
+    assert cert.has_feature("export", now=NOW + 31 * 86400)
+    assert not cert.has_feature("admin", now=NOW)
+    assert cert.user_pubkey == user[1]
+    assert cert.alg == "secp256k1"
+    assert len(cert.license_id) == 32
+
+    fields = cert.to_dict()
+    assert fields["pid"] == "my_app"
+    assert fields["lim"] == {"seats": 5}
+    assert fields["ent"] == {"formats": ["pdf", "csv"], "max_projects": 10}
+    assert fields["meta"]["customer"] == "acme"
+
+
+def test_issue_defaults_product_to_tier(vendor, user):
+    cert = aegis_crm.issue_cert(vendor[0], user[1], {"tier": "basic"}, now=NOW)
+    assert cert.product_id == "basic"
+    assert cert.expiry is None
+    assert cert.features == []
+
+
+def test_issue_with_license_id_and_algorithms():
+    vendor_priv, vendor_pub = aegis_crm.keygen("ed25519")
+    user_pub = aegis_crm.keygen("p256")[1]
+    license_id = bytes(range(32))
+    cert = aegis_crm.issue_cert(
+        vendor_priv,
+        user_pub,
+        PAYLOAD,
+        alg="ed25519",
+        user_alg="p256",
+        license_id=license_id,
+        now=NOW,
+    )
+    assert cert.license_id == license_id
+    assert cert.alg == "ed25519"
+    assert cert.user_alg == "p256"
+    aegis_crm.verify_cert(vendor_pub, cert, alg="ed25519", now=NOW)
+
+
+def test_encode_decode_roundtrip(cert):
+    cbor = aegis_crm.encode_cert(cert)
+    assert isinstance(cbor, bytes)
+    armored = aegis_crm.encode_cert(cert, armored=True)
+    assert armored.startswith("-----BEGIN AEGIS LICENSE-----")
+
+    for data in (cbor, base64.b64encode(cbor), base64.b64encode(cbor).decode(), armored):
+        decoded = aegis_crm.decode_cert(data)
+        assert decoded.license_id == cert.license_id
+        assert aegis_crm.encode_cert(decoded) == cbor
+
+
+def test_verify(cert, vendor):
+    aegis_crm.verify_cert(vendor[1], cert, now=NOW + 1)
+
+    with pytest.raises(aegis_crm.CertExpiredError) as err:
+        aegis_crm.verify_cert(vendor[1], cert, now=NOW + 400 * 86400)
+    assert err.value.code == "E_CERT_EXPIRED"
+    assert err.value.numeric_code == 4
+
+    other_pub = aegis_crm.keygen()[1]
+    with pytest.raises(aegis_crm.CertSignatureError) as err:
+        aegis_crm.verify_cert(other_pub, cert, now=NOW)
+    assert err.value.code == "E_CERT_SIG"
+    assert isinstance(err.value, aegis_crm.AegisError)
+
+
+def test_verify_fixture():
+    vendor_pub = bytes.fromhex((FIXTURES / "vendor_pub.hex").read_text().strip())
+    cert = aegis_crm.decode_cert((FIXTURES / "license.cert.base64").read_text())
+    aegis_crm.verify_cert(vendor_pub, cert, now=NOW)
+    assert cert.product_id == "example_product"
+    assert cert.tier == "lifetime_pro"
+    assert cert.has_feature("base_access")
+
+
+def test_pop(cert, user):
+    nonce = aegis_crm.challenge()
+    assert len(nonce) == 32
+    sig = aegis_crm.pop_prove(user[0], nonce)
+    assert len(sig) == 64
+    aegis_crm.pop_verify(cert.user_pubkey, nonce, sig)
+
+    with pytest.raises(aegis_crm.PopSignatureError) as err:
+        aegis_crm.pop_verify(cert.user_pubkey, aegis_crm.challenge(), sig)
+    assert err.value.code == "E_POP_SIG"
+    assert err.value.numeric_code == 5
+
+
+def test_errors_carry_codes(vendor, user):
+    with pytest.raises(aegis_crm.CertParseError) as err:
+        aegis_crm.decode_cert(b"not a certificate")
+    assert err.value.code == "E_CERT_PARSE"
+    assert err.value.numeric_code == 1
+
+    with pytest.raises(aegis_crm.InvalidKeyError) as err:
+        aegis_crm.issue_cert(vendor[0][:31], user[1], PAYLOAD)
+    assert err.value.code == "E_KEY"
+
+    with pytest.raises(aegis_crm.CryptoError):
+        aegis_crm.keygen("rsa")
+
+    with pytest.raises(aegis_crm.PayloadError) as err:
+        aegis_crm.issue_cert(vendor[0], user[1], {"tier": "pro", "features": ["bad name"]})
+    assert err.value.code == "E_PAYLOAD"
+
+    with pytest.raises(aegis_crm.PayloadError):
+        aegis_crm.issue_cert(vendor[0], user[1], {"features": []})
+
+
+def test_policy_violation(vendor, user):
+    policy = {"tiers": {"pro": {"required": ["expires_at"]}}}
+    with pytest.raises(aegis_crm.PolicyViolationError) as err:
+        aegis_crm.issue_cert(vendor[0], user[1], {"tier": "pro"}, policy=policy, now=NOW)
+    assert err.value.code == "E_POLICY_VIOLATION"
+    assert err.value.numeric_code == 12
@@ -0,0 +1,189 @@
+//! WebAssembly bindings for in-browser license verification and PoP.
+//!
+//! Keys, certificates, nonces and signatures are passed as `Uint8Array`s.
+//! Keys are secp256k1 unless an algorithm is named as in the CLI (`p256`,
+//! `ed25519`, `schnorr`, ...). Certificates may be raw CBOR, base64 or ASCII
+//! armor. Verification does not throw on an invalid license: it returns a
+//! JSON [`VerifyResult`] carrying the stable error code. Times are Unix
+//! seconds and default to the browser clock.
+
+use aegis_crm_core::armor::decode_cert_auto;
+use aegis_crm_core::cert::{self, Entitlement, FeatureGrant, LicenseCert};
+use aegis_crm_core::crypto::SignatureAlgorithm;
+use aegis_crm_core::keys::{PrivateKey, PublicKey};
+use aegis_crm_core::{pop, verify, AegisError};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use wasm_bindgen::prelude::*;
+
+/// Decoded license fields, as returned by `decodeCert` and in [`VerifyResult`].
+#[derive(Debug, Serialize)]
+pub struct LicenseJson {
+    product_id: String,
+    license_id: String,
+    tier: String,
+    alg: String,
+    issued_at: u64,
+    expiry: Option<u64>,
+    features: Vec<FeatureGrant>,
+    entitlements: BTreeMap<String, Entitlement>,
+    user_pubkey: String,
+    user_alg: String,
+}
+
+impl From<&LicenseCert> for LicenseJson {
+    fn from(cert: &LicenseCert) -> Self {
+        let payload = &cert.payload;
+        Self {
+            product_id: payload.product_id.clone(),
+            license_id: hex::encode(payload.license_id),
+            tier: payload.tier.clone(),
+            alg: payload.alg.to_string(),
+            issued_at: payload.issued_at,
+            expiry: payload.expiry,
+            features: payload.features.clone(),
+            entitlements: payload.entitlements.clone(),
+            user_pubkey: hex::encode(&payload.user_pubkey),
+            user_alg: payload.user_alg.to_string(),
+        }
+    }
+}
+
+/// Outcome of `verifyCert` / `verifyLicense`; `license` is set only when valid.
+#[derive(Debug, Serialize)]
+pub struct VerifyResult {
+    valid: bool,
+    error_code: Option<&'static str>,
+    error: Option<String>,
+    license: Option<LicenseJson>,
+}
+
+/// Decode a certificate without verifying it, returning [`LicenseJson`].
+#[wasm_bindgen(js_name = decodeCert)]
+pub fn decode_cert(cert: &[u8]) -> Result<String, JsError> {
+    let cert = decode_cert_auto(cert).map_err(js_error)?;
+    to_json(&LicenseJson::from(&cert))
+}
+
+/// Verify the vendor signature and expiry of a certificate; `alg` is the
+/// vendor key algorithm.
+#[wasm_bindgen(js_name = verifyCert)]
+pub fn verify_cert(
+    vendor_pubkey: &[u8],
+    cert: &[u8],
+    now_unix: Option<f64>,
+    alg: Option<String>,
+) -> Result<String, JsError> {
+    verify_with(cert, |cert| {
+        let vendor_key = PublicKey::new(parse_alg(alg)?, vendor_pubkey)?;
+        cert::verify_cert_with_key(&vendor_key, cert, now(now_unix)?)
+    })
+}
+
+/// Verify a certificate and the user's proof-of-possession of its key;
+/// `alg` is the vendor key algorithm (the user's is taken from the certificate).
+#[wasm_bindgen(js_name = verifyLicense)]
+pub fn verify_license(
+    vendor_pubkey: &[u8],
+    cert: &[u8],
+    nonce: &[u8],
+    pop_sig: &[u8],
+    now_unix: Option<f64>,
+    alg: Option<String>,
+) -> Result<String, JsError> {
+    verify_with(cert, |cert| {
+        let vendor_key = PublicKey::new(parse_alg(alg)?, vendor_pubkey)?;
+        let nonce = array(nonce, "nonce")?;
+        let pop_sig = pop_sig.try_into().map_err(|_| AegisError::PopSignature)?;
+        verify::verify_license_with_key(&vendor_key, cert, now(now_unix)?, &nonce, &pop_sig)
+    })
+}
+
+/// Random 32-byte challenge nonce from `crypto.getRandomValues`.
+#[wasm_bindgen]
+pub fn challenge() -> Result<Vec<u8>, JsError> {
+    pop::try_challenge().map(Vec::from).map_err(js_error)
+}
+
+/// Sign `nonce` with the user's private key; `alg` is the user key algorithm.
+#[wasm_bindgen(js_name = popProve)]
+pub fn pop_prove(
+    user_privkey: &[u8],
+    nonce: &[u8],
+    alg: Option<String>,
+) -> Result<Vec<u8>, JsError> {
+    let user_key = PrivateKey::new(
+        parse_alg(alg).map_err(js_error)?,
+        array(user_privkey, "user private key").map_err(js_error)?,
+    );
+    let nonce = array(nonce, "nonce").map_err(js_error)?;
+    pop::prove_with_key(&user_key, &nonce)
+        .map(Vec::from)
+        .map_err(js_error)
+}
+
+/// Check a PoP signature; throws only if the key, nonce or `alg` is malformed.
+#[wasm_bindgen(js_name = popVerify)]
+pub fn pop_verify(
+    user_pubkey: &[u8],
+    nonce: &[u8],
+    pop_sig: &[u8],
+    alg: Option<String>,
+) -> Result<bool, JsError> {
+    let user_key =
+        PublicKey::new(parse_alg(alg).map_err(js_error)?, user_pubkey).map_err(js_error)?;
+    let nonce = array(nonce, "nonce").map_err(js_error)?;
+    let Ok(pop_sig) = pop_sig.try_into() else {
+        return Ok(false);
+    };
+    Ok(pop::verify_with_key(&user_key, &nonce, &pop_sig).is_ok())
+}
+
+fn verify_with(
+    cert: &[u8],
+    check: impl FnOnce(&LicenseCert) -> Result<(), AegisError>,
+) -> Result<String, JsError> {
+    let result = decode_cert_auto(cert).and_then(|cert| check(&cert).map(|()| cert));
+    to_json(&match result {
+        Ok(cert) => VerifyResult {
+            valid: true,
+            error_code: None,
+            error: None,
+            license: Some(LicenseJson::from(&cert)),
+        },
+        Err(err) => VerifyResult {
+            valid: false,
+            error_code: Some(err.code()),
+            error: Some(err.to_string()),
+            license: None,
+        },
+    })
+}
+
+/// Verification time: `now_unix` if given, else the browser clock.
+fn now(now_unix: Option<f64>) -> Result<u64, AegisError> {
+    let now = now_unix.unwrap_or_else(|| js_sys::Date::now() / 1000.0);
+    if !now.is_finite() || now < 0.0 {
+        return Err(AegisError::Clock(format!("invalid time: {}", now)));
+    }
+    Ok(now as u64)
+}
+
+/// Key algorithm named as in the CLI, secp256k1 by default.
+fn parse_alg(alg: Option<String>) -> Result<SignatureAlgorithm, AegisError> {
+    alg.map_or(Ok(SignatureAlgorithm::Secp256k1), |alg| alg.parse())
+}
+
+fn array<const N: usize>(bytes: &[u8], what: &str) -> Result<[u8; N], AegisError> {
+    bytes
+        .try_into()
+        .map_err(|_| AegisError::Key(format!("{} must be {} bytes, got {}", what, N, bytes.len())))
+}
+
+fn js_error(err: AegisError) -> JsError {
+    JsError::new(&format!("{}: {}", err.code(), err))
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, JsError> {
+    serde_json::to_string(value).map_err(|e| JsError::new(&e.to_string()))
+}
@@ -0,0 +1,145 @@
+//! Run with `wasm-pack test --node crates/aegis-crm-wasm`.
+
+#![cfg(target_arch = "wasm32")]
+
+use aegis_crm_core::cert::{
+    encode_cert, issue_cert, issue_cert_with_policy, LicensePayload, PROTOCOL_VERSION,
+};
+use aegis_crm_core::crypto::SignatureAlgorithm;
+use aegis_crm_core::keys::{
+    try_keygen, try_user_keygen, try_vendor_keygen, UserKeypair, VendorKeypair,
+};
+use aegis_crm_core::policy::PolicySet;
+use aegis_crm_wasm::{challenge, decode_cert, pop_prove, pop_verify, verify_cert, verify_license};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+const NOW: f64 = 1_800_000_000.0;
+
+fn payload(
+    user_pubkey: Vec<u8>,
+    user_alg: SignatureAlgorithm,
+    expiry: Option<u64>,
+) -> LicensePayload {
+    LicensePayload {
+        version: PROTOCOL_VERSION,
+        alg: SignatureAlgorithm::Secp256k1,
+        pq_alg: None,
+        product_id: "web_editor".to_string(),
+        license_id: [7u8; 32],
+        issued_at: 1_700_000_000,
+        expiry,
+        user_pubkey,
+        user_alg,
+        tier: "lifetime_pro".to_string(),
+        features: vec!["export".into()],
+        limits: None,
+        entitlements: BTreeMap::new(),
+        metadata: None,
+    }
+}
+
+fn issue(expiry: Option<u64>) -> (VendorKeypair, UserKeypair, Vec<u8>) {
+    let vendor = try_vendor_keygen().unwrap();
+    let user = try_user_keygen().unwrap();
+    let payload = payload(user.pubkey.to_vec(), SignatureAlgorithm::Secp256k1, expiry);
+    let cert = encode_cert(&issue_cert(&vendor.privkey, payload).unwrap()).unwrap();
+    (vendor, user, cert)
+}
+
+fn json(text: &str) -> Value {
+    serde_json::from_str(text).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn test_decode_cert() {
+    let (_, user, cert) = issue(None);
+    let license = json(&decode_cert(&cert).unwrap());
+    assert_eq!(license["product_id"], "web_editor");
+    assert_eq!(license["license_id"], hex::encode([7u8; 32]));
+    assert_eq!(license["user_pubkey"], hex::encode(user.pubkey));
+    assert_eq!(license["features"][0], "export");
+    assert!(decode_cert(b"not a certificate").is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_verify_cert() {
+    let (vendor, _, cert) = issue(Some(1_900_000_000));
+    let result = json(&verify_cert(&vendor.pubkey, &cert, Some(NOW), None).unwrap());
+    assert_eq!(result["valid"], true);
+    assert_eq!(result["license"]["tier"], "lifetime_pro");
+
+    let result = json(&verify_cert(&vendor.pubkey, &cert, Some(2_000_000_000.0), None).unwrap());
+    assert_eq!(result["valid"], false);
+    assert_eq!(result["error_code"], "E_CERT_EXPIRED");
+    assert!(result["license"].is_null());
+
+    let other = try_vendor_keygen().unwrap();
+    let result = json(&verify_cert(&other.pubkey, &cert, Some(NOW), None).unwrap());
+    assert_eq!(result["error_code"], "E_CERT_SIG");
+
+    let result = json(&verify_cert(&vendor.pubkey[..32], &cert, Some(NOW), None).unwrap());
+    assert_eq!(result["error_code"], "E_KEY");
+}
+
+#[wasm_bindgen_test]
+fn test_verify_cert_uses_browser_clock() {
+    let (vendor, _, cert) = issue(Some(1_000_000_000_000));
+    let result = json(&verify_cert(&vendor.pubkey, &cert, None, None).unwrap());
+    assert_eq!(result["valid"], true);
+}
+
+#[wasm_bindgen_test]
+fn test_pop_and_verify_license() {
+    let (vendor, user, cert) = issue(None);
+    let nonce = challenge().unwrap();
+    assert_eq!(nonce.len(), 32);
+    assert_ne!(nonce, challenge().unwrap());
+
+    let sig = pop_prove(&user.privkey, &nonce, None).unwrap();
+    assert!(pop_verify(&user.pubkey, &nonce, &sig, None).unwrap());
+    assert!(!pop_verify(&user.pubkey, &challenge().unwrap(), &sig, None).unwrap());
+    assert!(!pop_verify(&user.pubkey, &nonce, &sig[..63], None).unwrap());
+
+    let result =
+        json(&verify_license(&vendor.pubkey, &cert, &nonce, &sig, Some(NOW), None).unwrap());
+    assert_eq!(result["valid"], true);
+
+    let other = try_user_keygen().unwrap();
+    let sig = pop_prove(&other.privkey, &nonce, None).unwrap();
+    let result =
+        json(&verify_license(&vendor.pubkey, &cert, &nonce, &sig, Some(NOW), None).unwrap());
+    assert_eq!(result["error_code"], "E_POP_SIG");
+}
+
+#[wasm_bindgen_test]
+fn test_other_algorithms() {
+    let vendor = try_keygen(SignatureAlgorithm::P256).unwrap();
+    let user = try_keygen(SignatureAlgorithm::Ed25519).unwrap();
+    let payload = payload(user.pubkey.bytes.clone(), SignatureAlgorithm::Ed25519, None);
+    let cert = issue_cert_with_policy(&vendor.privkey, payload, &PolicySet::builtin()).unwrap();
+    let cert = encode_cert(&cert).unwrap();
+    let p256 = || Some("p256".to_string());
+    let ed25519 = || Some("ed25519".to_string());
+
+    let result = json(&verify_cert(&vendor.pubkey.bytes, &cert, Some(NOW), p256()).unwrap());
+    assert_eq!(result["valid"], true);
+    assert_eq!(result["license"]["alg"], "p256");
+    // Same key bytes read as secp256k1 do not verify
+    let result = json(&verify_cert(&vendor.pubkey.bytes, &cert, Some(NOW), None).unwrap());
+    assert_eq!(result["valid"], false);
+    let result =
+        json(&verify_cert(&vendor.pubkey.bytes, &cert, Some(NOW), Some("rsa".into())).unwrap());
+    assert_eq!(result["valid"], false);
+
+    let nonce = challenge().unwrap();
+    let sig = pop_prove(&user.privkey.bytes, &nonce, ed25519()).unwrap();
+    assert!(pop_verify(&user.pubkey.bytes, &nonce, &sig, ed25519()).unwrap());
+    assert!(pop_verify(&user.pubkey.bytes, &nonce, &sig, None).is_err());
+
+    let result = json(
+        &verify_license(&vendor.pubkey.bytes, &cert, &nonce, &sig, Some(NOW), p256()).unwrap(),
+    );
+    assert_eq!(result["valid"], true);
+}